@@ -0,0 +1,9 @@
+#version 330
+
+in vec4 shape_colour;
+out vec4 frag_colour;
+
+void main()
+{
+    frag_colour = shape_colour;
+}
\ No newline at end of file
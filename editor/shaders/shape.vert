@@ -0,0 +1,14 @@
+#version 330 core
+
+layout (location = 0) in vec2 vertex;
+layout (location = 1) in vec4 vertex_colour;
+
+out vec4 shape_colour;
+
+uniform mat4 projection;
+
+void main()
+{
+    shape_colour = vertex_colour;
+    gl_Position = projection * vec4(vertex.xy, 0.0, 1.0);
+}
\ No newline at end of file
@@ -6,9 +6,11 @@ out vec2 tex_coords;
 
 uniform mat4 model;
 uniform mat4 projection;
+// The offset and size of the part of the texture to draw
+uniform vec4 texture_region;
 
 void main()
 {
-    tex_coords = vertex.zw;
+    tex_coords = texture_region.xy + vertex.zw * texture_region.zw;
     gl_Position = projection * model * vec4(vertex.xy, 0.0, 1.0);
 }
\ No newline at end of file
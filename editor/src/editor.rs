@@ -6,7 +6,6 @@ use c2::prelude::*;
 use imgui::{ImStr, ImString};
 use imgui_opengl_renderer::Renderer as ImguiRenderer;
 use imgui_sdl2::ImguiSdl2 as ImguiSdl;
-use indexmap::IndexMap;
 use nfd::Response;
 use sdl2::{
     event::Event,
@@ -22,7 +21,7 @@ use sfml::{
     system::{SfBox, Time as SfmlTime},
 };
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{HashMap, HashSet, VecDeque},
     fs,
     path::{Path, PathBuf},
     process, str, thread,
@@ -35,6 +34,10 @@ use wee::*;
 use wee_common::{
     Colour, Flip, Rect, Size, Vec2, WeeResult, AABB, PROJECTION_HEIGHT, PROJECTION_WIDTH,
 };
+use wee_render::{
+    export::{self, ExportFormat, ExportSettings},
+    Replay,
+};
 
 const SMALL_BUTTON: [f32; 2] = [100.0, 50.0];
 const NORMAL_BUTTON: [f32; 2] = [200.0, 50.0];
@@ -49,9 +52,11 @@ const MIN_SCALE: f32 = 0.1;
 const MAX_SCALE: f32 = 4.0;
 const DEFAULT_FONT_SIZE: u16 = 128;
 const COLOUR_BUTTON_SIZE: f32 = 108.0;
+const HANDLE_SIZE: f32 = 12.0;
 
 const FPS: f32 = 60.0;
 const DEFAULT_GAME_SPEED: f32 = 1.0;
+const REWIND_BUFFER_FRAMES: usize = 60 * 10;
 
 struct Editor {
     filename: Option<String>,
@@ -64,6 +69,7 @@ struct Editor {
 impl Editor {
     fn reset(&mut self) {
         self.object_state.index = None;
+        self.object_state.shape_handle = None;
         self.instruction_state = InstructionState::default();
         self.animation_editor.reset();
     }
@@ -85,6 +91,7 @@ impl Default for Editor {
         };
         let object_state = ObjectState {
             index: None,
+            shape_handle: None,
             rename_object: None,
             new_object: SerialiseObject::default(),
             new_name_buffer: ImString::from("".to_string()),
@@ -118,7 +125,7 @@ pub fn run<'a, 'b>(
 
     let mut windows = Windows::default();
 
-    let mut preview = Preview::new(settings);
+    let mut preview = Preview::new(settings.clone());
 
     let mut last_frame = Instant::now();
 
@@ -195,19 +202,43 @@ pub fn run<'a, 'b>(
         if let FileTask::ReturnToMenu = file_task {
             break 'editor_running;
         }
+        if let FileTask::ExportPlaythrough = file_task {
+            windows.export_playthrough = true;
+        }
 
         let play_game = main_window_show(ui, &mut windows.main, &mut game, &mut preview);
 
         if play_game {
             log::debug!("difficulty: {}", preview.difficulty_level);
             let seed = RandomNumberGenerator::new().next_u64();
-            let mut game = LoadedGame::with_assets(game.clone(), assets, &font_system)?.start(
+            let masks = match &editor.filename {
+                Some(filename) => {
+                    CollisionMasks::load(&game, Path::new(filename).parent().unwrap())?
+                }
+                None => CollisionMasks::default(),
+            };
+            let mut game_data = game.clone();
+            if let Some(filename) = &editor.filename {
+                let directory = Path::new(filename).parent().unwrap();
+                match Translations::load(directory, &settings.language) {
+                    Ok(translations) => game_data.localise(&translations),
+                    Err(error) => log::error!("Couldn't read string tables: {}", error),
+                }
+            }
+            let played = game_data.clone();
+            let mut game = LoadedGame::with_assets(game_data, assets, &font_system)?.start(
                 preview.playback_rate,
                 preview.difficulty_level,
-                settings,
+                settings.clone(),
                 seed,
+                masks,
             );
-            let completion = game.preview(renderer, events);
+            let step_frames = if preview.debug_mode {
+                Some(preview.step_frames.max(1) as u32)
+            } else {
+                None
+            };
+            let completion = game.preview(renderer, events, step_frames);
 
             match completion {
                 Ok(outcome) => {
@@ -216,6 +247,8 @@ pub fn run<'a, 'b>(
                         let path = get_relative_file_path(&path);
                         let playthrough = Playthrough {
                             path,
+                            game: played,
+                            language: settings.language.clone(),
                             inputs: outcome.inputs,
                             difficulty: preview.difficulty_level,
                             seed,
@@ -231,6 +264,7 @@ pub fn run<'a, 'b>(
             }
             assets = game.assets;
             assets.music.stop();
+            renderer.window.set_title("Wee")?;
             renderer.exit_fullscreen(&events.mouse.utils)?;
         }
 
@@ -251,10 +285,26 @@ pub fn run<'a, 'b>(
                     WASD: Move the scene around\n \
                     + and - keys: zoom in/out\n \
                     Click Object: Switch to object\n \
-                    Click + Z Key: Object jumps to mouse"));
+                    Click + Z Key: Object jumps to mouse\n \
+                    Drag Collision Shape Points: Reshape (with Show Collision Areas on)\n\n\
+                    Debug Mode (while playing):\n\
+                    Space: Pause/resume\n \
+                    Right/Left: Step forward/back one frame\n \
+                    Page Down/Page Up: Step forward/back by Step Frames\n \
+                    Escape: Stop playing. The game waits on its last frame until then"));
                 });
         }
 
+        if windows.export_playthrough {
+            export_playthrough_window_show(
+                ui,
+                &mut windows.export_playthrough,
+                &mut preview,
+                &editor.filename,
+                &font_system.filename,
+            );
+        }
+
         if windows.demo {
             ui.show_demo_window(&mut windows.demo);
         }
@@ -314,6 +364,9 @@ pub fn run<'a, 'b>(
 
         let mouse_state = events.pump.mouse_state();
         mouse_button.update(mouse_state.left());
+        if !mouse_state.left() {
+            editor.object_state.shape_handle = None;
+        }
         let window_size = renderer.window.size();
         if !ui.io().want_capture_mouse && mouse_state.left() {
             let calc_mouse_position =
@@ -335,34 +388,55 @@ pub fn run<'a, 'b>(
 
             let is_pressed = |scancode| events.pump.keyboard_state().is_scancode_pressed(scancode);
 
+            let grabbed_handle = if mouse_button == ButtonState::Press && show_collision_areas {
+                editor.object_state.index.and_then(|i| {
+                    let object = &game.objects[i];
+                    let shape = object.collision_shape.as_ref()?;
+                    shape_handles(shape)
+                        .into_iter()
+                        .find(|(_, point)| {
+                            (object.shape_point_to_world(*point) - Vec2::new(x, y)).magnitude()
+                                <= HANDLE_SIZE / scene.scale
+                        })
+                        .map(|(handle, _)| handle)
+                })
+            } else {
+                None
+            };
+            if grabbed_handle.is_some() {
+                editor.object_state.shape_handle = grabbed_handle;
+            }
+
             if is_pressed(Scancode::P) || is_pressed(Scancode::Z) {
                 if let Some(i) = editor.object_state.index {
                     game.objects[i].position.x = x.floor();
                     game.objects[i].position.y = y.floor();
                 }
+            } else if let (Some(i), Some(handle)) =
+                (editor.object_state.index, editor.object_state.shape_handle)
+            {
+                let object = &mut game.objects[i];
+                let position = object.world_to_shape_point(Vec2::new(x, y));
+                let position = Vec2::new(position.x.floor(), position.y.floor());
+                if let Some(shape) = &mut object.collision_shape {
+                    move_shape_handle(shape, handle, position);
+                }
             } else if mouse_button == ButtonState::Press {
-                let mut layers: Vec<u8> = game.objects.iter().map(|o| o.layer).collect();
-                layers.sort_unstable();
-                layers.dedup();
-                layers.reverse();
-                for layer in layers.into_iter() {
-                    for (i, object) in game.objects.iter().enumerate() {
-                        if object.layer == layer {
-                            let poly = object.full_poly();
-                            let clicked = poly
-                                .gjk(&c2::Circle::new(c2::Vec2::new(x, y), 1.0))
-                                .use_radius(false)
-                                .run()
-                                .distance()
-                                == 0.0;
-                            if clicked {
-                                //log::info!("{}", object.name);
-
-                                editor.switch_to_object(i);
-
-                                break;
-                            }
-                        }
+                for i in game.draw_order().into_iter().rev() {
+                    let object = &game.objects[i];
+                    let poly = object.full_poly();
+                    let clicked = poly
+                        .gjk(&c2::Circle::new(c2::Vec2::new(x, y), 1.0))
+                        .use_radius(false)
+                        .run()
+                        .distance()
+                        == 0.0;
+                    if clicked {
+                        //log::info!("{}", object.name);
+
+                        editor.switch_to_object(i);
+
+                        break;
                     }
                 }
             }
@@ -383,37 +457,17 @@ pub fn run<'a, 'b>(
             editor.draw_tasks.push(DrawTask::Border(model));
 
             if show_collision_areas {
-                // TODO: Remove into_object
-                let game_object = object
-                    .clone()
-                    .into_object(&mut EditorRng::from_rng(RandomNumberGenerator::new()));
-
-                let aabb = game_object.collision_aabb();
-                let mut origin = game_object.origin();
-                if let Some(area) = game_object.collision_area {
-                    let mut diff = area.min;
-                    if object.flip.horizontal {
-                        diff.x = object.size.width - area.max.x;
+                editor.draw_tasks.push(DrawTask::Shape(
+                    object.collision_outline(),
+                    Colour::rgba(1.0, 0.0, 0.0, 0.5),
+                ));
+                if let Some(shape) = &object.collision_shape {
+                    for (_, point) in shape_handles(shape) {
+                        editor
+                            .draw_tasks
+                            .push(DrawTask::Handle(object.shape_point_to_world(point)));
                     }
-                    if object.flip.vertical {
-                        diff.y = object.size.height - area.max.y;
-                    }
-                    origin = Vec2::new(origin.x - diff.x, origin.y - diff.y);
                 }
-                let rect = aabb
-                    .to_rect()
-                    .move_position(scene.position)
-                    .scale(scene.scale);
-                let model = Model::new(
-                    rect,
-                    Some(origin * scene.scale),
-                    object.angle,
-                    Flip::default(),
-                );
-
-                editor
-                    .draw_tasks
-                    .push(DrawTask::Model(model, Colour::rgba(1.0, 0.0, 0.0, 0.5)));
             }
             if show_origins {
                 let origin = object.origin_in_world();
@@ -451,7 +505,7 @@ pub fn run<'a, 'b>(
 
         renderer.draw_editor_background(&game.background, &assets.images, scene)?;
 
-        renderer.draw_editor_objects(&game.objects, &assets.images, scene)?;
+        renderer.draw_editor_objects(&game, &assets.images, scene)?;
 
         renderer.draw_tasks(editor.draw_tasks, scene);
         editor.draw_tasks = Vec::new();
@@ -478,7 +532,6 @@ fn load_game_data(filename: impl AsRef<Path>) -> WeeResult<GameData> {
     json_from_str(&json_string)
 }
 
-type Objects = IndexMap<String, Object>;
 type Images = HashMap<String, Texture>;
 type Sounds = HashMap<String, SfBox<SoundBuffer>>;
 type Fonts<'a, 'b> = HashMap<String, Font<'a, 'b>>;
@@ -638,86 +691,107 @@ impl ImageList for Images {
     }
 }
 
-trait RenderScene {
-    fn draw_background(&self, background: &[BackgroundPart], images: &Images) -> WeeResult<()>;
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct TextKey {
+    font: String,
+    text: String,
+    colour: [u8; 4],
+}
+
+impl TextKey {
+    fn new(font: &str, text: &str, colour: Colour) -> TextKey {
+        let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+        TextKey {
+            font: font.to_string(),
+            text: text.to_string(),
+            colour: [
+                channel(colour.r),
+                channel(colour.g),
+                channel(colour.b),
+                channel(colour.a),
+            ],
+        }
+    }
+}
+
+type TextTextures = HashMap<TextKey, Texture>;
+
+struct TtfMetrics<'f, 'a, 'b>(&'f Font<'a, 'b>);
+
+impl<'f, 'a, 'b> FontMetrics for TtfMetrics<'f, 'a, 'b> {
+    fn text_width(&self, text: &str) -> f32 {
+        self.0.size_of(text).map_or(0.0, |(width, _)| width as f32)
+    }
+
+    fn line_height(&self) -> f32 {
+        self.0.height() as f32
+    }
+}
 
-    fn draw_objects(
+trait RenderScene {
+    fn draw_commands(
         &self,
-        objects: &Objects,
+        commands: &[DrawCommand],
         images: &Images,
-        drawn_over_text: &HashMap<String, Texture>,
+        text_textures: &TextTextures,
+        intro_text: &IntroText,
     ) -> WeeResult<()>;
 }
 
 impl RenderScene for Renderer {
-    fn draw_background(&self, background: &[BackgroundPart], images: &Images) -> WeeResult<()> {
-        for part in background.iter() {
-            match &part.sprite {
-                Sprite::Image { name } => {
+    fn draw_commands(
+        &self,
+        commands: &[DrawCommand],
+        images: &Images,
+        text_textures: &TextTextures,
+        intro_text: &IntroText,
+    ) -> WeeResult<()> {
+        for command in commands {
+            match command {
+                DrawCommand::Image {
+                    name,
+                    region,
+                    transform,
+                } => {
                     let texture = images.get_image(name)?;
+                    let source = region.map(|region| region.to_aabb());
 
-                    self.prepare(&texture).set_dest(part.area.to_rect()).draw();
+                    self.draw_texture_corners(texture, source, transform.corners());
                 }
-                Sprite::Colour(colour) => {
-                    let model = Model::new(part.area.to_rect(), None, 0.0, Flip::default());
+                DrawCommand::Rectangle { colour, transform } => {
+                    let model = Model::from_corners(transform.corners());
 
                     self.fill_rectangle(model, *colour);
                 }
-            }
-        }
-        Ok(())
-    }
-
-    fn draw_objects(
-        &self,
-        objects: &Objects,
-        images: &Images,
-        drawn_over_text: &HashMap<String, Texture>,
-    ) -> WeeResult<()> {
-        let mut layers: Vec<u8> = objects.values().map(|o| o.layer).collect();
-        layers.sort_unstable();
-        layers.dedup();
-        layers.reverse();
-        for layer in layers.into_iter() {
-            for (key, object) in objects.iter() {
-                if object.layer == layer {
-                    match &object.sprite {
-                        Sprite::Image { name: image_name } => {
-                            let texture = images.get_image(image_name)?;
-                            let dest = object.rect();
-
-                            self.prepare(&texture)
-                                .set_dest(dest)
-                                .set_angle(object.angle)
-                                .set_origin(object.origin)
-                                .flip(object.flip)
-                                .draw();
-                        }
-                        Sprite::Colour(colour) => {
-                            let model = Model::new(
-                                Rect::new(
-                                    object.position.x,
-                                    object.position.y,
-                                    object.size.width,
-                                    object.size.height,
-                                ),
-                                object.origin,
-                                object.angle,
-                                object.flip,
-                            );
+                DrawCommand::Triangles { vertices } => {
+                    let vertices: Vec<(Vec2, Colour)> = vertices
+                        .iter()
+                        .map(|vertex| (vertex.position, vertex.colour))
+                        .collect();
 
-                            self.fill_rectangle(model, *colour);
-                        }
+                    self.fill_triangles(&vertices);
+                }
+                DrawCommand::Text {
+                    text,
+                    font,
+                    colour,
+                    transform,
+                    ..
+                } => {
+                    if let Some(texture) = text_textures.get(&TextKey::new(font, text, *colour)) {
+                        self.draw_texture_corners(texture, None, transform.corners());
                     }
-                    if drawn_over_text.contains_key(key) {
-                        self.prepare(&drawn_over_text[key])
-                            .set_dest(object.rect())
-                            .set_angle(object.angle)
-                            .set_origin(object.origin)
-                            .flip(object.flip)
-                            .draw();
+                }
+                DrawCommand::IntroText { centre, .. } => {
+                    for text in intro_text.iter().flatten() {
+                        let size = text.size();
+                        let dest = Rect::new(centre.x, centre.y, size.width, size.height);
+
+                        self.prepare(text).set_dest(dest).draw();
                     }
                 }
+                DrawCommand::Invert => self.invert_screen(),
+                DrawCommand::Cover { colour } => self.cover_screen(*colour),
             }
         }
         Ok(())
@@ -791,6 +865,7 @@ impl<'a, 'b> LoadedGame<'a, 'b> {
         difficulty: u32,
         settings: GameSettings,
         seed: u64,
+        masks: CollisionMasks,
     ) -> GameContainer<'a, 'b, 'c> {
         let mut assets = self.assets;
 
@@ -801,6 +876,7 @@ impl<'a, 'b> LoadedGame<'a, 'b> {
         let mut game = Game::from_data(self.game_data, &mut rng).unwrap();
 
         game.difficulty = difficulty;
+        game.set_collision_masks(masks);
 
         GameContainer {
             game,
@@ -809,7 +885,8 @@ impl<'a, 'b> LoadedGame<'a, 'b> {
             frame_start_time: Instant::now(),
             mouse: Mouse::default(),
             playing_sounds: Vec::new(),
-            drawn_over_text: HashMap::new(),
+            text_textures: HashMap::new(),
+            drawn_text: HashMap::new(),
             playback_rate,
             settings,
             initial_mouse_button_held: true,
@@ -819,6 +896,7 @@ impl<'a, 'b> LoadedGame<'a, 'b> {
     }
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 struct EditorRng(RandomNumberGenerator);
 
 impl EditorRng {
@@ -858,7 +936,8 @@ struct GameContainer<'a, 'b, 'c> {
     frame_start_time: Instant,
     mouse: Mouse,
     playing_sounds: Vec<Sound<'c>>,
-    pub drawn_over_text: HashMap<String, Texture>,
+    text_textures: TextTextures,
+    drawn_text: HashMap<String, DrawnText>,
     playback_rate: f32,
     settings: GameSettings,
     initial_mouse_button_held: bool,
@@ -898,17 +977,38 @@ impl<'a, 'b, 'c> GameContainer<'a, 'b, 'c> {
         &mut self,
         renderer: &mut Renderer,
         events: &mut EventState,
+        step_frames: Option<u32>,
     ) -> WeeResult<PreviewOutcome> {
         let mut inputs = Vec::new();
         let mut escape = ButtonState::Up;
+        let mut debugger = step_frames.map(Debugger::new);
         self.initial_mouse_button_held = events.pump.mouse_state().left();
         'game_running: loop {
-            if self.is_finished() {
-                break 'game_running;
+            if self.is_finished() || self.end_early {
+                match &mut debugger {
+                    // Stays on the last frame so it can be rewound until Escape is pressed
+                    Some(debugger) => {
+                        if !debugger.paused {
+                            debugger.paused = true;
+                            self.pause_audio();
+                        }
+                    }
+                    None => break 'game_running,
+                }
             }
 
             self.init_frame();
 
+            if let Some(debugger) = &mut debugger {
+                if sdlglue::has_quit(&mut events.pump) {
+                    process::exit(0);
+                }
+                self.game.frames.to_run = self.debug_frames_to_run(debugger, &mut inputs, events);
+                renderer
+                    .window
+                    .set_title(&debugger.title(self.game.frames.ran))?;
+            }
+
             let esc_down = |event_pump: &EventPump| {
                 event_pump
                     .keyboard_state()
@@ -917,12 +1017,7 @@ impl<'a, 'b, 'c> GameContainer<'a, 'b, 'c> {
             for _ in 0..self.game.frames.to_run {
                 escape.update(esc_down(&events.pump));
                 if escape == ButtonState::Press {
-                    if let Some(music) = &mut self.assets.music {
-                        music.data.pause();
-                    }
-                    for sound in &mut self.playing_sounds {
-                        sound.pause();
-                    }
+                    self.pause_audio();
 
                     return Ok(PreviewOutcome {
                         status: Completion::Quit,
@@ -933,9 +1028,16 @@ impl<'a, 'b, 'c> GameContainer<'a, 'b, 'c> {
                 renderer.adjust_fullscreen(&events.pump, &events.mouse.utils)?;
 
                 if self.end_early {
+                    if debugger.is_some() {
+                        break;
+                    }
                     break 'game_running;
                 }
 
+                if let Some(debugger) = &mut debugger {
+                    debugger.record(self);
+                }
+
                 self.update_frame(events, renderer.window.size())?;
                 inputs.push(self.mouse);
                 if self.settings.render_each_frame {
@@ -943,7 +1045,21 @@ impl<'a, 'b, 'c> GameContainer<'a, 'b, 'c> {
                 }
             }
 
-            if !self.settings.render_each_frame {
+            if self.game.frames.to_run == 0 {
+                escape.update(esc_down(&events.pump));
+                if escape == ButtonState::Press {
+                    self.pause_audio();
+
+                    return Ok(PreviewOutcome {
+                        status: Completion::Quit,
+                        inputs,
+                        has_been_won: self.has_been_won(),
+                    });
+                }
+                renderer.adjust_fullscreen(&events.pump, &events.mouse.utils)?;
+            }
+
+            if !self.settings.render_each_frame || self.game.frames.to_run == 0 {
                 self.render_frame(renderer, events.mouse.position)?;
             }
 
@@ -959,6 +1075,82 @@ impl<'a, 'b, 'c> GameContainer<'a, 'b, 'c> {
         })
     }
 
+    fn debug_frames_to_run(
+        &mut self,
+        debugger: &mut Debugger,
+        inputs: &mut Vec<Mouse>,
+        events: &EventState,
+    ) -> u32 {
+        debugger.update_keys(&events.pump);
+
+        // A finished game stays paused on its last frame until it's rewound or stopped
+        let finished = self.is_finished() || self.end_early;
+        if debugger.pause == ButtonState::Press && !(finished && debugger.paused) {
+            debugger.paused = !debugger.paused;
+            if debugger.paused {
+                self.pause_audio();
+            } else {
+                self.resume_audio();
+            }
+        }
+
+        if !debugger.paused {
+            return self.clamp_to_remaining(self.game.frames.to_run);
+        }
+
+        let frames_to_rewind = if debugger.rewind == ButtonState::Press {
+            1
+        } else if debugger.rewind_many == ButtonState::Press {
+            debugger.step_frames
+        } else {
+            0
+        };
+        if frames_to_rewind > 0 {
+            for _ in 0..frames_to_rewind {
+                if let Some(frame) = debugger.history.pop_back() {
+                    frame.restore(self);
+                }
+            }
+            inputs.truncate(self.game.frames.ran as usize);
+        }
+
+        let frames_to_step = if self.end_early {
+            0
+        } else if debugger.step == ButtonState::Press {
+            1
+        } else if debugger.step_many == ButtonState::Press {
+            debugger.step_frames
+        } else {
+            0
+        };
+        self.clamp_to_remaining(frames_to_step)
+    }
+
+    fn clamp_to_remaining(&self, frames: u32) -> u32 {
+        match self.game.frames.remaining() {
+            FrameCount::Frames(remaining) => frames.min(remaining),
+            FrameCount::Infinite => frames,
+        }
+    }
+
+    fn pause_audio(&mut self) {
+        if let Some(music) = &mut self.assets.music {
+            music.data.pause();
+        }
+        for sound in &mut self.playing_sounds {
+            sound.pause();
+        }
+    }
+
+    fn resume_audio(&mut self) {
+        if let Some(music) = &mut self.assets.music {
+            music.data.play();
+        }
+        for sound in &mut self.playing_sounds {
+            sound.play();
+        }
+    }
+
     fn sleep(&self) {
         let elapsed = self.frame_start_time.elapsed().as_nanos();
         let sleep_time = (1_000_000_000u32 / FPS as u32) as u128;
@@ -996,30 +1188,26 @@ impl<'a, 'b, 'c> GameContainer<'a, 'b, 'c> {
                     self.end_early = true;
                 }
                 WorldAction::DrawText { name, text } => {
-                    let left_before = self.game.objects[&name].position.x
-                        - self.game.objects[&name].size.width / 2.0;
-                    let texture =
-                        Texture::text(&self.assets.fonts[&text.font], &text.text, text.colour)?;
-                    match texture {
-                        Some(texture) => {
-                            self.game.objects[&name].size = Size {
-                                width: texture.width as f32,
-                                height: texture.height as f32,
-                            };
-                            self.drawn_over_text.insert(name.to_string(), texture);
-                        }
-                        None => {
-                            self.drawn_over_text.remove(&name);
+                    if text.resize == TextResize::MatchText && !text.text.is_empty() {
+                        let metrics = TtfMetrics(&self.assets.fonts[&text.font]);
+                        let object = &self.game.objects[&name];
+                        let area = AABB::new(0.0, 0.0, object.size.width, object.size.height);
+                        let size = text.lay_out(area, &metrics).size();
+                        let half_change = (object.size.width - size.width) / 2.0;
+                        // Justified text keeps the edge it's justified to where it was
+                        let offset = match text.justify {
+                            JustifyText::Left => -half_change,
+                            JustifyText::Centre => 0.0,
+                            JustifyText::Right => half_change,
+                        };
+                        let position = object.position + Vec2::new(offset, 0.0);
+                        self.game.objects[&name].size = size;
+                        if offset != 0.0 {
+                            let motion = Motion::JumpTo(JumpLocation::Point(position));
+                            self.game.objects[&name].queued_motion.push(motion);
                         }
                     }
-                    if let JustifyText::Left = text.justify {
-                        let left_now = self.game.objects[&name].position.x
-                            - self.game.objects[&name].size.width / 2.0;
-                        let position = self.game.objects[&name].position;
-                        let offset = Vec2::new(left_before - left_now, 0.0);
-                        let motion = Motion::JumpTo(JumpLocation::Point(position + offset));
-                        self.game.objects[&name].queued_motion.push(motion);
-                    }
+                    self.drawn_text.insert(name, text);
                 }
             }
         }
@@ -1029,22 +1217,24 @@ impl<'a, 'b, 'c> GameContainer<'a, 'b, 'c> {
         Ok(())
     }
 
-    fn render_frame(&self, renderer: &Renderer, mouse_position: Vec2) -> WeeResult<()> {
+    fn render_frame(&mut self, renderer: &Renderer, mouse_position: Vec2) -> WeeResult<()> {
         sdlglue::clear_screen(Colour::white());
 
-        renderer.draw_background(&self.game.background, &self.assets.images)?;
-        renderer.draw_objects(
-            &self.game.objects,
+        let effects = self.game.screen_effects();
+        renderer.set_offset(effects.offset);
+
+        let fonts = &self.assets.fonts;
+        let commands = self
+            .game
+            .draw_commands(&self.drawn_text, |font| fonts.get(font).map(TtfMetrics));
+        self.update_text_textures(&commands)?;
+        renderer.draw_commands(
+            &commands,
             &self.assets.images,
-            &self.drawn_over_text,
+            &self.text_textures,
+            &self.intro_text,
         )?;
-
-        const INTRO_TEXT_TIME: u32 = 60;
-        if self.game.frames.ran < INTRO_TEXT_TIME {
-            for text in self.intro_text.iter().flatten() {
-                renderer.prepare(text).draw();
-            }
-        }
+        renderer.set_offset(Vec2::zero());
 
         renderer.draw_mouse(mouse_position);
         renderer.present();
@@ -1052,6 +1242,27 @@ impl<'a, 'b, 'c> GameContainer<'a, 'b, 'c> {
         Ok(())
     }
 
+    // Each line of text in each colour gets a texture, which is kept while it's still drawn
+    fn update_text_textures(&mut self, commands: &[DrawCommand]) -> WeeResult<()> {
+        let mut drawn = HashSet::new();
+        for command in commands {
+            if let DrawCommand::Text {
+                text, font, colour, ..
+            } = command
+            {
+                let key = TextKey::new(font, text, *colour);
+                if !self.text_textures.contains_key(&key) {
+                    if let Some(texture) = Texture::text(&self.assets.fonts[font], text, *colour)? {
+                        self.text_textures.insert(key.clone(), texture);
+                    }
+                }
+                drawn.insert(key);
+            }
+        }
+        self.text_textures.retain(|key, _| drawn.contains(key));
+        Ok(())
+    }
+
     fn play_sounds(&mut self, played_sounds: &[String], volume: f32) -> WeeResult<()> {
         play_sounds(
             &mut self.playing_sounds,
@@ -1201,6 +1412,7 @@ pub struct FontSystem<'a, 'b> {
     main: Font<'a, 'b>,
     outline: Option<Font<'a, 'b>>,
     pub ttf_context: &'a TtfContext,
+    pub filename: String,
 }
 
 impl<'a, 'b> FontSystem<'a, 'b> {
@@ -1225,6 +1437,7 @@ impl<'a, 'b> FontSystem<'a, 'b> {
             main,
             outline,
             ttf_context,
+            filename: intro_font.info.filename.clone(),
         })
     }
 }
@@ -1235,21 +1448,107 @@ pub struct IntroFontConfig {
     outline_width: Option<u16>,
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone)]
 pub struct GameSettings {
     pub volume: f32,
     pub render_each_frame: bool,
+    // Previews translate text with keys into this language
+    pub language: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct Playthrough {
     path: String,
+    // The game as it was played, so later edits don't change what's exported
+    #[serde(skip)]
+    game: GameData,
+    #[serde(skip)]
+    language: String,
     inputs: Vec<Mouse>,
     difficulty: u32,
     seed: u64,
     has_been_won: bool,
 }
 
+struct DebugFrame {
+    snapshot: Snapshot<EditorRng>,
+    mouse: Mouse,
+    end_early: bool,
+    drawn_text: HashMap<String, DrawnText>,
+}
+
+impl DebugFrame {
+    fn restore(self, container: &mut GameContainer) {
+        self.snapshot
+            .restore(&mut container.game, &mut container.rng);
+        container.mouse = self.mouse;
+        container.end_early = self.end_early;
+        container.drawn_text = self.drawn_text;
+    }
+}
+
+struct Debugger {
+    paused: bool,
+    step_frames: u32,
+    history: VecDeque<DebugFrame>,
+    pause: ButtonState,
+    step: ButtonState,
+    step_many: ButtonState,
+    rewind: ButtonState,
+    rewind_many: ButtonState,
+}
+
+impl Debugger {
+    fn new(step_frames: u32) -> Debugger {
+        Debugger {
+            paused: false,
+            step_frames,
+            history: VecDeque::with_capacity(REWIND_BUFFER_FRAMES),
+            pause: ButtonState::Up,
+            step: ButtonState::Up,
+            step_many: ButtonState::Up,
+            rewind: ButtonState::Up,
+            rewind_many: ButtonState::Up,
+        }
+    }
+
+    fn update_keys(&mut self, event_pump: &EventPump) {
+        let keyboard = event_pump.keyboard_state();
+        self.pause
+            .update(keyboard.is_scancode_pressed(Scancode::Space));
+        self.step
+            .update(keyboard.is_scancode_pressed(Scancode::Right));
+        self.step_many
+            .update(keyboard.is_scancode_pressed(Scancode::PageDown));
+        self.rewind
+            .update(keyboard.is_scancode_pressed(Scancode::Left));
+        self.rewind_many
+            .update(keyboard.is_scancode_pressed(Scancode::PageUp));
+    }
+
+    fn record(&mut self, container: &GameContainer) {
+        if self.history.len() == REWIND_BUFFER_FRAMES {
+            self.history.pop_front();
+        }
+        self.history.push_back(DebugFrame {
+            snapshot: Snapshot::take(&container.game, &container.rng),
+            mouse: container.mouse,
+            end_early: container.end_early,
+            drawn_text: container.drawn_text.clone(),
+        });
+    }
+
+    fn title(&self, frame: u32) -> String {
+        let status = if self.paused { "Paused" } else { "Running" };
+        format!(
+            "Wee - {} - Frame {} ({} frames to rewind)",
+            status,
+            frame,
+            self.history.len()
+        )
+    }
+}
+
 #[derive(Debug)]
 struct PreviewOutcome {
     status: Completion,
@@ -1355,12 +1654,44 @@ trait RenderEditor {
 
     fn draw_editor_objects(
         &self,
-        objects: &[SerialiseObject],
+        game: &GameData,
         images: &Images,
         location: SceneLocation,
     ) -> WeeResult<()>;
 
     fn draw_tasks(&self, draw_tasks: Vec<DrawTask>, location: SceneLocation);
+
+    fn draw_editor_shape(
+        &self,
+        shape: &VectorSprite,
+        transform: SpriteTransform,
+        location: SceneLocation,
+    );
+
+    fn draw_editor_nine_slice(
+        &self,
+        texture: &Texture,
+        rect: ImageRect,
+        insets: SliceInsets,
+        transform: SpriteTransform,
+        location: SceneLocation,
+    );
+}
+
+// Where an object is drawn in the game, before the editor's scene is moved and scaled
+fn object_transform(object: &SerialiseObject) -> SpriteTransform {
+    let top_left = object.position - Vec2::new(object.size.width, object.size.height) / 2.0;
+    SpriteTransform {
+        area: AABB::new(
+            top_left.x,
+            top_left.y,
+            top_left.x + object.size.width,
+            top_left.y + object.size.height,
+        ),
+        angle: object.angle,
+        pivot: object.origin_in_world(),
+        flip: object.flip,
+    }
 }
 
 impl RenderEditor for Renderer {
@@ -1393,11 +1724,32 @@ impl RenderEditor for Renderer {
 
                     self.prepare(&texture).set_dest(dest).draw();
                 }
+                Sprite::Region { image, rect } => {
+                    let texture = images.get_image(image)?;
+
+                    self.prepare(&texture)
+                        .set_dest(dest)
+                        .set_source(Some(rect.to_aabb()))
+                        .draw();
+                }
                 Sprite::Colour(colour) => {
                     let model = Model::new(dest, None, 0.0, Flip::default());
 
                     self.fill_rectangle(model, *colour);
                 }
+                Sprite::Shape(shape) => {
+                    self.draw_editor_shape(shape, SpriteTransform::new(part.area), location)
+                }
+                Sprite::NineSlice {
+                    image,
+                    rect,
+                    insets,
+                } => {
+                    let texture = images.get_image(image)?;
+                    let transform = SpriteTransform::new(part.area);
+
+                    self.draw_editor_nine_slice(texture, *rect, *insets, transform, location);
+                }
             }
         }
         Ok(())
@@ -1405,47 +1757,57 @@ impl RenderEditor for Renderer {
 
     fn draw_editor_objects(
         &self,
-        objects: &[SerialiseObject],
+        game: &GameData,
         images: &Images,
         location: SceneLocation,
     ) -> WeeResult<()> {
-        let mut layers: Vec<u8> = objects.iter().map(|o| o.layer).collect();
-        layers.sort_unstable();
-        layers.dedup();
-        layers.reverse();
-        for layer in layers.into_iter() {
-            for object in objects.iter() {
-                if object.layer == layer {
-                    let dest = Rect::new(
-                        object.position.x,
-                        object.position.y,
-                        object.size.width,
-                        object.size.height,
-                    )
-                    .move_position(location.position)
-                    .scale(location.scale);
-                    match &object.sprite {
-                        Sprite::Image { name: image_name } => {
-                            let texture = images.get_image(image_name)?;
-
-                            self.prepare(&texture)
-                                .set_dest(dest)
-                                .set_angle(object.angle)
-                                .set_origin(Some(object.origin() * location.scale))
-                                .flip(object.flip)
-                                .draw();
-                        }
-                        Sprite::Colour(colour) => {
-                            let model = Model::new(
-                                dest,
-                                Some(object.origin() * location.scale),
-                                object.angle,
-                                object.flip,
-                            );
+        for index in game.draw_order() {
+            let object = &game.objects[index];
+            let dest = Rect::new(
+                object.position.x,
+                object.position.y,
+                object.size.width,
+                object.size.height,
+            )
+            .move_position(location.position)
+            .scale(location.scale);
+            let draw_image = |image_name: &str, source: Option<AABB>| -> WeeResult<()> {
+                let texture = images.get_image(image_name)?;
+
+                self.prepare(&texture)
+                    .set_dest(dest)
+                    .set_source(source)
+                    .set_angle(object.angle)
+                    .set_origin(Some(object.origin() * location.scale))
+                    .flip(object.flip)
+                    .draw();
+                Ok(())
+            };
+            match &object.sprite {
+                Sprite::Image { name } => draw_image(name, None)?,
+                Sprite::Region { image, rect } => draw_image(image, Some(rect.to_aabb()))?,
+                Sprite::Colour(colour) => {
+                    let model = Model::new(
+                        dest,
+                        Some(object.origin() * location.scale),
+                        object.angle,
+                        object.flip,
+                    );
 
-                            self.fill_rectangle(model, *colour);
-                        }
-                    }
+                    self.fill_rectangle(model, *colour);
+                }
+                Sprite::Shape(shape) => {
+                    self.draw_editor_shape(shape, object_transform(object), location);
+                }
+                Sprite::NineSlice {
+                    image,
+                    rect,
+                    insets,
+                } => {
+                    let texture = images.get_image(image)?;
+                    let transform = object_transform(object);
+
+                    self.draw_editor_nine_slice(texture, *rect, *insets, transform, location);
                 }
             }
         }
@@ -1469,6 +1831,20 @@ impl RenderEditor for Renderer {
                 DrawTask::Model(model, colour) => {
                     self.fill_rectangle(model, colour);
                 }
+                DrawTask::Shape(points, colour) => {
+                    let points: Vec<Vec2> = points
+                        .into_iter()
+                        .map(|point| (point + location.position) * location.scale)
+                        .collect();
+                    self.fill_polygon(&points, colour);
+                    self.draw_polygon_lines(&points, Colour::rgba(1.0, 0.0, 0.0, 0.8));
+                }
+                DrawTask::Handle(point) => {
+                    let point = (point + location.position) * location.scale;
+                    let rect = Rect::new(point.x, point.y, HANDLE_SIZE, HANDLE_SIZE);
+                    let model = Model::new(rect, None, 0.0, Flip::default());
+                    self.fill_rectangle(model, Colour::rgba(1.0, 1.0, 1.0, 0.8));
+                }
                 DrawTask::Point(point) => {
                     let rect = Rect::new(point.x, point.y, 20.0, 20.0)
                         .move_position(location.position)
@@ -1479,6 +1855,40 @@ impl RenderEditor for Renderer {
             }
         }
     }
+
+    fn draw_editor_shape(
+        &self,
+        shape: &VectorSprite,
+        transform: SpriteTransform,
+        location: SceneLocation,
+    ) {
+        let vertices: Vec<(Vec2, Colour)> = shape
+            .triangles(&transform)
+            .into_iter()
+            .map(|vertex| {
+                let position = (vertex.position + location.position) * location.scale;
+                (position, vertex.colour)
+            })
+            .collect();
+        self.fill_triangles(&vertices);
+    }
+
+    fn draw_editor_nine_slice(
+        &self,
+        texture: &Texture,
+        rect: ImageRect,
+        insets: SliceInsets,
+        transform: SpriteTransform,
+        location: SceneLocation,
+    ) {
+        for (region, transform) in transform.nine_slice(rect, insets) {
+            let corners = transform
+                .corners()
+                .map(|corner| (corner + location.position) * location.scale);
+
+            self.draw_texture_corners(texture, Some(region.to_aabb()), corners);
+        }
+    }
 }
 
 fn choose_difficulty_level(level: &mut u32, ui: &imgui::Ui) {
@@ -1588,6 +1998,13 @@ fn main_window_show(
                 } else {
                     Some(intro_text.to_string())
                 };
+                choose_text_key(&mut game.intro_text_key, ui, im_str!("Intro Text Key"));
+
+                choose_layer_sorting(&mut game.layer_sorting, &game.objects, ui);
+                ui.checkbox(
+                    im_str!("Only Topmost Object Reacts To Mouse"),
+                    &mut game.topmost_mouse_over,
+                );
 
                 imgui::Slider::new(
                     im_str!("Playback rate"),
@@ -1598,6 +2015,13 @@ fn main_window_show(
 
                 choose_difficulty_level(&mut preview.difficulty_level, ui);
 
+                ui.checkbox(im_str!("Debug Mode"), &mut preview.debug_mode);
+                if preview.debug_mode {
+                    ui.input_int(im_str!("Step Frames"), &mut preview.step_frames)
+                        .build();
+                    preview.step_frames = preview.step_frames.max(1);
+                }
+
                 if let Some(playthrough) = &preview.last_playthrough {
                     let win_status = if playthrough.has_been_won {
                         "Won"
@@ -1630,6 +2054,7 @@ fn assets_path(filename: &Option<String>, assets_directory: &str) -> PathBuf {
 }
 
 struct GameNotes<'a> {
+    objects: &'a [SerialiseObject],
     object_names: Vec<&'a str>,
     sprites: HashMap<&'a str, &'a Sprite>,
     length: Length,
@@ -1671,6 +2096,7 @@ fn objects_window_show<'a>(
                     let sprites = game.objects.sprites();
 
                     let game_notes = GameNotes {
+                        objects: &game.objects,
                         object_names,
                         sprites,
                         length: game.length,
@@ -1956,6 +2382,183 @@ fn choose_collision_area(area: &mut AABB, ui: &imgui::Ui) -> bool {
             .build()
 }
 
+fn choose_collision_shape(object: &mut SerialiseObject, ui: &imgui::Ui) {
+    let shape_types = [
+        im_str!("Object Size"),
+        im_str!("Rectangle"),
+        im_str!("Circle"),
+        im_str!("Capsule"),
+        im_str!("Polygon"),
+    ];
+    let mut current_shape_position = match object.collision_shape {
+        None => 0,
+        Some(CollisionShape::AABB(_)) => 1,
+        Some(CollisionShape::Circle { .. }) => 2,
+        Some(CollisionShape::Capsule { .. }) => 3,
+        Some(CollisionShape::Polygon { .. }) => 4,
+    };
+    if imgui::ComboBox::new(im_str!("Collision Shape")).build_simple_string(
+        ui,
+        &mut current_shape_position,
+        &shape_types,
+    ) {
+        let Size { width, height } = object.size;
+        let radius = width.min(height) / 2.0;
+        object.collision_shape = match current_shape_position {
+            0 => None,
+            1 => Some(CollisionShape::AABB(AABB::new(0.0, 0.0, width, height))),
+            2 => Some(CollisionShape::Circle {
+                centre: Vec2::new(width / 2.0, height / 2.0),
+                radius,
+            }),
+            3 => Some(if width >= height {
+                CollisionShape::Capsule {
+                    start: Vec2::new(radius, height / 2.0),
+                    end: Vec2::new(width - radius, height / 2.0),
+                    radius,
+                }
+            } else {
+                CollisionShape::Capsule {
+                    start: Vec2::new(width / 2.0, radius),
+                    end: Vec2::new(width / 2.0, height - radius),
+                    radius,
+                }
+            }),
+            4 => Some(CollisionShape::Polygon {
+                points: vec![
+                    Vec2::new(width / 2.0, 0.0),
+                    Vec2::new(width, height),
+                    Vec2::new(0.0, height),
+                ],
+            }),
+            _ => unreachable!(),
+        };
+    }
+    if ui.is_item_hovered() {
+        ui.tooltip(|| {
+            ui.text("Turn on Show Collision Areas to drag the shape's points in the scene")
+        });
+    }
+
+    let size = object.size;
+    match &mut object.collision_shape {
+        None => {}
+        Some(CollisionShape::AABB(area)) => {
+            choose_collision_area(area, ui);
+        }
+        Some(CollisionShape::Circle { centre, radius }) => {
+            ui.input_float(im_str!("Centre X"), &mut centre.x).build();
+            ui.input_float(im_str!("Centre Y"), &mut centre.y).build();
+            ui.input_float(im_str!("Radius"), radius).build();
+        }
+        Some(CollisionShape::Capsule { start, end, radius }) => {
+            ui.input_float(im_str!("Start X"), &mut start.x).build();
+            ui.input_float(im_str!("Start Y"), &mut start.y).build();
+            ui.input_float(im_str!("End X"), &mut end.x).build();
+            ui.input_float(im_str!("End Y"), &mut end.y).build();
+            ui.input_float(im_str!("Radius"), radius).build();
+        }
+        Some(CollisionShape::Polygon { points }) => {
+            let mut remove = None;
+            for (i, point) in points.iter_mut().enumerate() {
+                let stack = ui.push_id(i as i32);
+                ui.input_float(im_str!("Point X"), &mut point.x).build();
+                ui.input_float(im_str!("Point Y"), &mut point.y).build();
+                if ui.small_button(im_str!("Delete Point")) {
+                    remove = Some(i);
+                }
+                stack.pop(ui);
+            }
+            if let Some(i) = remove {
+                points.remove(i);
+            }
+            if points.len() < MAX_POLYGON_POINTS && ui.small_button(im_str!("Add Point")) {
+                let point = points
+                    .last()
+                    .copied()
+                    .unwrap_or_else(|| Vec2::new(size.width, size.height) / 2.0);
+                points.push(point);
+            }
+            if points.len() < 3 {
+                ui.text("A polygon needs at least 3 points, until then the object size is used");
+            }
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum ShapeHandle {
+    Min,
+    Max,
+    Centre,
+    Radius,
+    Start,
+    End,
+    Point(usize),
+}
+
+fn capsule_normal(start: Vec2, end: Vec2) -> Vec2 {
+    let direction = end - start;
+    let normal = Vec2::new(-direction.y, direction.x).unit();
+    if normal == Vec2::zero() {
+        Vec2::new(0.0, 1.0)
+    } else {
+        normal
+    }
+}
+
+fn shape_handles(shape: &CollisionShape) -> Vec<(ShapeHandle, Vec2)> {
+    match shape {
+        CollisionShape::AABB(area) => {
+            vec![(ShapeHandle::Min, area.min), (ShapeHandle::Max, area.max)]
+        }
+        CollisionShape::Circle { centre, radius } => vec![
+            (ShapeHandle::Centre, *centre),
+            (ShapeHandle::Radius, *centre + Vec2::new(*radius, 0.0)),
+        ],
+        CollisionShape::Capsule { start, end, radius } => {
+            let middle = (*start + *end) / 2.0;
+            vec![
+                (ShapeHandle::Start, *start),
+                (ShapeHandle::End, *end),
+                (
+                    ShapeHandle::Radius,
+                    middle + capsule_normal(*start, *end) * *radius,
+                ),
+            ]
+        }
+        CollisionShape::Polygon { points } => points
+            .iter()
+            .enumerate()
+            .map(|(i, point)| (ShapeHandle::Point(i), *point))
+            .collect(),
+    }
+}
+
+fn move_shape_handle(shape: &mut CollisionShape, handle: ShapeHandle, position: Vec2) {
+    match (shape, handle) {
+        (CollisionShape::AABB(area), ShapeHandle::Min) => area.min = position,
+        (CollisionShape::AABB(area), ShapeHandle::Max) => area.max = position,
+        (CollisionShape::Circle { centre, .. }, ShapeHandle::Centre) => *centre = position,
+        (CollisionShape::Circle { centre, radius }, ShapeHandle::Radius) => {
+            *radius = (position - *centre).magnitude();
+        }
+        (CollisionShape::Capsule { start, .. }, ShapeHandle::Start) => *start = position,
+        (CollisionShape::Capsule { end, .. }, ShapeHandle::End) => *end = position,
+        (CollisionShape::Capsule { start, end, radius }, ShapeHandle::Radius) => {
+            let normal = capsule_normal(*start, *end);
+            let offset = position - (*start + *end) / 2.0;
+            *radius = (offset.x * normal.x + offset.y * normal.y).abs();
+        }
+        (CollisionShape::Polygon { points }, ShapeHandle::Point(i)) => {
+            if let Some(point) = points.get_mut(i) {
+                *point = position;
+            }
+        }
+        _ => {}
+    }
+}
+
 fn edit_object<'a>(
     ui: &imgui::Ui,
     object: &mut SerialiseObject,
@@ -2031,10 +2634,18 @@ fn edit_object_properties(
     ui.input_float(im_str!("Starting Y"), &mut object.position.y)
         .build();
     object.size.choose(ui);
-    if let Sprite::Image { name } = &object.sprite {
-        if ui.button(im_str!("Match Image Size"), NORMAL_BUTTON) {
-            object.size = images[name].size();
+    match &object.sprite {
+        Sprite::Image { name } => {
+            if ui.button(im_str!("Match Image Size"), NORMAL_BUTTON) {
+                object.size = images[name].size();
+            }
+        }
+        Sprite::Region { rect, .. } | Sprite::NineSlice { rect, .. } => {
+            if ui.button(im_str!("Match Region Size"), NORMAL_BUTTON) {
+                object.size = Size::new(rect.width as f32, rect.height as f32);
+            }
         }
+        Sprite::Colour(_) | Sprite::Shape(_) => {}
     }
 
     choose_angle(&mut object.angle, ui);
@@ -2059,20 +2670,18 @@ fn edit_object_properties(
         }
     };
 
-    object.collision_area = match object.collision_area {
-        Some(mut area) => {
-            choose_collision_area(&mut area, ui);
-            Some(area)
-        }
-        None => {
-            let mut area = AABB::new(0.0, 0.0, object.size.width, object.size.height);
-            if choose_collision_area(&mut area, ui) {
-                Some(area)
-            } else {
-                None
-            }
-        }
-    };
+    choose_collision_shape(object, ui);
+    let pixel_collision = object.collision_mode == CollisionMode::Pixels;
+    if ui.radio_button_bool(im_str!("Pixel Perfect Collision"), pixel_collision) {
+        object.collision_mode = if pixel_collision {
+            CollisionMode::Area
+        } else {
+            CollisionMode::Pixels
+        };
+    }
+    if ui.is_item_hovered() {
+        ui.tooltip(|| ui.text("Only collide where the sprite image isn't transparent"));
+    }
     if ui.radio_button_bool(im_str!("Flip Horizontal"), object.flip.horizontal) {
         object.flip.horizontal = !object.flip.horizontal;
     }
@@ -2089,6 +2698,29 @@ fn edit_object_properties(
     let mut change_layer = object.layer as i32;
     ui.input_int(im_str!("Layer"), &mut change_layer).build();
     object.layer = change_layer.max(0).min(255) as u8;
+
+    ui.input_float(im_str!("Depth"), &mut object.depth).build();
+
+    choose_tags(&mut object.tags, ui);
+}
+
+fn choose_tags(tags: &mut Vec<String>, ui: &imgui::Ui) {
+    let mut remove = None;
+    for (i, tag) in tags.iter_mut().enumerate() {
+        let stack = ui.push_id(i as i32);
+        choose_string(tag, ui, im_str!("Tag"));
+        ui.same_line(0.0);
+        if ui.small_button(im_str!("Delete")) {
+            remove = Some(i);
+        }
+        stack.pop(ui);
+    }
+    if let Some(i) = remove {
+        tags.remove(i);
+    }
+    if ui.small_button(im_str!("Add Tag")) {
+        tags.push(String::new());
+    }
 }
 
 fn move_back<T>(list: &mut Vec<T>, index: &mut usize) {
@@ -2370,31 +3002,43 @@ fn edit_trigger(
     let first_name = || game_notes.object_names[0].to_string();
     let mut current_trigger_position = match trigger {
         Trigger::Time(_) => 0,
-        Trigger::Collision(_) => 1,
-        Trigger::Input(_) => 2,
-        Trigger::WinStatus(_) => 3,
-        Trigger::Random { .. } => 4,
+        Trigger::Collision(..) => 1,
+        Trigger::Bounds { .. } => 2,
+        Trigger::Distance { .. } => 3,
+        Trigger::Input(_) => 4,
+        Trigger::WinStatus(_) => 5,
+        Trigger::Random { .. } => 6,
         Trigger::CheckProperty {
             check: PropertyCheck::Switch(_),
             ..
-        } => 5,
+        } => 7,
         Trigger::CheckProperty {
             check: PropertyCheck::Sprite(_),
             ..
-        } => 6,
+        } => 8,
         Trigger::CheckProperty {
             check: PropertyCheck::FinishedAnimation,
             ..
-        } => 7,
+        } => 9,
         Trigger::CheckProperty {
             check: PropertyCheck::Timer,
             ..
-        } => 8,
-        Trigger::DifficultyLevel { .. } => 9,
+        } => 10,
+        Trigger::CheckProperty {
+            check: PropertyCheck::Compare { .. },
+            ..
+        } => 11,
+        Trigger::DifficultyLevel { .. } => 12,
+        Trigger::CheckProperty {
+            check: PropertyCheck::AnimationFrame { .. },
+            ..
+        } => 13,
     };
     let trigger_names = [
         im_str!("Time"),
         im_str!("Collision"),
+        im_str!("Bounds"),
+        im_str!("Distance"),
         im_str!("Mouse"),
         im_str!("Win Status"),
         im_str!("Random Chance"),
@@ -2402,7 +3046,9 @@ fn edit_trigger(
         im_str!("Check Sprite"),
         im_str!("Finished Animation"),
         im_str!("Timer"),
+        im_str!("Compare Property"),
         im_str!("Difficulty Level"),
+        im_str!("Animation Frame"),
     ];
     if imgui::ComboBox::new(im_str!("Trigger")).build_simple_string(
         ui,
@@ -2411,32 +3057,58 @@ fn edit_trigger(
     ) {
         *trigger = match current_trigger_position {
             0 => Trigger::Time(When::Start),
-            1 => Trigger::Collision(CollisionWith::Area(AABB::new(0.0, 0.0, 1600.0, 900.0))),
-            2 => Trigger::Input(Input::Mouse {
+            1 => Trigger::Collision(
+                CollisionWith::Area(AABB::new(0.0, 0.0, 1600.0, 900.0)),
+                CollisionPhase::Stay,
+            ),
+            2 => Trigger::Bounds {
+                area: screen_area(),
+                condition: BoundsCondition::FullyOutside,
+            },
+            3 => Trigger::Distance {
+                from: DistanceEnd::Object { name: first_name() },
+                to: DistanceEnd::Mouse,
+                measure: DistanceMeasure::Centre,
+                comparison: Comparison::LessThan,
+                value: 100.0,
+            },
+            4 => Trigger::Input(Input::Mouse {
                 over: MouseOver::Anywhere,
                 interaction: MouseInteraction::Hover,
             }),
-            3 => Trigger::WinStatus(WinStatus::Won),
-            4 => Trigger::Random { chance: 0.5 },
-            5 => Trigger::CheckProperty {
+            5 => Trigger::WinStatus(WinStatus::Won),
+            6 => Trigger::Random { chance: 0.5 },
+            7 => Trigger::CheckProperty {
                 name: first_name(),
                 check: PropertyCheck::Switch(SwitchState::On),
             },
-            6 => Trigger::CheckProperty {
+            8 => Trigger::CheckProperty {
                 name: first_name(),
                 check: PropertyCheck::Sprite(Sprite::Colour(Colour::black())),
             },
-            7 => Trigger::CheckProperty {
+            9 => Trigger::CheckProperty {
                 name: first_name(),
                 check: PropertyCheck::FinishedAnimation,
             },
-            8 => Trigger::CheckProperty {
+            10 => Trigger::CheckProperty {
                 name: first_name(),
                 check: PropertyCheck::Timer,
             },
-            9 => Trigger::DifficultyLevel {
+            11 => Trigger::CheckProperty {
+                name: first_name(),
+                check: PropertyCheck::Compare {
+                    property: NumericProperty::X,
+                    comparison: Comparison::GreaterThan,
+                    to: CompareTo::Value(0.0),
+                },
+            },
+            12 => Trigger::DifficultyLevel {
                 levels: HashSet::new(),
             },
+            13 => Trigger::CheckProperty {
+                name: first_name(),
+                check: PropertyCheck::AnimationFrame { index: 0 },
+            },
             _ => unreachable!(),
         }
     }
@@ -2444,8 +3116,49 @@ fn edit_trigger(
         Trigger::Time(when) => {
             choose_when(when, ui, game_notes.length);
         }
-        Trigger::Collision(with) => {
+        Trigger::Collision(with, phase) => {
             choose_collision_with(with, ui, &game_notes.object_names, &mut editor.draw_tasks);
+            phase.choose(ui);
+        }
+        Trigger::Bounds { area, condition } => {
+            condition.choose(ui);
+            choose_collision_area(area, ui);
+            editor.draw_tasks.push(DrawTask::AABB(*area));
+        }
+        Trigger::Distance {
+            from,
+            to,
+            measure,
+            comparison,
+            value,
+        } => {
+            ui.text("From");
+            let stack = ui.push_id(0);
+            choose_distance_end(from, ui, &game_notes.object_names);
+            stack.pop(ui);
+            ui.text("To");
+            let stack = ui.push_id(1);
+            choose_distance_end(to, ui, &game_notes.object_names);
+            stack.pop(ui);
+            measure.choose(ui);
+            comparison.choose(ui);
+            ui.input_float(im_str!("Distance"), value).build();
+
+            for (centre, size) in distance_end_centres(from, game_notes.objects) {
+                // Edges are approximated by the circle around the object
+                let radius = match measure {
+                    DistanceMeasure::Centre => *value,
+                    DistanceMeasure::Edge => *value + size.width.max(size.height) / 2.0,
+                };
+                let circle = WorldShape::Circle {
+                    centre,
+                    radius: radius.max(0.0),
+                };
+                editor.draw_tasks.push(DrawTask::Shape(
+                    circle.outline(),
+                    Colour::rgba(0.0, 1.0, 0.0, 0.2),
+                ));
+            }
         }
         Trigger::Input(Input::Mouse { over, interaction }) => {
             choose_mouse_over(over, ui, &game_notes.object_names);
@@ -2459,7 +3172,14 @@ fn edit_trigger(
         }
         Trigger::CheckProperty { name, check } => {
             choose_object(name, ui, &game_notes.object_names);
-            choose_property_check(check, ui, images, image_files, &editor.filename);
+            choose_property_check(
+                check,
+                ui,
+                images,
+                image_files,
+                &editor.filename,
+                &game_notes.object_names,
+            );
         }
         Trigger::DifficultyLevel { levels } => {
             choose_difficulty_level_set(levels, ui);
@@ -2542,7 +3262,7 @@ fn choose_action_type(ui: &imgui::Ui, action: &mut Action, assets: &mut Assets)
     let action_names = [
         im_str!("Win"),
         im_str!("Lose"),
-        im_str!("Freeze"),
+        im_str!("Screen Effect"),
         im_str!("Motion"),
         im_str!("Play Sound"),
         im_str!("Stop Music"),
@@ -2574,6 +3294,9 @@ fn choose_action_type(ui: &imgui::Ui, action: &mut Action, assets: &mut Assets)
                 animation_type: AnimationType::Loop,
                 sprites: Vec::new(),
                 speed: Speed::Normal,
+                frame_durations: Vec::new(),
+                playback: Playback::Forward,
+                start_frame: 0,
             },
             8 => Action::DrawText {
                 text: "".to_string(),
@@ -2581,6 +3304,8 @@ fn choose_action_type(ui: &imgui::Ui, action: &mut Action, assets: &mut Assets)
                 colour: Colour::black(),
                 resize: TextResize::MatchText,
                 justify: JustifyText::Centre,
+                layout: TextLayout::default(),
+                key: None,
             },
             9 => Action::Random {
                 random_actions: Vec::new(),
@@ -2649,10 +3374,22 @@ fn edit_individual_action<'a>(
             animation_type,
             sprites,
             speed,
+            frame_durations,
+            playback,
+            start_frame,
         } => {
             let mut modified = animation_type.choose(ui);
+            modified |= playback.choose(ui);
+            modified |= choose_imported_animation(
+                sprites,
+                frame_durations,
+                playback,
+                ui,
+                &asset_files.animations,
+            );
             modified |= choose_animation(
                 sprites,
+                frame_durations,
                 ui,
                 &mut editor.animation_editor,
                 asset_files,
@@ -2660,15 +3397,34 @@ fn edit_individual_action<'a>(
                 &editor.filename,
             );
             modified |= speed.choose(ui);
+            modified |= choose_frame_durations(frame_durations, sprites.len(), *speed, ui);
+
+            let mut frame = *start_frame as i32;
+            if ui.input_int(im_str!("Start Frame"), &mut frame).build() {
+                *start_frame = (frame.max(0) as usize).min(sprites.len().saturating_sub(1));
+                modified = true;
+            }
+
             modified |= ui.button(im_str!("Play Animation"), NORMAL_BUTTON);
             if modified {
-                editor.animation_editor.preview =
-                    AnimationStatus::start(*animation_type, sprites, *speed);
-                editor.animation_editor.displayed_sprite = sprites.get(0).cloned();
+                editor.animation_editor.preview = AnimationStatus::start(
+                    *animation_type,
+                    sprites,
+                    frame_durations,
+                    *speed,
+                    *playback,
+                    *start_frame,
+                );
+                editor.animation_editor.displayed_sprite =
+                    editor.animation_editor.preview.sprite().cloned();
             }
             if let Some(sprite) = editor.animation_editor.preview.update() {
                 editor.animation_editor.displayed_sprite = Some(sprite);
             }
+            match editor.animation_editor.preview.frame() {
+                Some(frame) => ui.text(format!("Frame {}", frame)),
+                None => ui.text("Finished"),
+            }
             if let Some(sprite) = &editor.animation_editor.displayed_sprite {
                 match sprite {
                     Sprite::Image { name } => {
@@ -2679,6 +3435,17 @@ fn edit_individual_action<'a>(
                         )
                         .build(ui);
                     }
+                    Sprite::Region { image, rect } | Sprite::NineSlice { image, rect, .. } => {
+                        let texture = &assets.images[image];
+                        let (uv0, uv1) = region_uvs(texture, *rect);
+                        imgui::ImageButton::new(
+                            imgui::TextureId::from(texture.id as usize),
+                            [100.0, 100.0],
+                        )
+                        .uv0(uv0)
+                        .uv1(uv1)
+                        .build(ui);
+                    }
                     Sprite::Colour(colour) => {
                         imgui::ColorButton::new(
                             im_str!("##Colour"),
@@ -2687,6 +3454,11 @@ fn edit_individual_action<'a>(
                         .size([COLOUR_BUTTON_SIZE, COLOUR_BUTTON_SIZE])
                         .build(ui);
                     }
+                    Sprite::Shape(shape) => {
+                        imgui::ColorButton::new(im_str!("##Colour"), shape_colour(shape))
+                            .size([COLOUR_BUTTON_SIZE, COLOUR_BUTTON_SIZE])
+                            .build(ui);
+                    }
                 }
             }
         }
@@ -2694,10 +3466,13 @@ fn edit_individual_action<'a>(
             text,
             font,
             colour,
-            resize: _resize,
+            resize,
             justify,
+            layout,
+            key,
         } => {
             text.choose(ui);
+            choose_text_key(key, ui, im_str!("Translation Key"));
             choose_font(
                 font,
                 ui,
@@ -2707,8 +3482,15 @@ fn edit_individual_action<'a>(
                 &editor.filename,
             );
             colour.choose(ui);
-            //resize.choose(ui);
+            resize.choose(ui);
             justify.choose(ui);
+            layout.choose(ui);
+            if *resize == TextResize::MatchText && (layout.wrap || layout.shrink_to_fit) {
+                ui.text("Text only wraps or shrinks when the object's size is kept");
+            }
+        }
+        Action::Effect(effect) => {
+            effect.choose(ui);
         }
         Action::Random { .. } => {}
         _ => {}
@@ -2933,8 +3715,15 @@ fn edit_objects_list(
                     images,
                     &filename,
                 ) {
-                    if let Sprite::Image { name } = &object_state.new_object.sprite {
-                        object_state.new_object.size = images[name].size();
+                    match &object_state.new_object.sprite {
+                        Sprite::Image { name } => {
+                            object_state.new_object.size = images[name].size();
+                        }
+                        Sprite::Region { rect, .. } | Sprite::NineSlice { rect, .. } => {
+                            object_state.new_object.size =
+                                Size::new(rect.width as f32, rect.height as f32);
+                        }
+                        Sprite::Colour(_) | Sprite::Shape(_) => {}
                     }
                 }
 
@@ -3022,7 +3811,7 @@ fn edit_objects_list(
         });
 }
 
-fn file_show(ui: &imgui::Ui) -> FileTask {
+fn file_show(ui: &imgui::Ui, last_playthrough: &Option<Playthrough>) -> FileTask {
     let menu = ui.begin_menu(im_str!("File"), true);
     let mut file_task = FileTask::None;
     if let Some(menu) = menu {
@@ -3042,6 +3831,21 @@ fn file_show(ui: &imgui::Ui) -> FileTask {
         if imgui::MenuItem::new(im_str!("Reload Assets")).build(ui) {
             file_task = FileTask::ReloadAssets;
         }
+        if imgui::MenuItem::new(im_str!("Import Sprite Sheet")).build(ui) {
+            file_task = FileTask::ImportSpriteSheet;
+        }
+        if imgui::MenuItem::new(im_str!("Extract Text for Translation")).build(ui) {
+            file_task = FileTask::ExtractText;
+        }
+        if last_playthrough.is_some() {
+            ui.separator();
+            if imgui::MenuItem::new(im_str!("Save Previous Playthrough")).build(ui) {
+                file_task = FileTask::SavePlaythrough;
+            }
+            if imgui::MenuItem::new(im_str!("Export Previous Playthrough")).build(ui) {
+                file_task = FileTask::ExportPlaythrough;
+            }
+        }
 
         menu.end(ui);
     }
@@ -3066,7 +3870,7 @@ fn main_menu_bar_show(
     let menu_bar = ui.begin_main_menu_bar();
     let mut file_task = FileTask::None;
     if let Some(bar) = menu_bar {
-        file_task = file_show(ui);
+        file_task = file_show(ui, last_playthrough);
 
         view_show(ui, windows);
 
@@ -3075,14 +3879,6 @@ fn main_menu_bar_show(
             toggle_menu_item(ui, im_str!("Show Collision Areas"), show_collision_areas);
             toggle_menu_item(ui, im_str!("Show Origins"), show_origins);
 
-            ui.separator();
-
-            if last_playthrough.is_some()
-                && imgui::MenuItem::new(im_str!("Save Previous Playthrough")).build(ui)
-            {
-                file_task = FileTask::SavePlaythrough;
-            }
-
             menu.end(ui);
         }
         bar.end(ui);
@@ -3098,8 +3894,11 @@ enum FileTask {
     Save,
     SaveAs,
     ReloadAssets,
+    ImportSpriteSheet,
+    ExtractText,
     ReturnToMenu,
     SavePlaythrough,
+    ExportPlaythrough,
     Exit,
     None,
 }
@@ -3245,12 +4044,43 @@ impl FileTask {
                     log::error!("Can't reload assets when the game is not saved")
                 }
             }
+            FileTask::ImportSpriteSheet => {
+                if let Some(filename) = &editor.filename {
+                    let images_path = assets_path(&Some(filename.clone()), "images");
+                    let response = nfd::open_file_dialog(Some("json"), images_path.to_str());
+                    for _ in events.pump.poll_iter() {}
+                    if let Ok(Response::Okay(sheet_path)) = response {
+                        match import_sprite_sheet(
+                            &sheet_path,
+                            &images_path,
+                            game,
+                            &mut assets.images,
+                        ) {
+                            Ok(count) => log::info!("Imported {} animations", count),
+                            Err(error) => log::error!("Couldn't import sprite sheet: {}", error),
+                        }
+                    }
+                } else {
+                    log::error!("Save the game before importing a sprite sheet")
+                }
+            }
+            FileTask::ExtractText => {
+                if let Some(filename) = &editor.filename {
+                    match extract_text_template(Path::new(filename), game) {
+                        Ok(path) => log::info!("Saved the translation template to {:?}", path),
+                        Err(error) => log::error!("Couldn't extract text: {}", error),
+                    }
+                } else {
+                    log::error!("Save the game before extracting its text")
+                }
+            }
             FileTask::ReturnToMenu => {}
             FileTask::SavePlaythrough => {
                 if let Some(playthrough) = last_playthrough {
                     save_playthrough_file_as(playthrough);
                 }
             }
+            FileTask::ExportPlaythrough => {}
             FileTask::Exit => process::exit(0),
             FileTask::None => {}
         }
@@ -3259,6 +4089,60 @@ impl FileTask {
     }
 }
 
+// Adds the sheet's image and an animation for each of its tags, returning how many were added
+fn import_sprite_sheet(
+    sheet_path: &str,
+    images_path: &Path,
+    game: &mut GameData,
+    images: &mut Images,
+) -> WeeResult<usize> {
+    let sheet = SpriteSheet::from_json(&std::fs::read_to_string(sheet_path)?)?;
+    let image_path = Path::new(sheet_path)
+        .parent()
+        .ok_or("Could not get the sprite sheet's directory")?
+        .join(&sheet.image);
+    // Images are always loaded from the images directory
+    if image_path.parent().map(Path::canonicalize).transpose()? != Some(images_path.canonicalize()?)
+    {
+        return Err(format!(
+            "Move {:?} into {:?} before importing it",
+            image_path, images_path
+        )
+        .into());
+    }
+
+    let image_name = get_main_filename_part(&image_path)?;
+    let texture = Texture::from_file(&image_path)?;
+    images.insert(image_name.clone(), texture);
+    game.asset_files
+        .images
+        .insert(image_name.clone(), get_filename(&image_path)?);
+
+    let animations = sheet.animations(&image_name);
+    let count = animations.len();
+    game.asset_files.animations.extend(animations);
+    Ok(count)
+}
+
+// Keys are given to the game's text and added to the template shared by games in the directory
+fn extract_text_template(game_path: &Path, game: &mut GameData) -> WeeResult<PathBuf> {
+    let directory = game_path
+        .parent()
+        .ok_or("Could not get the game's directory")?;
+    let extracted = game.extract_text(&get_main_filename_part(game_path)?);
+
+    let path = table_path(directory, TEMPLATE_NAME);
+    let mut template = if path.exists() {
+        parse_string_table(&std::fs::read_to_string(&path)?)?
+    } else {
+        StringTable::new()
+    };
+    template.extend(extracted);
+    std::fs::create_dir_all(directory.join(TEXT_DIRECTORY))?;
+    std::fs::write(&path, serde_json::to_string_pretty(&template)?)?;
+    Ok(path)
+}
+
 fn save_game_file_as(game: &GameData, filename: &mut Option<String>) {
     let response = nfd::open_save_dialog(None, Path::new("games").to_str());
     match response {
@@ -3312,6 +4196,109 @@ fn save_playthrough_file_as(saved_run: &Playthrough) {
     }
 }
 
+fn export_playthrough_window_show(
+    ui: &imgui::Ui,
+    opened: &mut bool,
+    preview: &mut Preview,
+    game_filename: &Option<String>,
+    intro_font_filename: &str,
+) {
+    let mut export_clicked = false;
+    imgui::Window::new(im_str!("Export Playthrough"))
+        .size([400.0, 220.0], imgui::Condition::FirstUseEver)
+        .opened(opened)
+        .build(ui, || {
+            let settings = &mut preview.export_settings;
+            imgui::Slider::new(im_str!("Scale"), std::ops::RangeInclusive::new(0.1, 1.0))
+                .display_format(im_str!("%.02f"))
+                .build(ui, &mut settings.scale);
+            let mut frame_skip = settings.frame_skip as i32;
+            ui.input_int(im_str!("Frame Skip"), &mut frame_skip).build();
+            settings.frame_skip = frame_skip.max(0) as u32;
+            ui.checkbox(im_str!("Show Cursor"), &mut settings.show_cursor);
+
+            if ui.radio_button_bool(im_str!("GIF"), preview.export_format == ExportFormat::Gif) {
+                preview.export_format = ExportFormat::Gif;
+            }
+            ui.same_line(0.0);
+            if ui.radio_button_bool(
+                im_str!("PNG Sequence"),
+                preview.export_format == ExportFormat::PngSequence,
+            ) {
+                preview.export_format = ExportFormat::PngSequence;
+            }
+
+            export_clicked = ui.button(im_str!("Export"), NORMAL_BUTTON);
+        });
+
+    if export_clicked {
+        match (&preview.last_playthrough, game_filename) {
+            (Some(playthrough), Some(game_filename)) => export_playthrough_file_as(
+                playthrough,
+                game_filename,
+                intro_font_filename,
+                preview.export_settings,
+                preview.export_format,
+            ),
+            _ => log::error!("Play the saved game before exporting a playthrough"),
+        }
+    }
+}
+
+// The game is replayed as it was played, with assets from beside the saved game file
+fn export_playthrough_file_as(
+    playthrough: &Playthrough,
+    game_filename: &str,
+    intro_font_filename: &str,
+    settings: ExportSettings,
+    format: ExportFormat,
+) {
+    let response = nfd::open_save_dialog(None, Path::new("").to_str());
+    match response {
+        Ok(Response::Okay(file_path)) => {
+            let mut output = PathBuf::from(file_path);
+            match format {
+                ExportFormat::Gif => {
+                    output.set_extension("gif");
+                }
+                ExportFormat::PngSequence => {
+                    output.set_extension("");
+                }
+            }
+            log::info!("Exporting playthrough to {:?}", output);
+
+            let saved_run = SavedRun {
+                path: game_filename.to_string(),
+                inputs: playthrough.inputs.clone(),
+                difficulty: playthrough.difficulty,
+                seed: playthrough.seed,
+                has_been_won: playthrough.has_been_won,
+            };
+            let game = playthrough.game.clone();
+            let base_path = Path::new(game_filename)
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default();
+            let intro_font_filename = intro_font_filename.to_string();
+            let language = playthrough.language.clone();
+            // Drawing every frame takes a while so the editor carries on in the meantime
+            thread::spawn(move || {
+                let exported =
+                    Replay::from_data(game, base_path, &saved_run, intro_font_filename, &language)
+                        .and_then(|replay| export::export_replay(replay, &output, settings));
+                match exported {
+                    Ok(frames) => log::info!("Exported {} frames to {:?}", frames, output),
+                    Err(error) => log::error!("Couldn't export playthrough: {}", error),
+                }
+            });
+        }
+        Ok(_) => {}
+        Err(error) => {
+            log::error!("{}", error);
+        }
+    }
+}
+
 fn view_show(ui: &imgui::Ui, windows: &mut Windows) {
     let toggle = |label, opened: &mut bool| {
         toggle_menu_item(ui, label, opened);
@@ -3345,6 +4332,7 @@ struct Windows {
     music: bool,
     sounds: bool,
     help: bool,
+    export_playthrough: bool,
     demo: bool,
 }
 
@@ -3358,6 +4346,7 @@ impl Default for Windows {
             music: false,
             sounds: false,
             help: false,
+            export_playthrough: false,
             demo: false,
         }
     }
@@ -3369,6 +4358,10 @@ struct Preview {
     difficulty_level: u32,
     last_playthrough: Option<Playthrough>,
     settings: GameSettings,
+    debug_mode: bool,
+    step_frames: i32,
+    export_settings: ExportSettings,
+    export_format: ExportFormat,
 }
 
 impl Preview {
@@ -3378,6 +4371,10 @@ impl Preview {
             difficulty_level: 1,
             last_playthrough: None,
             settings,
+            debug_mode: false,
+            step_frames: 10,
+            export_settings: ExportSettings::default(),
+            export_format: ExportFormat::Gif,
         }
     }
 }
@@ -3398,6 +4395,7 @@ impl Default for FontState {
 
 struct ObjectState {
     index: Option<usize>,
+    shape_handle: Option<ShapeHandle>,
     rename_object: Option<RenameObject>,
     new_object: SerialiseObject,
     new_name_buffer: ImString,
@@ -3543,6 +4541,28 @@ fn show_object_tooltip(
                     );
                 }
             }
+            Some(Sprite::Region { image, rect }) | Some(Sprite::NineSlice { image, rect, .. }) => {
+                if let Some(texture) = &images.get(image) {
+                    let max_side = rect.width.max(rect.height).max(1) as f32;
+                    let size = Size::new(
+                        rect.width as f32 / max_side * TOOLTIP_IMAGE_SIZE,
+                        rect.height as f32 / max_side * TOOLTIP_IMAGE_SIZE,
+                    );
+                    let (uv0, uv1) = region_uvs(texture, *rect);
+                    imgui::Image::new(
+                        imgui::TextureId::from(texture.id as usize),
+                        [size.width, size.height],
+                    )
+                    .uv0(uv0)
+                    .uv1(uv1)
+                    .build(ui);
+                } else {
+                    ui.text_colored(
+                        [1.0, 0.0, 0.0, 1.0],
+                        format!("Warning: Image `{}` not found", image),
+                    );
+                }
+            }
             Some(Sprite::Colour(colour)) => {
                 imgui::ColorButton::new(
                     im_str!("##Colour"),
@@ -3551,6 +4571,12 @@ fn show_object_tooltip(
                 .size([80.0, 80.0])
                 .build(ui);
             }
+            Some(Sprite::Shape(shape)) => {
+                ui.text(shape_name(&shape.shape));
+                imgui::ColorButton::new(im_str!("##Colour"), shape_colour(shape))
+                    .size([80.0, 80.0])
+                    .build(ui);
+            }
             None => {
                 ui.text_colored(
                     [1.0, 0.0, 0.0, 1.0],
@@ -3599,6 +4625,20 @@ impl ImguiDisplayTrigger for Trigger {
                 Sprite::Image { name } => {
                     image_button(name);
                 }
+                Sprite::Region { image, rect } => {
+                    let label = format!(
+                        "{} ({}, {}, {}x{})",
+                        image, rect.x, rect.y, rect.width, rect.height
+                    );
+                    image_button_with_label(&ImString::from(label), image);
+                }
+                Sprite::NineSlice { image, rect, .. } => {
+                    let label = format!(
+                        "{} ({}, {}, {}x{}) as a nine-slice",
+                        image, rect.x, rect.y, rect.width, rect.height
+                    );
+                    image_button_with_label(&ImString::from(label), image);
+                }
                 Sprite::Colour(colour) => {
                     ui.text(format!(
                         "The colour {{ red: {}, green: {}, blue: {}, alpha: {} }}",
@@ -3615,14 +4655,28 @@ impl ImguiDisplayTrigger for Trigger {
                         })
                     };
                 }
+                Sprite::Shape(shape) => {
+                    ui.text(format!("A {} shape", shape_name(&shape.shape)));
+                    if ui.is_item_hovered() {
+                        ui.tooltip(|| {
+                            imgui::ColorButton::new(im_str!("##Colour"), shape_colour(shape))
+                                .size([80.0, 80.0])
+                                .build(ui);
+                        })
+                    };
+                }
             };
         };
 
         let same_line = || ui.same_line_with_spacing(0.0, 3.0);
 
         match self {
-            Trigger::Collision(CollisionWith::Object { name }) => {
-                ui.text("While this object collides with");
+            Trigger::Collision(CollisionWith::Object { name }, phase) => {
+                ui.text(match phase {
+                    CollisionPhase::Enter => "When this object starts colliding with",
+                    CollisionPhase::Stay => "While this object collides with",
+                    CollisionPhase::Exit => "When this object stops colliding with",
+                });
                 same_line();
                 object_button(name);
             }
@@ -3729,6 +4783,13 @@ impl ImguiDisplayTrigger for Trigger {
                     same_line();
                     ui.text("animation is finished");
                 }
+                PropertyCheck::AnimationFrame { index } => {
+                    ui.text("While");
+                    same_line();
+                    object_button_with_label(&im_str!("{}'s", name), name);
+                    same_line();
+                    ui.text(format!("animation is on frame {}", index));
+                }
                 _ => ui.text(self.to_string()),
             },
             _ => ui.text(self.to_string()),
@@ -3789,6 +4850,20 @@ impl ImguiDisplayAction for Action {
                 Sprite::Image { name } => {
                     image_button(name);
                 }
+                Sprite::Region { image, rect } => {
+                    let label = format!(
+                        "{} ({}, {}, {}x{})",
+                        image, rect.x, rect.y, rect.width, rect.height
+                    );
+                    image_button_with_label(&ImString::from(label), image);
+                }
+                Sprite::NineSlice { image, rect, .. } => {
+                    let label = format!(
+                        "{} ({}, {}, {}x{}) as a nine-slice",
+                        image, rect.x, rect.y, rect.width, rect.height
+                    );
+                    image_button_with_label(&ImString::from(label), image);
+                }
                 Sprite::Colour(colour) => {
                     ui.text(format!(
                         "The colour {{ red: {}, green: {}, blue: {}, alpha: {} }}",
@@ -3805,6 +4880,16 @@ impl ImguiDisplayAction for Action {
                         })
                     };
                 }
+                Sprite::Shape(shape) => {
+                    ui.text(format!("A {} shape", shape_name(&shape.shape)));
+                    if ui.is_item_hovered() {
+                        ui.tooltip(|| {
+                            imgui::ColorButton::new(im_str!("##Colour"), shape_colour(shape))
+                                .size([80.0, 80.0])
+                                .build(ui);
+                        })
+                    };
+                }
             };
         };
 
@@ -3878,6 +4963,8 @@ enum DrawTask {
     Border(Model),
     Model(Model, Colour),
     Point(Vec2),
+    Shape(Vec<Vec2>, Colour),
+    Handle(Vec2),
 }
 
 enum InstructionMode {
@@ -4162,6 +5249,94 @@ impl Choose for WinStatus {
     }
 }
 
+impl Choose for BoundsCondition {
+    fn choose(&mut self, ui: &imgui::Ui) -> bool {
+        let conditions = [
+            im_str!("Fully Inside"),
+            im_str!("Fully Outside"),
+            im_str!("Touching Edge"),
+            im_str!("Crossed Edge"),
+        ];
+        let mut current_condition = match self {
+            BoundsCondition::FullyInside => 0,
+            BoundsCondition::FullyOutside => 1,
+            BoundsCondition::TouchingEdge => 2,
+            BoundsCondition::CrossedEdge(_) => 3,
+        };
+
+        let mut changed = imgui::ComboBox::new(im_str!("Condition")).build_simple_string(
+            ui,
+            &mut current_condition,
+            &conditions,
+        );
+        if changed {
+            *self = match current_condition {
+                0 => BoundsCondition::FullyInside,
+                1 => BoundsCondition::FullyOutside,
+                2 => BoundsCondition::TouchingEdge,
+                3 => BoundsCondition::CrossedEdge(Side::Bottom),
+                _ => unreachable!(),
+            };
+        }
+
+        if let BoundsCondition::CrossedEdge(side) = self {
+            let sides = [
+                im_str!("Top"),
+                im_str!("Bottom"),
+                im_str!("Left"),
+                im_str!("Right"),
+            ];
+            let mut current_side = *side as usize;
+            if imgui::ComboBox::new(im_str!("Side")).build_simple_string(
+                ui,
+                &mut current_side,
+                &sides,
+            ) {
+                *side = match current_side {
+                    0 => Side::Top,
+                    1 => Side::Bottom,
+                    2 => Side::Left,
+                    3 => Side::Right,
+                    _ => unreachable!(),
+                };
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
+impl Choose for CollisionPhase {
+    fn choose(&mut self, ui: &imgui::Ui) -> bool {
+        let phases = [
+            im_str!("Starts Colliding"),
+            im_str!("While Colliding"),
+            im_str!("Stops Colliding"),
+        ];
+        let mut current_phase = match self {
+            CollisionPhase::Enter => 0,
+            CollisionPhase::Stay => 1,
+            CollisionPhase::Exit => 2,
+        };
+
+        if imgui::ComboBox::new(im_str!("When")).build_simple_string(
+            ui,
+            &mut current_phase,
+            &phases,
+        ) {
+            *self = match current_phase {
+                0 => CollisionPhase::Enter,
+                1 => CollisionPhase::Stay,
+                2 => CollisionPhase::Exit,
+                _ => unreachable!(),
+            };
+            true
+        } else {
+            false
+        }
+    }
+}
+
 fn choose_percent(percent: &mut f32, ui: &imgui::Ui) {
     let mut chance_percent = *percent * 100.0;
     ui.drag_float(im_str!("Chance"), &mut chance_percent)
@@ -4239,6 +5414,8 @@ fn select_sprite_type(
 ) -> bool {
     let mut modified = false;
     let is_sprite = matches!(sprite, Sprite::Image { .. });
+    let is_region = matches!(sprite, Sprite::Region { .. });
+    let is_colour = matches!(sprite, Sprite::Colour(_));
     let mut sorted_keys: Vec<&String> = images.keys().collect();
     sorted_keys.sort();
     if ui.radio_button_bool(im_str!("Sprite"), is_sprite) {
@@ -4261,39 +5438,309 @@ fn select_sprite_type(
         modified = true;
     }
     ui.same_line(0.0);
-    if ui.radio_button_bool(im_str!("Colour"), !is_sprite) {
+    if ui.radio_button_bool(im_str!("Region"), is_region) {
+        // Starts as the whole of the current image so it can be cut down from there
+        let image = match sprite {
+            Sprite::Image { name } => Some(name.clone()),
+            _ => sorted_keys.get(0).map(|name| (*name).to_string()),
+        };
+        match image {
+            Some(image) => {
+                let size = images[&image].size();
+                *sprite = Sprite::Region {
+                    image,
+                    rect: ImageRect::new(0, 0, size.width as u32, size.height as u32),
+                };
+                modified = true;
+            }
+            None => log::error!("Add an image before choosing a region of one"),
+        }
+    }
+    ui.same_line(0.0);
+    if ui.radio_button_bool(
+        im_str!("Nine-Slice"),
+        matches!(sprite, Sprite::NineSlice { .. }),
+    ) {
+        // Keeps the current image or region, with borders a quarter of its shortest side
+        let image = match sprite {
+            Sprite::Image { name } => Some((name.clone(), None)),
+            Sprite::Region { image, rect } => Some((image.clone(), Some(*rect))),
+            _ => sorted_keys.get(0).map(|name| ((*name).to_string(), None)),
+        };
+        match image {
+            Some((image, rect)) => {
+                let rect = rect.unwrap_or_else(|| {
+                    let size = images[&image].size();
+                    ImageRect::new(0, 0, size.width as u32, size.height as u32)
+                });
+                let border = rect.width.min(rect.height) / 4;
+                *sprite = Sprite::NineSlice {
+                    image,
+                    rect,
+                    insets: SliceInsets::new(border, border, border, border),
+                };
+                modified = true;
+            }
+            None => log::error!("Add an image before making a nine-slice sprite"),
+        }
+    }
+    ui.same_line(0.0);
+    if ui.radio_button_bool(im_str!("Colour"), is_colour) {
         *sprite = Sprite::Colour(Colour::black());
         modified = true;
     }
+    ui.same_line(0.0);
+    if ui.radio_button_bool(im_str!("Shape"), matches!(sprite, Sprite::Shape(_))) {
+        *sprite = Sprite::Shape(VectorSprite::new(VectorShape::Ellipse, Colour::black()));
+        modified = true;
+    }
     modified
 }
 
-fn choose_new_image<P: AsRef<Path>>(
-    image: &mut Sprite,
-    image_files: &mut HashMap<String, String>,
-    images: &mut Images,
-    path: P,
-) -> bool {
-    let first_key = choose_image_from_files(image_files, images, path);
-    match first_key {
-        Some(key) => {
-            *image = Sprite::Image { name: key };
-            true
-        }
-        None => {
-            log::error!("None of the new images loaded correctly");
-            false
-        }
+fn region_uvs(texture: &Texture, rect: ImageRect) -> ([f32; 2], [f32; 2]) {
+    let size = texture.size();
+    if size.width <= 0.0 || size.height <= 0.0 {
+        return ([0.0, 0.0], [1.0, 1.0]);
     }
+    (
+        [rect.x as f32 / size.width, rect.y as f32 / size.height],
+        [
+            (rect.x + rect.width) as f32 / size.width,
+            (rect.y + rect.height) as f32 / size.height,
+        ],
+    )
 }
 
-fn choose_font<'a, 'b>(
-    font_name: &mut String,
-    ui: &imgui::Ui,
-    font_files: &mut HashMap<String, FontLoadInfo>,
-    fonts: &mut Fonts<'a, 'b>,
-    ttf_context: &'a TtfContext,
-    filename: &Option<String>,
+fn shape_name(shape: &VectorShape) -> &'static str {
+    match shape {
+        VectorShape::Ellipse => "Ellipse",
+        VectorShape::RoundedRectangle { .. } => "Rounded Rectangle",
+        VectorShape::Line { .. } => "Line",
+        VectorShape::Polygon { .. } => "Polygon",
+    }
+}
+
+// Used where a small preview of the shape is needed
+fn shape_colour(shape: &VectorSprite) -> [f32; 4] {
+    let colour = match (shape.fill, shape.stroke) {
+        (Fill::Solid(colour), _) | (Fill::Gradient { start: colour, .. }, _) => colour,
+        (Fill::None, Some(stroke)) => stroke.colour,
+        (Fill::None, None) => Colour::rgba(0.0, 0.0, 0.0, 0.0),
+    };
+    [colour.r, colour.g, colour.b, colour.a]
+}
+
+fn choose_vector_sprite(shape: &mut VectorSprite, ui: &imgui::Ui) -> bool {
+    let mut modified = shape.shape.combo(ui);
+    match &mut shape.shape {
+        VectorShape::Ellipse => {}
+        VectorShape::RoundedRectangle { radius } => {
+            if ui.input_float(im_str!("Corner Radius"), radius).build() {
+                *radius = radius.max(0.0);
+                modified = true;
+            }
+        }
+        VectorShape::Line { start, end } => {
+            ui.text("Points are fractions of the object's size");
+            ui.text("Start");
+            let stack = ui.push_id(0);
+            modified |= start.choose(ui);
+            stack.pop(ui);
+            ui.text("End");
+            let stack = ui.push_id(1);
+            modified |= end.choose(ui);
+            stack.pop(ui);
+        }
+        VectorShape::Polygon { points } => {
+            ui.text("Points are fractions of the object's size");
+            let mut remove = None;
+            for (i, point) in points.iter_mut().enumerate() {
+                let stack = ui.push_id(i as i32);
+                ui.text(format!("Point {}", i + 1));
+                ui.same_line(0.0);
+                if ui.small_button(im_str!("Delete")) {
+                    remove = Some(i);
+                }
+                modified |= point.choose(ui);
+                stack.pop(ui);
+            }
+            if let Some(i) = remove {
+                points.remove(i);
+                modified = true;
+            }
+            if ui.button(im_str!("Add Point"), NORMAL_BUTTON) {
+                points.push(Vec2::new(0.5, 0.5));
+                modified = true;
+            }
+        }
+    }
+
+    modified |= shape.fill.radio(ui);
+    match &mut shape.fill {
+        Fill::None => {}
+        Fill::Solid(colour) => {
+            let stack = ui.push_id(0);
+            modified |= colour.choose(ui);
+            stack.pop(ui);
+        }
+        Fill::Gradient {
+            start,
+            end,
+            direction,
+        } => {
+            ui.text("Start");
+            let stack = ui.push_id(0);
+            modified |= start.choose(ui);
+            stack.pop(ui);
+            ui.text("End");
+            let stack = ui.push_id(1);
+            modified |= end.choose(ui);
+            stack.pop(ui);
+            modified |= direction.combo(ui);
+        }
+    }
+
+    let mut has_outline = shape.stroke.is_some();
+    if ui.checkbox(im_str!("Outline"), &mut has_outline) {
+        shape.stroke = if has_outline {
+            Some(Stroke {
+                colour: Colour::black(),
+                width: 4.0,
+            })
+        } else {
+            None
+        };
+        modified = true;
+    }
+    if let Some(stroke) = &mut shape.stroke {
+        let stack = ui.push_id(2);
+        modified |= stroke.colour.choose(ui);
+        stack.pop(ui);
+        if ui
+            .input_float(im_str!("Outline Width"), &mut stroke.width)
+            .build()
+        {
+            stroke.width = stroke.width.max(0.0);
+            modified = true;
+        }
+    }
+    modified
+}
+
+fn choose_image_rect(rect: &mut ImageRect, ui: &imgui::Ui) -> bool {
+    let mut modified = false;
+    let mut choose = |label: &ImStr, value: &mut u32, minimum: i32| {
+        let mut input = *value as i32;
+        if ui.input_int(label, &mut input).build() {
+            *value = input.max(minimum) as u32;
+            modified = true;
+        }
+    };
+    choose(im_str!("Region X"), &mut rect.x, 0);
+    choose(im_str!("Region Y"), &mut rect.y, 0);
+    choose(im_str!("Region Width"), &mut rect.width, 1);
+    choose(im_str!("Region Height"), &mut rect.height, 1);
+    modified
+}
+
+fn choose_loaded_image(image: &mut String, images: &Images, ui: &imgui::Ui) -> bool {
+    let mut sorted_keys: Vec<&String> = images.keys().collect();
+    sorted_keys.sort();
+    let mut current_image = sorted_keys.iter().position(|k| *k == image).unwrap_or(0);
+    let keys: Vec<ImString> = sorted_keys
+        .iter()
+        .map(|k| ImString::from((*k).to_string()))
+        .collect();
+    let image_names: Vec<&ImString> = keys.iter().collect();
+
+    if imgui::ComboBox::new(im_str!("Image")).build_simple_string(
+        ui,
+        &mut current_image,
+        &image_names,
+    ) {
+        if let Some(new_image) = sorted_keys.get(current_image) {
+            *image = (*new_image).to_string();
+            return true;
+        }
+    }
+    false
+}
+
+// The insets are grouped so hovering over any of them shows the preview
+fn choose_slice_insets(insets: &mut SliceInsets, ui: &imgui::Ui) -> bool {
+    let mut modified = false;
+    ui.group(|| {
+        let mut choose = |label: &ImStr, value: &mut u32| {
+            let mut input = *value as i32;
+            if ui.input_int(label, &mut input).build() {
+                *value = input.max(0) as u32;
+                modified = true;
+            }
+        };
+        choose(im_str!("Left Border"), &mut insets.left);
+        choose(im_str!("Top Border"), &mut insets.top);
+        choose(im_str!("Right Border"), &mut insets.right);
+        choose(im_str!("Bottom Border"), &mut insets.bottom);
+    });
+    modified
+}
+
+// Stretched wider and taller than the image so it's clear the corners keep their size
+fn show_nine_slice_preview(
+    ui: &imgui::Ui,
+    texture: &Texture,
+    rect: ImageRect,
+    insets: SliceInsets,
+) {
+    let preview = AABB::new(
+        0.0,
+        0.0,
+        (rect.width as f32 * 2.0).max(TOOLTIP_IMAGE_SIZE),
+        (rect.height as f32 * 1.5).max(TOOLTIP_IMAGE_SIZE / 2.0),
+    );
+    let spacing = ui.push_style_var(imgui::StyleVar::ItemSpacing([0.0, 0.0]));
+    for (i, (region, area)) in insets.pieces(rect, preview).iter().enumerate() {
+        if i % 3 != 0 {
+            ui.same_line(0.0);
+        }
+        let (uv0, uv1) = region_uvs(texture, *region);
+        imgui::Image::new(
+            imgui::TextureId::from(texture.id as usize),
+            [area.width(), area.height()],
+        )
+        .uv0(uv0)
+        .uv1(uv1)
+        .build(ui);
+    }
+    spacing.pop(ui);
+}
+
+fn choose_new_image<P: AsRef<Path>>(
+    image: &mut Sprite,
+    image_files: &mut HashMap<String, String>,
+    images: &mut Images,
+    path: P,
+) -> bool {
+    let first_key = choose_image_from_files(image_files, images, path);
+    match first_key {
+        Some(key) => {
+            *image = Sprite::Image { name: key };
+            true
+        }
+        None => {
+            log::error!("None of the new images loaded correctly");
+            false
+        }
+    }
+}
+
+fn choose_font<'a, 'b>(
+    font_name: &mut String,
+    ui: &imgui::Ui,
+    font_files: &mut HashMap<String, FontLoadInfo>,
+    fonts: &mut Fonts<'a, 'b>,
+    ttf_context: &'a TtfContext,
+    filename: &Option<String>,
 ) {
     let path = assets_path(filename, "fonts");
 
@@ -4425,8 +5872,69 @@ impl AnimationEditor {
     }
 }
 
+fn choose_imported_animation(
+    sprites: &mut Vec<Sprite>,
+    frame_durations: &mut Vec<u32>,
+    playback: &mut Playback,
+    ui: &imgui::Ui,
+    animations: &HashMap<String, FrameAnimation>,
+) -> bool {
+    if animations.is_empty() {
+        return false;
+    }
+    let mut modified = false;
+    if ui.button(im_str!("Use Imported Animation"), NORMAL_BUTTON) {
+        ui.open_popup(im_str!("Imported Animations"));
+    }
+    ui.popup(im_str!("Imported Animations"), || {
+        let mut names: Vec<&String> = animations.keys().collect();
+        names.sort();
+        for name in names {
+            if imgui::Selectable::new(&ImString::from(name.clone())).build(ui) {
+                let animation = &animations[name];
+                *sprites = animation.sprites.clone();
+                *frame_durations = animation.frame_durations.clone();
+                *playback = animation.playback;
+                modified = true;
+            }
+        }
+    });
+    modified
+}
+
+// Without durations every sprite is shown for as long as the speed says
+fn choose_frame_durations(
+    frame_durations: &mut Vec<u32>,
+    sprite_count: usize,
+    speed: Speed,
+    ui: &imgui::Ui,
+) -> bool {
+    let mut modified = false;
+    let mut use_durations = !frame_durations.is_empty();
+    if ui.checkbox(im_str!("Frame Durations"), &mut use_durations) {
+        frame_durations.clear();
+        modified = true;
+    }
+    if use_durations {
+        // The speed shows each sprite for one frame more than its animation time
+        frame_durations.resize(sprite_count, speed.to_animation_time() + 1);
+        for (index, duration) in frame_durations.iter_mut().enumerate() {
+            let mut frames = *duration as i32;
+            if ui
+                .input_int(&im_str!("Frame {} Duration", index), &mut frames)
+                .build()
+            {
+                *duration = frames.max(1) as u32;
+                modified = true;
+            }
+        }
+    }
+    modified
+}
+
 fn choose_animation(
     animation: &mut Vec<Sprite>,
+    frame_durations: &mut Vec<u32>,
     ui: &imgui::Ui,
     editor: &mut AnimationEditor,
     asset_files: &mut AssetFiles,
@@ -4449,6 +5957,21 @@ fn choose_animation(
                     editor.index = index;
                 }
             }
+            Sprite::Region { image, rect } | Sprite::NineSlice { image, rect, .. } => {
+                let texture = &images[image];
+                let (uv0, uv1) = region_uvs(texture, *rect);
+                if imgui::ImageButton::new(
+                    imgui::TextureId::from(texture.id as usize),
+                    [100.0, 100.0],
+                )
+                .uv0(uv0)
+                .uv1(uv1)
+                .build(ui)
+                {
+                    ui.open_popup(im_str!("Edit Animation"));
+                    editor.index = index;
+                }
+            }
             Sprite::Colour(colour) => {
                 // TODO: Does this work with all the buttons having the same id?
                 if imgui::ColorButton::new(
@@ -4484,12 +6007,19 @@ fn choose_animation(
         }
     });
 
+    // Durations stay with their sprites, and sprites past the end of them use the speed
+    let swap_durations = |frame_durations: &mut Vec<u32>, a: usize, b: usize| {
+        if a < frame_durations.len() && b < frame_durations.len() {
+            frame_durations.swap(a, b);
+        }
+    };
     match task {
         AnimationTask::MoveBefore => {
             if editor.index > 0 {
                 let tmp = animation[editor.index].clone();
                 animation[editor.index] = animation[editor.index - 1].clone();
                 animation[editor.index - 1] = tmp;
+                swap_durations(frame_durations, editor.index, editor.index - 1);
                 modified = true;
             }
         }
@@ -4498,12 +6028,16 @@ fn choose_animation(
                 let tmp = animation[editor.index].clone();
                 animation[editor.index] = animation[editor.index + 1].clone();
                 animation[editor.index + 1] = tmp;
+                swap_durations(frame_durations, editor.index, editor.index + 1);
                 modified = true;
             }
         }
         AnimationTask::Delete => {
             log::info!("{}", editor.index);
             animation.remove(editor.index);
+            if editor.index < frame_durations.len() {
+                frame_durations.remove(editor.index);
+            }
             modified = true;
         }
         AnimationTask::None => {}
@@ -4598,6 +6132,24 @@ fn choose_sprite(
                 }
             };
         }
+        Sprite::Region { image, rect } => {
+            modified |= choose_loaded_image(image, images, ui);
+            modified |= choose_image_rect(rect, ui);
+        }
+        Sprite::NineSlice {
+            image,
+            rect,
+            insets,
+        } => {
+            modified |= choose_loaded_image(image, images, ui);
+            modified |= choose_image_rect(rect, ui);
+            modified |= choose_slice_insets(insets, ui);
+            if ui.is_item_hovered() {
+                if let Some(texture) = images.get(image.as_str()) {
+                    ui.tooltip(|| show_nine_slice_preview(ui, texture, *rect, *insets));
+                }
+            }
+        }
         Sprite::Colour(colour) => {
             let mut colour_array = [colour.r, colour.g, colour.b, colour.a];
             modified |= imgui::ColorEdit::new(im_str!("Colour"), &mut colour_array)
@@ -4610,6 +6162,9 @@ fn choose_sprite(
                 colour_array[3],
             );
         }
+        Sprite::Shape(shape) => {
+            modified |= choose_vector_sprite(shape, ui);
+        }
     };
     modified
 }
@@ -4620,6 +6175,7 @@ fn choose_property_check(
     images: &mut Images,
     image_files: &mut HashMap<String, String>,
     filename: &Option<String>,
+    object_names: &[&str],
 ) {
     match property {
         PropertyCheck::Switch(switch_state) => {
@@ -4628,10 +6184,227 @@ fn choose_property_check(
         PropertyCheck::Sprite(sprite) => {
             choose_sprite(sprite, ui, image_files, images, filename);
         }
+        PropertyCheck::Compare {
+            property,
+            comparison,
+            to,
+        } => {
+            choose_numeric_property(property, im_str!("Property"), ui);
+            comparison.choose(ui);
+            choose_compare_to(to, ui, object_names);
+        }
+        PropertyCheck::AnimationFrame { index } => {
+            let mut frame = *index as i32;
+            if ui.input_int(im_str!("Frame"), &mut frame).build() {
+                *index = frame.max(0) as usize;
+            }
+        }
         _ => {}
     }
 }
 
+fn choose_distance_end(end: &mut DistanceEnd, ui: &imgui::Ui, object_names: &[&str]) {
+    let end_types = [
+        im_str!("Object"),
+        im_str!("Tag"),
+        im_str!("Mouse"),
+        im_str!("Point"),
+    ];
+    let mut current_end = match end {
+        DistanceEnd::Object { .. } => 0,
+        DistanceEnd::Tag { .. } => 1,
+        DistanceEnd::Mouse => 2,
+        DistanceEnd::Point(_) => 3,
+    };
+    if imgui::ComboBox::new(im_str!("End")).build_simple_string(ui, &mut current_end, &end_types) {
+        *end = match current_end {
+            0 => DistanceEnd::Object {
+                name: object_names[0].to_string(),
+            },
+            1 => DistanceEnd::Tag { tag: String::new() },
+            2 => DistanceEnd::Mouse,
+            3 => DistanceEnd::Point(Vec2::new(800.0, 450.0)),
+            _ => unreachable!(),
+        };
+    }
+
+    match end {
+        DistanceEnd::Object { name } => choose_object(name, ui, object_names),
+        DistanceEnd::Tag { tag } => {
+            choose_string(tag, ui, im_str!("Tag"));
+        }
+        DistanceEnd::Mouse => {}
+        DistanceEnd::Point(point) => {
+            point.choose(ui);
+        }
+    }
+}
+
+fn distance_end_centres(end: &DistanceEnd, objects: &[SerialiseObject]) -> Vec<(Vec2, Size)> {
+    match end {
+        DistanceEnd::Object { name } => objects
+            .iter()
+            .filter(|object| object.name == *name)
+            .map(|object| (object.position, object.size))
+            .collect(),
+        DistanceEnd::Tag { tag } => objects
+            .iter()
+            .filter(|object| object.tags.contains(tag))
+            .map(|object| (object.position, object.size))
+            .collect(),
+        DistanceEnd::Mouse => Vec::new(),
+        DistanceEnd::Point(point) => vec![(*point, Size::new(0.0, 0.0))],
+    }
+}
+
+impl Choose for DistanceMeasure {
+    fn choose(&mut self, ui: &imgui::Ui) -> bool {
+        let measures = [im_str!("Centre To Centre"), im_str!("Edge To Edge")];
+        let mut current_measure = *self as usize;
+        if imgui::ComboBox::new(im_str!("Measure")).build_simple_string(
+            ui,
+            &mut current_measure,
+            &measures,
+        ) {
+            *self = match current_measure {
+                0 => DistanceMeasure::Centre,
+                1 => DistanceMeasure::Edge,
+                _ => unreachable!(),
+            };
+            true
+        } else {
+            false
+        }
+    }
+}
+
+fn choose_layer_sorting(sorting: &mut LayerSorting, objects: &[SerialiseObject], ui: &imgui::Ui) {
+    let mut layers: Vec<u8> = objects.iter().map(|o| o.layer).collect();
+    layers.sort_unstable();
+    layers.dedup();
+    for layer in layers {
+        let mut sort = sorting.get(&layer).copied().unwrap_or_default();
+        if choose_layer_sort(&mut sort, layer, ui) {
+            if sort == LayerSort::InsertionOrder {
+                sorting.remove(&layer);
+            } else {
+                sorting.insert(layer, sort);
+            }
+        }
+    }
+}
+
+fn choose_layer_sort(sort: &mut LayerSort, layer: u8, ui: &imgui::Ui) -> bool {
+    let sorts = [
+        im_str!("Insertion Order"),
+        im_str!("Y Position"),
+        im_str!("Depth"),
+    ];
+    let mut current_sort = *sort as usize;
+    if imgui::ComboBox::new(&im_str!("Layer {} Sorting", layer)).build_simple_string(
+        ui,
+        &mut current_sort,
+        &sorts,
+    ) {
+        *sort = match current_sort {
+            0 => LayerSort::InsertionOrder,
+            1 => LayerSort::YPosition,
+            2 => LayerSort::Depth,
+            _ => unreachable!(),
+        };
+        true
+    } else {
+        false
+    }
+}
+
+fn choose_numeric_property(property: &mut NumericProperty, label: &ImStr, ui: &imgui::Ui) {
+    let properties = [
+        im_str!("X"),
+        im_str!("Y"),
+        im_str!("Width"),
+        im_str!("Height"),
+        im_str!("Angle"),
+        im_str!("Layer"),
+        im_str!("Speed"),
+    ];
+    let mut current_property = *property as usize;
+    if imgui::ComboBox::new(label).build_simple_string(ui, &mut current_property, &properties) {
+        *property = match current_property {
+            0 => NumericProperty::X,
+            1 => NumericProperty::Y,
+            2 => NumericProperty::Width,
+            3 => NumericProperty::Height,
+            4 => NumericProperty::Angle,
+            5 => NumericProperty::Layer,
+            6 => NumericProperty::Speed,
+            _ => unreachable!(),
+        };
+    }
+}
+
+impl Choose for Comparison {
+    fn choose(&mut self, ui: &imgui::Ui) -> bool {
+        let comparisons = [
+            im_str!("Equal To"),
+            im_str!("Not Equal To"),
+            im_str!("Less Than"),
+            im_str!("Less Than Or Equal To"),
+            im_str!("Greater Than"),
+            im_str!("Greater Than Or Equal To"),
+        ];
+        let mut current_comparison = *self as usize;
+        if imgui::ComboBox::new(im_str!("Comparison")).build_simple_string(
+            ui,
+            &mut current_comparison,
+            &comparisons,
+        ) {
+            *self = match current_comparison {
+                0 => Comparison::Equal,
+                1 => Comparison::NotEqual,
+                2 => Comparison::LessThan,
+                3 => Comparison::LessThanOrEqual,
+                4 => Comparison::GreaterThan,
+                5 => Comparison::GreaterThanOrEqual,
+                _ => unreachable!(),
+            };
+            true
+        } else {
+            false
+        }
+    }
+}
+
+fn choose_compare_to(to: &mut CompareTo, ui: &imgui::Ui, object_names: &[&str]) {
+    let mut compare_type = if let CompareTo::Value(_) = to { 0 } else { 1 };
+    let compare_typename = if compare_type == 0 { "Value" } else { "Object" };
+    if imgui::Slider::new(im_str!("Compare To"), std::ops::RangeInclusive::new(0, 1))
+        .display_format(&ImString::from(compare_typename.to_string()))
+        .build(ui, &mut compare_type)
+    {
+        *to = if compare_type == 0 {
+            CompareTo::Value(0.0)
+        } else {
+            CompareTo::Object {
+                name: object_names[0].to_string(),
+                property: NumericProperty::X,
+            }
+        };
+    }
+
+    match to {
+        CompareTo::Value(value) => {
+            ui.input_float(im_str!("Value"), value).build();
+        }
+        CompareTo::Object { name, property } => {
+            let stack = ui.push_id(1);
+            choose_object(name, ui, object_names);
+            choose_numeric_property(property, im_str!("Other Property"), ui);
+            stack.pop(ui);
+        }
+    }
+}
+
 impl Choose for TextResize {
     fn choose(&mut self, ui: &imgui::Ui) -> bool {
         if ui.radio_button_bool(
@@ -4666,12 +6439,134 @@ impl Choose for Colour {
     }
 }
 
+impl Choose for Effect {
+    fn choose(&mut self, ui: &imgui::Ui) -> bool {
+        const DEFAULT_FRAMES: u32 = 30;
+        let effect_names = [
+            im_str!("Freeze"),
+            im_str!("No Effect"),
+            im_str!("Slow Motion"),
+            im_str!("Shake"),
+            im_str!("Flash"),
+            im_str!("Fade In"),
+            im_str!("Fade Out"),
+            im_str!("Invert"),
+        ];
+        let mut current_effect = match self {
+            Effect::Freeze => 0,
+            Effect::None => 1,
+            Effect::SlowMotion { .. } => 2,
+            Effect::Shake { .. } => 3,
+            Effect::Flash { .. } => 4,
+            Effect::Fade { fade: Fade::In, .. } => 5,
+            Effect::Fade {
+                fade: Fade::Out, ..
+            } => 6,
+            Effect::Invert { .. } => 7,
+        };
+        let mut modified = false;
+        if imgui::ComboBox::new(im_str!("Effect")).build_simple_string(
+            ui,
+            &mut current_effect,
+            &effect_names,
+        ) {
+            *self = match current_effect {
+                0 => Effect::Freeze,
+                1 => Effect::None,
+                2 => Effect::SlowMotion {
+                    every: 2,
+                    frames: DEFAULT_FRAMES,
+                },
+                3 => Effect::Shake {
+                    amplitude: 10.0,
+                    frames: DEFAULT_FRAMES,
+                },
+                4 => Effect::Flash {
+                    colour: Colour::white(),
+                    frames: DEFAULT_FRAMES,
+                },
+                5 => Effect::Fade {
+                    fade: Fade::In,
+                    colour: Colour::black(),
+                    frames: DEFAULT_FRAMES,
+                },
+                6 => Effect::Fade {
+                    fade: Fade::Out,
+                    colour: Colour::black(),
+                    frames: DEFAULT_FRAMES,
+                },
+                7 => Effect::Invert {
+                    frames: DEFAULT_FRAMES,
+                },
+                _ => unreachable!(),
+            };
+            modified = true;
+        }
+
+        match self {
+            Effect::SlowMotion { every, .. } => {
+                let mut changed_every = *every as i32;
+                if ui
+                    .input_int(im_str!("Run Every Nth Frame"), &mut changed_every)
+                    .build()
+                {
+                    *every = changed_every.max(1) as u32;
+                    modified = true;
+                }
+            }
+            Effect::Shake { amplitude, .. } => {
+                modified |= ui
+                    .drag_float(im_str!("Amplitude"), amplitude)
+                    .min(0.0)
+                    .build();
+            }
+            Effect::Flash { colour, .. } | Effect::Fade { colour, .. } => {
+                modified |= colour.choose(ui);
+            }
+            Effect::Freeze | Effect::None | Effect::Invert { .. } => {}
+        }
+
+        match self {
+            Effect::SlowMotion { frames, .. }
+            | Effect::Shake { frames, .. }
+            | Effect::Flash { frames, .. }
+            | Effect::Fade { frames, .. }
+            | Effect::Invert { frames } => {
+                let mut seconds = *frames as f32 / FPS;
+                if ui
+                    .drag_float(im_str!("Seconds"), &mut seconds)
+                    .min(0.0)
+                    .speed(1.0 / FPS)
+                    .build()
+                {
+                    *frames = (seconds.max(0.0) * FPS) as u32;
+                    modified = true;
+                }
+            }
+            Effect::Freeze | Effect::None => {}
+        }
+
+        modified
+    }
+}
+
 impl Choose for String {
     fn choose(&mut self, ui: &imgui::Ui) -> bool {
         choose_string(self, ui, im_str!("Text"))
     }
 }
 
+// An empty key means the text isn't translated
+fn choose_text_key(key: &mut Option<String>, ui: &imgui::Ui, label: &ImStr) -> bool {
+    let mut text = key.clone().unwrap_or_default();
+    if choose_string(&mut text, ui, label) {
+        *key = if text.is_empty() { None } else { Some(text) };
+        true
+    } else {
+        false
+    }
+}
+
 fn choose_string(text: &mut String, ui: &imgui::Ui, label: &ImStr) -> bool {
     let mut change_text = ImString::from(text.to_owned());
     if ui
@@ -4688,12 +6583,21 @@ fn choose_string(text: &mut String, ui: &imgui::Ui, label: &ImStr) -> bool {
 
 impl Choose for AnimationType {
     fn choose(&mut self, ui: &imgui::Ui) -> bool {
-        if ui.radio_button_bool(im_str!("Loop Animation"), *self == AnimationType::Loop) {
-            *self = !*self;
-            true
-        } else {
-            false
+        let mut modified = self.combo(ui);
+        if let AnimationType::LoopTimes(times) = self {
+            let mut input = *times as i32;
+            if ui.input_int(im_str!("Times"), &mut input).build() {
+                *times = input.max(1) as u32;
+                modified = true;
+            }
         }
+        modified
+    }
+}
+
+impl Choose for Playback {
+    fn choose(&mut self, ui: &imgui::Ui) -> bool {
+        self.combo(ui)
     }
 }
 
@@ -4933,20 +6837,24 @@ fn choose_angle_setter(setter: &mut AngleSetter, ui: &imgui::Ui, object_names: &
 
 impl Choose for JustifyText {
     fn choose(&mut self, ui: &imgui::Ui) -> bool {
-        let mut justify_type = if *self == JustifyText::Left { 0 } else { 1 };
-        let justify_typename = if justify_type == 0 {
-            "Left".to_string()
-        } else {
-            "Centre".to_string()
+        let mut justify_type = match self {
+            JustifyText::Left => 0,
+            JustifyText::Centre => 1,
+            JustifyText::Right => 2,
         };
-        if imgui::Slider::new(im_str!("Justify Text"), std::ops::RangeInclusive::new(0, 1))
-            .display_format(&ImString::from(justify_typename))
+        let justify_typename = match justify_type {
+            0 => "Left",
+            1 => "Centre",
+            _ => "Right",
+        };
+        if imgui::Slider::new(im_str!("Justify Text"), std::ops::RangeInclusive::new(0, 2))
+            .display_format(&ImString::from(justify_typename.to_string()))
             .build(ui, &mut justify_type)
         {
-            *self = if justify_type == 0 {
-                JustifyText::Left
-            } else {
-                JustifyText::Centre
+            *self = match justify_type {
+                0 => JustifyText::Left,
+                1 => JustifyText::Centre,
+                _ => JustifyText::Right,
             };
             true
         } else {
@@ -4955,6 +6863,67 @@ impl Choose for JustifyText {
     }
 }
 
+impl Choose for TextLayout {
+    fn choose(&mut self, ui: &imgui::Ui) -> bool {
+        let mut modified = self.vertical_align.combo(ui);
+        if ui
+            .input_float(im_str!("Line Spacing"), &mut self.line_spacing)
+            .build()
+        {
+            self.line_spacing = self.line_spacing.max(0.0);
+            modified = true;
+        }
+        modified |= ui.checkbox(im_str!("Wrap Words"), &mut self.wrap);
+        modified |= ui.checkbox(im_str!("Shrink to Fit"), &mut self.shrink_to_fit);
+
+        let mut has_outline = self.outline.is_some();
+        if ui.checkbox(im_str!("Outline"), &mut has_outline) {
+            self.outline = if has_outline {
+                Some(TextOutline {
+                    colour: Colour::black(),
+                    width: 2.0,
+                })
+            } else {
+                None
+            };
+            modified = true;
+        }
+        if let Some(outline) = &mut self.outline {
+            let stack = ui.push_id(0);
+            modified |= outline.colour.choose(ui);
+            stack.pop(ui);
+            if ui
+                .input_float(im_str!("Outline Width"), &mut outline.width)
+                .build()
+            {
+                outline.width = outline.width.max(0.0);
+                modified = true;
+            }
+        }
+
+        let mut has_shadow = self.shadow.is_some();
+        if ui.checkbox(im_str!("Shadow"), &mut has_shadow) {
+            self.shadow = if has_shadow {
+                Some(TextShadow {
+                    colour: Colour::rgba(0.0, 0.0, 0.0, 0.5),
+                    offset: Vec2::new(3.0, 3.0),
+                })
+            } else {
+                None
+            };
+            modified = true;
+        }
+        if let Some(shadow) = &mut self.shadow {
+            let stack = ui.push_id(1);
+            modified |= shadow.colour.choose(ui);
+            ui.text("Shadow Offset");
+            modified |= shadow.offset.choose(ui);
+            stack.pop(ui);
+        }
+        modified
+    }
+}
+
 fn choose_motion(
     motion: &mut Motion,
     ui: &imgui::Ui,
@@ -5520,6 +7489,174 @@ impl EnumSetters for LayerSetter {
     }
 }
 
+impl EnumSetters for AnimationType {
+    fn to_value(&self) -> usize {
+        match self {
+            AnimationType::Loop => 0,
+            AnimationType::PlayOnce => 1,
+            AnimationType::LoopTimes(_) => 2,
+        }
+    }
+
+    fn from_value(value: usize) -> Self {
+        match value {
+            0 => AnimationType::Loop,
+            1 => AnimationType::PlayOnce,
+            2 => AnimationType::LoopTimes(2),
+            _ => unreachable!(),
+        }
+    }
+
+    fn component<F: Fn(&mut Self, &ImStr, &[&ImStr]) -> bool>(&mut self, f: F) -> bool {
+        let types = [im_str!("Loop"), im_str!("Play Once"), im_str!("Loop Times")];
+
+        f(self, im_str!("Animation Type"), &types)
+    }
+}
+
+impl EnumSetters for Playback {
+    fn to_value(&self) -> usize {
+        match self {
+            Playback::Forward => 0,
+            Playback::Reverse => 1,
+            Playback::PingPong => 2,
+        }
+    }
+
+    fn from_value(value: usize) -> Self {
+        match value {
+            0 => Playback::Forward,
+            1 => Playback::Reverse,
+            2 => Playback::PingPong,
+            _ => unreachable!(),
+        }
+    }
+
+    fn component<F: Fn(&mut Self, &ImStr, &[&ImStr]) -> bool>(&mut self, f: F) -> bool {
+        let types = [im_str!("Forward"), im_str!("Reverse"), im_str!("Ping-Pong")];
+
+        f(self, im_str!("Playback"), &types)
+    }
+}
+
+impl EnumSetters for VectorShape {
+    fn to_value(&self) -> usize {
+        match self {
+            VectorShape::Ellipse => 0,
+            VectorShape::RoundedRectangle { .. } => 1,
+            VectorShape::Line { .. } => 2,
+            VectorShape::Polygon { .. } => 3,
+        }
+    }
+
+    fn from_value(value: usize) -> Self {
+        match value {
+            0 => VectorShape::Ellipse,
+            1 => VectorShape::RoundedRectangle { radius: 10.0 },
+            2 => VectorShape::Line {
+                start: Vec2::new(0.0, 0.5),
+                end: Vec2::new(1.0, 0.5),
+            },
+            3 => VectorShape::Polygon {
+                points: vec![
+                    Vec2::new(0.5, 0.0),
+                    Vec2::new(1.0, 1.0),
+                    Vec2::new(0.0, 1.0),
+                ],
+            },
+            _ => unreachable!(),
+        }
+    }
+
+    fn component<F: Fn(&mut Self, &ImStr, &[&ImStr]) -> bool>(&mut self, f: F) -> bool {
+        let types = [
+            im_str!("Ellipse"),
+            im_str!("Rounded Rectangle"),
+            im_str!("Line"),
+            im_str!("Polygon"),
+        ];
+
+        f(self, im_str!("Shape"), &types)
+    }
+}
+
+impl EnumSetters for Fill {
+    fn to_value(&self) -> usize {
+        match self {
+            Fill::None => 0,
+            Fill::Solid(_) => 1,
+            Fill::Gradient { .. } => 2,
+        }
+    }
+
+    fn from_value(value: usize) -> Self {
+        match value {
+            0 => Fill::None,
+            1 => Fill::Solid(Colour::black()),
+            2 => Fill::Gradient {
+                start: Colour::black(),
+                end: Colour::white(),
+                direction: GradientDirection::Vertical,
+            },
+            _ => unreachable!(),
+        }
+    }
+
+    fn component<F: Fn(&mut Self, &ImStr, &[&ImStr]) -> bool>(&mut self, f: F) -> bool {
+        let types = [im_str!("No Fill"), im_str!("Solid"), im_str!("Gradient")];
+
+        f(self, im_str!("Fill"), &types)
+    }
+}
+
+impl EnumSetters for GradientDirection {
+    fn to_value(&self) -> usize {
+        match self {
+            GradientDirection::Horizontal => 0,
+            GradientDirection::Vertical => 1,
+        }
+    }
+
+    fn from_value(value: usize) -> Self {
+        match value {
+            0 => GradientDirection::Horizontal,
+            1 => GradientDirection::Vertical,
+            _ => unreachable!(),
+        }
+    }
+
+    fn component<F: Fn(&mut Self, &ImStr, &[&ImStr]) -> bool>(&mut self, f: F) -> bool {
+        let types = [im_str!("Left to Right"), im_str!("Top to Bottom")];
+
+        f(self, im_str!("Direction"), &types)
+    }
+}
+
+impl EnumSetters for VerticalAlign {
+    fn to_value(&self) -> usize {
+        match self {
+            VerticalAlign::Top => 0,
+            VerticalAlign::Middle => 1,
+            VerticalAlign::Bottom => 2,
+        }
+    }
+
+    fn from_value(value: usize) -> Self {
+        match value {
+            0 => VerticalAlign::Top,
+            1 => VerticalAlign::Middle,
+            2 => VerticalAlign::Bottom,
+            _ => unreachable!(),
+        }
+    }
+
+    fn component<F: Fn(&mut Self, &ImStr, &[&ImStr]) -> bool>(&mut self, f: F) -> bool {
+        let types = [im_str!("Top"), im_str!("Middle"), im_str!("Bottom")];
+
+        f(self, im_str!("Vertical Align"), &types)
+    }
+}
+
 impl EnumSetters for Speed {
     fn to_value(&self) -> usize {
         match self {
@@ -5782,7 +7919,7 @@ fn rename_in_instructions(instructions: &mut Vec<Instruction>, old_name: &str, n
     for instruction in instructions.iter_mut() {
         for trigger in instruction.triggers.iter_mut() {
             match trigger {
-                Trigger::Collision(CollisionWith::Object { name }) => {
+                Trigger::Collision(CollisionWith::Object { name }, _) => {
                     rename(name);
                 }
                 Trigger::Input(Input::Mouse {
@@ -5791,8 +7928,22 @@ fn rename_in_instructions(instructions: &mut Vec<Instruction>, old_name: &str, n
                 }) => {
                     rename(name);
                 }
-                Trigger::CheckProperty { name, .. } => {
+                Trigger::CheckProperty { name, check } => {
                     rename(name);
+                    if let PropertyCheck::Compare {
+                        to: CompareTo::Object { name, .. },
+                        ..
+                    } = check
+                    {
+                        rename(name);
+                    }
+                }
+                Trigger::Distance { from, to, .. } => {
+                    for end in [from, to] {
+                        if let DistanceEnd::Object { name } = end {
+                            rename(name);
+                        }
+                    }
                 }
                 _ => {}
             }
@@ -56,6 +56,12 @@ struct Config {
     volume: f32,
     render_each_frame: bool,
     sensitivity: f32,
+    #[serde(default = "default_language")]
+    language: String,
+}
+
+fn default_language() -> String {
+    DEFAULT_LANGUAGE.to_string()
 }
 
 fn yaml_from_str<T: DeserializeOwned>(text: &str) -> WeeResult<T> {
@@ -76,6 +82,7 @@ impl Config {
         GameSettings {
             volume: self.volume,
             render_each_frame: self.render_each_frame,
+            language: self.language.clone(),
         }
     }
 }
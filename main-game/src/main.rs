@@ -27,6 +27,9 @@ use wee_common::{Vec2 as WeeVec2, *};
 
 use wee::*;
 
+mod transition;
+use transition::{TransitionSettings, Transitions};
+
 const PROJECTION_WIDTH: f32 = 1600.0;
 const PROJECTION_HEIGHT: f32 = 900.0;
 const WIDE_RATIO: f32 = PROJECTION_WIDTH / PROJECTION_HEIGHT + 0.0001;
@@ -44,6 +47,9 @@ const BOSS_GAME_INTERVAL: i32 = 15;
 const INCREASE_SPEED_AFTER_GAMES: i32 = 5;
 const VOLUME: f32 = 0.5;
 const FUTURES_WORKAROUND_LIMIT: usize = 30;
+// Small images share textures so drawing them doesn't need as many texture binds
+const ATLAS_PAGE_SIZE: u32 = 2048;
+const ATLAS_MAX_IMAGE_SIZE: u16 = 512;
 
 async fn load_images<P: AsRef<Path>>(
     image_files: &HashMap<String, String>,
@@ -64,26 +70,105 @@ async fn load_images<P: AsRef<Path>>(
     }
 
     for path in &paths {
-        loading_images.push(texture::load_texture(path));
+        loading_images.push(texture::load_image(path));
     }
 
-    let mut textures = Vec::new();
+    let mut loaded = Vec::new();
     let mut rest = loading_images.split_off(FUTURES_WORKAROUND_LIMIT.min(loading_images.len()));
     while !loading_images.is_empty() {
-        textures.append(&mut join_all(loading_images).await);
+        loaded.append(&mut join_all(loading_images).await);
         loading_images =
             rest.split_off((rest.len() as i64 - FUTURES_WORKAROUND_LIMIT as i64).max(0) as usize);
     }
 
-    for (key, texture) in image_files.keys().zip(textures) {
-        let texture = texture?;
-        texture.set_filter(macroquad::texture::FilterMode::Nearest);
-        images.insert(key.to_string(), texture);
+    let mut pixels = HashMap::new();
+    for (key, image) in image_files.keys().zip(loaded) {
+        pixels.insert(key.to_string(), image?);
+    }
+
+    let layout = AtlasLayout::pack(
+        pixels
+            .iter()
+            .filter(|(_, image)| {
+                image.width <= ATLAS_MAX_IMAGE_SIZE && image.height <= ATLAS_MAX_IMAGE_SIZE
+            })
+            .map(|(name, image)| (name.as_str(), image.width as u32, image.height as u32)),
+        ATLAS_PAGE_SIZE,
+    );
+    let mut pages: Vec<Image> = layout
+        .pages
+        .iter()
+        .map(|(width, height)| Image::gen_image_color(*width as u16, *height as u16, BLANK))
+        .collect();
+    for (name, placement) in &layout.placements {
+        copy_into_atlas(&pixels[name], &mut pages[placement.page], placement.rect);
+    }
+    let page_textures: Vec<Texture2D> = pages.iter().map(nearest_texture).collect();
+    log::debug!(
+        "Packed {} images into {} atlas textures",
+        layout.placements.len(),
+        page_textures.len()
+    );
+
+    for (name, image) in &pixels {
+        let loaded_image = match layout.placements.get(name) {
+            Some(placement) => LoadedImage {
+                texture: page_textures[placement.page],
+                area: MacroRect::new(
+                    placement.rect.x as f32,
+                    placement.rect.y as f32,
+                    placement.rect.width as f32,
+                    placement.rect.height as f32,
+                ),
+            },
+            None => LoadedImage {
+                texture: nearest_texture(image),
+                area: MacroRect::new(0.0, 0.0, image.width as f32, image.height as f32),
+            },
+        };
+        images.insert(name.clone(), loaded_image);
     }
 
     Ok(images)
 }
 
+fn nearest_texture(image: &Image) -> Texture2D {
+    let texture = Texture2D::from_image(image);
+    texture.set_filter(macroquad::texture::FilterMode::Nearest);
+    texture
+}
+
+fn copy_into_atlas(image: &Image, page: &mut Image, rect: ImageRect) {
+    let row_length = rect.width as usize * 4;
+    let page_width = page.width as usize;
+    for row in 0..rect.height as usize {
+        let source = row * row_length;
+        let destination = ((rect.y as usize + row) * page_width + rect.x as usize) * 4;
+        page.bytes[destination..destination + row_length]
+            .copy_from_slice(&image.bytes[source..source + row_length]);
+    }
+}
+
+async fn load_collision_masks(
+    game_data: &GameData,
+    base_path: impl AsRef<Path>,
+) -> WeeResult<CollisionMasks> {
+    if !game_data.uses_pixel_collision() {
+        return Ok(CollisionMasks::default());
+    }
+
+    log::debug!("Start loading collision masks");
+    let base_path = base_path.as_ref().join("images");
+    let mut masks = HashMap::new();
+    for (name, filename) in &game_data.asset_files.images {
+        let path = base_path.join(filename);
+        let bytes = macroquad::file::load_file(&path.to_string_lossy()).await?;
+        masks.insert(name.to_string(), AlphaMask::from_bytes(&bytes)?);
+    }
+
+    Ok(CollisionMasks::new(masks))
+}
+
 async fn load_sounds(
     sound_files: &HashMap<String, String>,
     base_path: impl AsRef<Path>,
@@ -191,13 +276,68 @@ async fn load_fonts(
         let path = base_path.join(&font_info.filename);
 
         let font = macroquad::text::load_ttf_font(path.to_str().unwrap()).await?;
-        fonts.insert(key.to_string(), (font, font_info.size as u16));
+        fonts.insert(
+            key.to_string(),
+            LoadedFont {
+                font,
+                size: font_info.size as u16,
+            },
+        );
     }
     Ok(fonts)
 }
 
-type Images = HashMap<String, Texture2D>;
-type Fonts = HashMap<String, (Font, u16)>;
+#[derive(Debug, Copy, Clone)]
+struct LoadedImage {
+    texture: Texture2D,
+    // Where the image is in the texture, in pixels
+    area: MacroRect,
+}
+
+impl LoadedImage {
+    // Texture coordinates for the region of the image, or all of it
+    fn texture_coordinates(&self, region: Option<ImageRect>) -> MacroRect {
+        let area = match region {
+            Some(region) => {
+                let region = region.clamped(self.area.w as u32, self.area.h as u32);
+                MacroRect::new(
+                    self.area.x + region.x as f32,
+                    self.area.y + region.y as f32,
+                    region.width as f32,
+                    region.height as f32,
+                )
+            }
+            None => self.area,
+        };
+        let width = self.texture.width();
+        let height = self.texture.height();
+        MacroRect::new(
+            area.x / width,
+            area.y / height,
+            area.w / width,
+            area.h / height,
+        )
+    }
+}
+
+type Images = HashMap<String, LoadedImage>;
+#[derive(Debug, Copy, Clone)]
+struct LoadedFont {
+    font: Font,
+    size: u16,
+}
+
+impl FontMetrics for LoadedFont {
+    fn text_width(&self, text: &str) -> f32 {
+        macroquad::text::measure_text(text, Some(self.font), self.size, 1.0).width
+    }
+
+    fn line_height(&self) -> f32 {
+        self.size as f32
+    }
+}
+
+type Fonts = HashMap<String, LoadedFont>;
 type Sounds = HashMap<String, Sound>;
 
 fn json_from_str<'a, T: Deserialize<'a>>(text: &'a str) -> WeeResult<T> {
@@ -213,6 +353,54 @@ pub async fn load_game_data(filename: impl AsRef<Path>) -> WeeResult<GameData> {
     json_from_str(&json_string)
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+struct Settings {
+    // Text with a key is translated into this language when there's a string table for it
+    language: String,
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings {
+            language: DEFAULT_LANGUAGE.to_string(),
+        }
+    }
+}
+
+// Missing tables are skipped so games fall back to the default language, then their own text
+async fn load_translations(directory: &str, language: &str) -> Translations {
+    let mut tables = Vec::new();
+    for language in fallback_languages(language) {
+        let path = table_path(directory, language)
+            .to_string_lossy()
+            .replace("\\", "/");
+        if let Ok(json) = macroquad::file::load_string(&path).await {
+            match parse_string_table(&json) {
+                Ok(table) => tables.push(table),
+                Err(error) => log::error!("Couldn't read string table {}: {}", path, error),
+            }
+        }
+    }
+    Translations::new(tables)
+}
+
+// Games in the same directory share their string tables
+async fn localise_games(games: &mut HashMap<String, GameData>, language: &str) {
+    let mut translations: HashMap<String, Translations> = HashMap::new();
+    for (filename, game) in games.iter_mut() {
+        let directory = Path::new(filename)
+            .parent()
+            .map(|directory| directory.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if !translations.contains_key(&directory) {
+            let loaded = load_translations(&directory, language).await;
+            translations.insert(directory.clone(), loaded);
+        }
+        game.localise(&translations[&directory]);
+    }
+}
+
 #[derive(Clone)]
 struct LoadedGameData {
     data: GameData,
@@ -225,7 +413,7 @@ impl LoadedGameData {
         let game_data = load_game_data(&filename).await?;
         let base_path = filename.as_ref().parent().unwrap();
         let data = LoadedGameData {
-            assets: Assets::load(&game_data.asset_files, base_path).await?,
+            assets: Assets::load(&game_data, base_path).await?,
             data: game_data,
         };
         Ok(data)
@@ -238,16 +426,19 @@ struct Assets {
     music: Option<Music>,
     sounds: Sounds,
     fonts: Fonts,
+    masks: CollisionMasks,
 }
 
 impl Assets {
-    async fn load(asset_files: &AssetFiles, base_path: impl AsRef<Path>) -> WeeResult<Assets> {
+    async fn load(game_data: &GameData, base_path: impl AsRef<Path>) -> WeeResult<Assets> {
         log::debug!("Loading assets");
+        let asset_files = &game_data.asset_files;
         let assets = Assets {
             images: load_images(&asset_files.images, &base_path).await?,
             music: load_music(&asset_files.music, &base_path).await?,
             sounds: load_sounds(&asset_files.audio, &base_path).await?,
             fonts: load_fonts(&asset_files.fonts, &base_path).await?,
+            masks: load_collision_masks(game_data, &base_path).await?,
         };
         Ok(assets)
     }
@@ -322,61 +513,54 @@ impl Progress {
     }
 }
 
-// Adapted from draw_rectangle/draw_texture_ex in macroquad
-fn draw_rectangle_ex(
-    color: Color,
-    x: f32,
-    y: f32,
-    w: f32,
-    h: f32,
-    rotation: f32,
-    pivot: Option<glam::f32::Vec2>,
-) {
+// Adapted from draw_rectangle/draw_texture_ex in macroquad, with the corners already transformed
+fn draw_quad(texture: Option<Texture2D>, uv: MacroRect, corners: [WeeVec2; 4], color: Color) {
     unsafe {
         let gl = macroquad::window::get_internal_gl().quad_gl;
 
-        let pivot = pivot.unwrap_or_else(|| vec2(x + w / 2., y + h / 2.));
-        let m = pivot;
-        let p = [
-            vec2(x, y) - pivot,
-            vec2(x + w, y) - pivot,
-            vec2(x + w, y + h) - pivot,
-            vec2(x, y + h) - pivot,
-        ];
-        let r = rotation;
-        let p = [
-            vec2(
-                p[0].x * r.cos() - p[0].y * r.sin(),
-                p[0].x * r.sin() + p[0].y * r.cos(),
-            ) + m,
-            vec2(
-                p[1].x * r.cos() - p[1].y * r.sin(),
-                p[1].x * r.sin() + p[1].y * r.cos(),
-            ) + m,
-            vec2(
-                p[2].x * r.cos() - p[2].y * r.sin(),
-                p[2].x * r.sin() + p[2].y * r.cos(),
-            ) + m,
-            vec2(
-                p[3].x * r.cos() - p[3].y * r.sin(),
-                p[3].x * r.sin() + p[3].y * r.cos(),
-            ) + m,
-        ];
+        let p = corners;
+        let (left, top, right, bottom) = (uv.x, uv.y, uv.x + uv.w, uv.y + uv.h);
         #[rustfmt::skip]
         let vertices = [
-            Vertex::new(p[0].x, p[0].y, 0.0,  0.0,  0.0, color),
-            Vertex::new(p[1].x, p[1].y, 0.0, 1.0,  0.0, color),
-            Vertex::new(p[2].x, p[2].y, 0.0, 1.0, 1.0, color),
-            Vertex::new(p[3].x, p[3].y, 0.0,  0.0, 1.0, color),
+            Vertex::new(p[0].x, p[0].y, 0.0, left, top, color),
+            Vertex::new(p[1].x, p[1].y, 0.0, right, top, color),
+            Vertex::new(p[2].x, p[2].y, 0.0, right, bottom, color),
+            Vertex::new(p[3].x, p[3].y, 0.0, left, bottom, color),
         ];
         let indices: [u16; 6] = [0, 1, 2, 0, 2, 3];
 
-        gl.texture(None);
+        gl.texture(texture);
         gl.draw_mode(DrawMode::Triangles);
         gl.geometry(&vertices, &indices);
     }
 }
 
+// Shape sprites are already split into triangles on screen by wee
+fn draw_triangles(shape: &[ShapeVertex]) {
+    // Keeps each batch well inside the index range
+    const MAX_VERTICES: usize = 3000;
+    for triangles in shape.chunks(MAX_VERTICES) {
+        let vertices: Vec<Vertex> = triangles
+            .iter()
+            .map(|vertex| {
+                let p = vertex.position;
+                Vertex::new(p.x, p.y, 0.0, 0.0, 0.0, to_color(vertex.colour))
+            })
+            .collect();
+        let indices: Vec<u16> = (0..vertices.len() as u16).collect();
+        unsafe {
+            let gl = macroquad::window::get_internal_gl().quad_gl;
+            gl.texture(None);
+            gl.draw_mode(DrawMode::Triangles);
+            gl.geometry(&vertices, &indices);
+        }
+    }
+}
+
+fn to_color(colour: Colour) -> Color {
+    Color::new(colour.r, colour.g, colour.b, colour.a)
+}
+
 fn draw_game(
     game: &Game,
     images: &Images,
@@ -384,6 +568,12 @@ fn draw_game(
     intro_font: &Font,
     drawn_text: &HashMap<String, DrawnText>,
 ) {
+    set_game_camera(game.screen_effects().offset);
+    draw_game_contents(game, images, fonts, intro_font, drawn_text);
+}
+
+// Fits the projection to the window, letterboxing whatever is left over
+fn set_game_camera(shake: WeeVec2) {
     let screen_width = window::screen_width();
     let screen_height = window::screen_height();
     let ratio = screen_width / screen_height;
@@ -393,8 +583,8 @@ fn draw_game(
             let scaled_projection_width = (PROJECTION_HEIGHT / screen_height) * screen_width;
             let camera_x = (scaled_projection_width - PROJECTION_WIDTH) / 2.0;
             let camera = Camera2D::from_display_rect(MacroRect::new(
-                -camera_x,
-                0.0,
+                -camera_x - shake.x,
+                -shake.y,
                 scaled_projection_width,
                 PROJECTION_HEIGHT,
             ));
@@ -419,8 +609,8 @@ fn draw_game(
         let scaled_projection_height = (PROJECTION_WIDTH / screen_width) * screen_height;
         let camera_y = (scaled_projection_height - PROJECTION_HEIGHT) / 2.0;
         let camera = macroquad::camera::Camera2D::from_display_rect(MacroRect::new(
-            0.0,
-            -camera_y,
+            -shake.x,
+            -camera_y - shake.y,
             PROJECTION_WIDTH,
             scaled_projection_height,
         ));
@@ -440,172 +630,145 @@ fn draw_game(
         }
     } else {
         let camera = macroquad::camera::Camera2D::from_display_rect(MacroRect::new(
-            0.0,
-            0.0,
+            -shake.x,
+            -shake.y,
             PROJECTION_WIDTH,
             PROJECTION_HEIGHT,
         ));
         macroquad::camera::set_camera(&camera);
     }
+}
+
+fn draw_game_contents(
+    game: &Game,
+    images: &Images,
+    fonts: &Fonts,
+    intro_font: &Font,
+    drawn_text: &HashMap<String, DrawnText>,
+) {
     clear_background(BLACK);
     macroquad::shapes::draw_rectangle(0.0, 0.0, PROJECTION_WIDTH, PROJECTION_HEIGHT, WHITE);
-    // Draw background
-    for part in &game.background {
-        match &part.sprite {
-            Sprite::Image { name } => {
-                let params = macroquad::texture::DrawTextureParams {
-                    dest_size: Some(macroquad::math::Vec2::new(
-                        part.area.width(),
-                        part.area.height(),
-                    )),
-                    source: None,
-                    rotation: 0.0,
-                    pivot: None,
-                    flip_x: false,
-                    flip_y: false,
+
+    for command in game.draw_commands(drawn_text, |font| fonts.get(font)) {
+        match command {
+            DrawCommand::Image {
+                name,
+                region,
+                transform,
+            } => {
+                let image = images[&name];
+                let uv = image.texture_coordinates(region);
+                draw_quad(Some(image.texture), uv, transform.corners(), WHITE)
+            }
+            DrawCommand::Rectangle { colour, transform } => {
+                let uv = MacroRect::new(0.0, 0.0, 1.0, 1.0);
+                draw_quad(None, uv, transform.corners(), to_color(colour))
+            }
+            DrawCommand::Triangles { vertices } => draw_triangles(&vertices),
+            DrawCommand::Text {
+                text,
+                font,
+                colour,
+                scale,
+                transform,
+                ..
+            } => {
+                // Text can't be rotated so it's drawn along the bottom of its unrotated line,
+                // centred in the line's height
+                let LoadedFont { font, size } = fonts[&font];
+                let dimensions = macroquad::text::measure_text(&text, Some(font), size, scale);
+                let bottom =
+                    transform.area.max.y - (transform.area.height() - dimensions.height) / 2.0;
+                let params = macroquad::text::TextParams {
+                    font,
+                    font_size: size,
+                    font_scale: scale,
+                    font_scale_aspect: 1.0,
+                    color: to_color(colour),
                 };
-                draw_texture_ex(
-                    images[name],
-                    part.area.min.x,
-                    part.area.min.y,
-                    macroquad::color::WHITE,
-                    params,
-                );
+                macroquad::text::draw_text_ex(&text, transform.area.min.x, bottom, params);
             }
-            Sprite::Colour(colour) => macroquad::shapes::draw_rectangle(
-                part.area.min.x,
-                part.area.min.y,
-                part.area.max.x,
-                part.area.max.y,
-                macroquad::color::Color::new(colour.r, colour.g, colour.b, colour.a),
-            ),
+            DrawCommand::IntroText { text, centre } => draw_intro_text(&text, centre, intro_font),
+            DrawCommand::Invert => {
+                let material = unsafe { invert_material() };
+                macroquad::material::gl_use_material(material);
+                cover_screen(WHITE);
+                macroquad::material::gl_use_default_material();
+            }
+            DrawCommand::Cover { colour } => cover_screen(to_color(colour)),
         }
     }
+}
 
-    // Draw Objects
-    let mut layers: Vec<u8> = game.objects.values().map(|o| o.layer).collect();
-    layers.sort_unstable();
-    layers.dedup();
-    layers.reverse();
-    for layer in layers.into_iter() {
-        for (key, object) in game.objects.iter() {
-            if object.layer == layer {
-                match &object.sprite {
-                    Sprite::Image { name } => {
-                        let origin = object.origin_in_world();
-                        let origin = macroquad::math::Vec2::new(origin.x, origin.y);
-                        let params = macroquad::texture::DrawTextureParams {
-                            dest_size: Some(macroquad::math::Vec2::new(
-                                object.size.width,
-                                object.size.height,
-                            )),
-                            source: None,
-                            rotation: object.angle.to_radians(),
-                            pivot: Some(origin),
-                            flip_x: object.flip.horizontal,
-                            flip_y: object.flip.vertical,
-                        };
-                        draw_texture_ex(
-                            images[name],
-                            object.position.x - object.size.width / 2.0,
-                            object.position.y - object.size.height / 2.0,
-                            macroquad::color::WHITE,
-                            params,
-                        );
-                    }
-                    Sprite::Colour(colour) => {
-                        let origin = object.origin_in_world();
-                        let origin = macroquad::math::Vec2::new(origin.x, origin.y);
-                        draw_rectangle_ex(
-                            Color::new(colour.r, colour.g, colour.b, colour.a),
-                            object.position.x - object.size.width / 2.0,
-                            object.position.y - object.size.height / 2.0,
-                            object.size.width,
-                            object.size.height,
-                            object.angle.to_radians(),
-                            Some(origin),
-                        );
-                    }
-                }
-
-                if drawn_text.contains_key(key) {
-                    let colour = drawn_text[key].colour;
-                    let colour = Color::new(colour.r, colour.g, colour.b, colour.a);
-                    let size = macroquad::text::measure_text(
-                        &drawn_text[key].text,
-                        Some(fonts[&drawn_text[key].font].0),
-                        fonts[&drawn_text[key].font].1,
-                        1.0,
-                    );
-                    let position = match drawn_text[key].justify {
-                        JustifyText::Left => WeeVec2::new(
-                            object.position.x - object.half_width(),
-                            object.position.y + size.height / 2.0,
-                        ),
-                        JustifyText::Centre => WeeVec2::new(
-                            object.position.x - size.width / 2.0,
-                            object.position.y + size.height / 2.0,
-                        ),
-                    };
-                    let params = macroquad::text::TextParams {
-                        font: fonts[&drawn_text[key].font].0,
-                        font_size: fonts[&drawn_text[key].font].1,
-                        font_scale: 1.0,
-                        font_scale_aspect: 1.0,
-                        color: colour,
-                    };
-                    macroquad::text::draw_text_ex(
-                        &drawn_text[key].text,
-                        position.x,
-                        position.y,
-                        params,
-                    );
-                }
-            }
-        }
+fn draw_intro_text(text: &str, centre: WeeVec2, intro_font: &Font) {
+    const FONT_SIZE: u16 = 174;
+    let size = macroquad::text::measure_text(text, Some(*intro_font), FONT_SIZE, 1.0);
+    let params = |color| macroquad::text::TextParams {
+        font: *intro_font,
+        font_size: FONT_SIZE,
+        font_scale: 1.0,
+        font_scale_aspect: 1.0,
+        color,
+    };
+    let x = centre.x - size.width / 2.0;
+    let y = centre.y + size.height / 2.0;
+    for (offset_x, offset_y) in [(-2.0, 0.0), (0.0, -2.0), (2.0, 0.0), (0.0, 2.0)].iter() {
+        macroquad::text::draw_text_ex(text, x + offset_x, y + offset_y, params(BLACK));
     }
+    macroquad::text::draw_text_ex(text, x, y, params(WHITE));
+}
 
-    // Draw Intro Text
-    const INTRO_TEXT_TIME: u32 = 60;
-    if game.frames.ran < INTRO_TEXT_TIME {
-        let colour = BLACK;
-        let size = macroquad::text::measure_text(&game.intro_text, Some(*intro_font), 174, 1.00);
-        let params = macroquad::text::TextParams {
-            font: *intro_font,
-            font_size: 174,
-            font_scale: 1.00,
-            font_scale_aspect: 1.0,
-            color: colour,
-        };
-        let draw_text_border = |offset_x, offset_y| {
-            macroquad::text::draw_text_ex(
-                &game.intro_text,
-                PROJECTION_WIDTH / 2.0 - size.width / 2.0 + offset_x,
-                PROJECTION_HEIGHT / 2.0 + offset_y,
-                params,
-            );
-        };
-        draw_text_border(-2.0, 0.0);
-        draw_text_border(0.0, -2.0);
-        draw_text_border(2.0, 0.0);
-        draw_text_border(0.0, 2.0);
-
-        let colour = WHITE;
-        let size = macroquad::text::measure_text(&game.intro_text, Some(*intro_font), 174, 1.0);
-        let params = macroquad::text::TextParams {
-            font: *intro_font,
-            font_size: 174,
-            font_scale: 1.0,
-            font_scale_aspect: 1.0,
-            color: colour,
+// Covers more than the projection so shaking doesn't reveal the edges
+fn cover_screen(colour: Color) {
+    macroquad::shapes::draw_rectangle(
+        -PROJECTION_WIDTH,
+        -PROJECTION_HEIGHT,
+        PROJECTION_WIDTH * 3.0,
+        PROJECTION_HEIGHT * 3.0,
+        colour,
+    )
+}
+
+static mut INVERT_MATERIAL: Option<macroquad::material::Material> = None;
+
+// Draws white as one minus whatever is already on screen
+unsafe fn invert_material() -> macroquad::material::Material {
+    const VERTEX: &str = r#"#version 100
+attribute vec3 position;
+attribute vec2 texcoord;
+attribute vec4 color0;
+
+uniform mat4 Model;
+uniform mat4 Projection;
+
+void main() {
+    gl_Position = Projection * Model * vec4(position, 1);
+}
+"#;
+    const FRAGMENT: &str = r#"#version 100
+void main() {
+    gl_FragColor = vec4(1.0, 1.0, 1.0, 1.0);
+}
+"#;
+    if INVERT_MATERIAL.is_none() {
+        use macroquad::miniquad::{BlendFactor, BlendState, BlendValue, Equation, PipelineParams};
+        let params = macroquad::material::MaterialParams {
+            pipeline_params: PipelineParams {
+                color_blend: Some(BlendState::new(
+                    Equation::Add,
+                    BlendFactor::OneMinusValue(BlendValue::DestinationColor),
+                    BlendFactor::Zero,
+                )),
+                ..Default::default()
+            },
+            ..Default::default()
         };
-        macroquad::text::draw_text_ex(
-            &game.intro_text,
-            PROJECTION_WIDTH / 2.0 - size.width / 2.0,
-            PROJECTION_HEIGHT / 2.0,
-            params,
+        INVERT_MATERIAL = Some(
+            macroquad::material::load_material(VERTEX, FRAGMENT, params)
+                .expect("Couldn't compile the invert shader"),
         );
     }
+    INVERT_MATERIAL.unwrap()
 }
 
 struct GameOutput {
@@ -853,7 +1016,7 @@ impl FramesToRun for FrameInfo {
         let total_ms_elapsed = self.total_time_elapsed * 1000.0;
         let frame_time = (1000.0 / 60.0) / playback_rate as f64;
         let expected_frame_count = (total_ms_elapsed / frame_time) as i64 + 1;
-        let frames_to_run = expected_frame_count - self.ran as i64;
+        let frames_to_run = expected_frame_count - (self.ran + self.slowed) as i64;
 
         if frames_to_run == 0 {
             1
@@ -918,19 +1081,27 @@ struct MainGame<S> {
     high_scores: HashMap<String, (i32, i32, i32)>,
     played_games: PlayedGames,
     rng: MacroRng,
+    transitions: Transitions,
+    // Text the host adds to games, like the intro text after losing every life
+    host_text: Translations,
 }
 
 struct LoadingScreen {}
 
 impl MainGame<LoadingScreen> {
     async fn load() -> WeeResult<MainGame<Menu>> {
-        let game = LoadedGameData::load("games/system/loading-screen.json").await?;
+        let settings = Self::settings();
+        let host_text = load_translations("games/system", &settings.language).await;
+
+        let mut game = LoadedGameData::load("games/system/loading-screen.json").await?;
+        game.data.localise(&host_text);
 
         let assets = game.assets;
 
         let mut rng = MacroRng {};
 
         let mut game = Game::from_data(game.data, &mut rng)?;
+        game.set_collision_masks(assets.masks.clone());
 
         let intro_font = macroquad::text::load_ttf_font("fonts/Roboto-Medium.ttf").await?;
 
@@ -1020,7 +1191,7 @@ impl MainGame<LoadingScreen> {
                 for filename in &games_to_preload {
                     let path = Path::new(filename);
                     let base_path = path.parent().unwrap();
-                    waiting_data.push(Assets::load(&games[filename].asset_files, base_path));
+                    waiting_data.push(Assets::load(&games[filename], base_path));
                 }
 
                 let mut data = join_all(waiting_data).await;
@@ -1086,10 +1257,14 @@ impl MainGame<LoadingScreen> {
             next_frame().await;
         }
 
+        let mut transitions = Transitions::new();
+        transitions.capture(&game, &assets, &intro_font, &drawn_text);
+
         assets.stop_sounds();
 
-        let (games, preloaded_assets) =
+        let (mut games, preloaded_assets) =
             dispenser::take::<WeeResult<(HashMap<String, GameData>, HashMap<String, Assets>)>>()?;
+        localise_games(&mut games, &settings.language).await;
 
         let all_games = games
             .iter()
@@ -1111,6 +1286,8 @@ impl MainGame<LoadingScreen> {
                 all_games,
             },
             rng,
+            transitions,
+            host_text,
         })
     }
 
@@ -1190,6 +1367,14 @@ impl MainGame<LoadingScreen> {
             let mut v = Vec::new();
 
             for entry in WalkDir::new("games").into_iter().filter_map(|e| e.ok()) {
+                // String tables are JSON too
+                if entry
+                    .path()
+                    .components()
+                    .any(|component| component.as_os_str() == TEXT_DIRECTORY)
+                {
+                    continue;
+                }
                 let metadata = entry.metadata()?;
                 let right_extension = match entry.path().extension() {
                     Some(ext) => ext == "json",
@@ -1201,6 +1386,7 @@ impl MainGame<LoadingScreen> {
                     "loading-screen.json",
                     "high-scores.json",
                     "played-games.json",
+                    "transition.json",
                 ]
                 .contains(&filename.to_str().unwrap())
                 {
@@ -1217,6 +1403,20 @@ impl MainGame<LoadingScreen> {
         Ok(game_filenames)
     }
 
+    fn settings() -> Settings {
+        #[cfg(target_arch = "wasm32")]
+        let json = match &mut quad_storage::STORAGE.lock() {
+            Ok(storage) => storage.get("weegames_settings"),
+            Err(_) => None,
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let json = std::fs::read_to_string("settings.json").ok();
+
+        json.and_then(|json| json_from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
     fn played_games() -> HashSet<String> {
         #[cfg(target_arch = "wasm32")]
         let played_games: HashSet<String> = {
@@ -1291,9 +1491,15 @@ impl MainGame<Menu> {
         }
 
         let mut game = Game::from_data(game_data, &mut self.rng)?;
+        game.set_collision_masks(assets.masks.clone());
 
         let mut drawn_text = HashMap::new();
 
+        self.transitions.settings = TransitionSettings::load("games/system").await;
+        self.transitions
+            .play(&game, &assets, &self.intro_font)
+            .await;
+
         assets.music.play(DEFAULT_PLAYBACK_RATE, VOLUME);
 
         let directory;
@@ -1331,6 +1537,9 @@ impl MainGame<Menu> {
             next_frame().await;
         }
 
+        self.transitions
+            .capture(&game, &assets, &self.intro_font, &drawn_text);
+
         assets.stop_sounds();
 
         Ok(MainGame {
@@ -1341,6 +1550,8 @@ impl MainGame<Menu> {
             high_scores: self.high_scores,
             played_games: self.played_games,
             rng: self.rng,
+            transitions: self.transitions,
+            host_text: self.host_text,
         })
     }
 }
@@ -1363,6 +1574,12 @@ impl MainGame<Prelude> {
         let mut drawn_text = HashMap::new();
 
         let mut game = Game::from_data(game, &mut self.rng)?;
+        game.set_collision_masks(assets.masks.clone());
+
+        self.transitions.settings = TransitionSettings::load(&self.state.directory).await;
+        self.transitions
+            .play(&game, &assets, &self.intro_font)
+            .await;
 
         assets.music.play(DEFAULT_PLAYBACK_RATE, VOLUME);
 
@@ -1387,6 +1604,9 @@ impl MainGame<Prelude> {
             }
         }
 
+        self.transitions
+            .capture(&game, &assets, &self.intro_font, &drawn_text);
+
         assets.stop_sounds();
 
         let games_list = GamesList::from_directory(&self.games, self.state.directory);
@@ -1404,6 +1624,8 @@ impl MainGame<Prelude> {
             high_scores: self.high_scores,
             played_games: self.played_games,
             rng: self.rng,
+            transitions: self.transitions,
+            host_text: self.host_text,
         })
     }
 }
@@ -1448,7 +1670,10 @@ impl MainGame<Interlude> {
                     ("{Score}", progress.score.to_string()),
                     ("{Lives}", progress.lives.to_string()),
                     ("{Game}", "game-over.json".to_string()),
-                    ("{IntroText}", "Game Over".to_string()),
+                    (
+                        "{IntroText}",
+                        self.host_text.text("game-over", "Game Over").to_string(),
+                    ),
                 ];
                 for object in game_data.objects.iter_mut() {
                     let mut set_switch = |name, pred| {
@@ -1465,9 +1690,14 @@ impl MainGame<Interlude> {
             }
 
             let mut game = Game::from_data(game_data, &mut self.rng)?;
+            game.set_collision_masks(assets.masks.clone());
 
             let playback_rate = self.state.progress.playback_rate;
 
+            self.transitions
+                .play(&game, &assets, &self.intro_font)
+                .await;
+
             assets.music.play(playback_rate, VOLUME);
 
             let mut drawn_text = HashMap::new();
@@ -1497,6 +1727,9 @@ impl MainGame<Interlude> {
                 next_frame().await;
             }
 
+            self.transitions
+                .capture(&game, &assets, &self.intro_font, &drawn_text);
+
             assets.stop_sounds();
 
             for key in assets.sounds.keys() {
@@ -1514,6 +1747,8 @@ impl MainGame<Interlude> {
                 high_scores: self.high_scores,
                 played_games: self.played_games,
                 rng: self.rng,
+                transitions: self.transitions,
+                host_text: self.host_text,
             });
             Ok(next_step)
         } else {
@@ -1570,15 +1805,20 @@ impl MainGame<Interlude> {
             }
 
             let mut game = Game::from_data(game_data, &mut self.rng)?;
+            game.set_collision_masks(assets.masks.clone());
 
             let resources_loading = start_coroutine(async move {
                 let base_path = Path::new(&nf).parent().unwrap();
-                let resources = Assets::load(&new_game_data.asset_files, base_path).await;
+                let resources = Assets::load(&new_game_data, base_path).await;
                 dispenser::store(resources);
             });
 
             let playback_rate = self.state.progress.playback_rate;
 
+            self.transitions
+                .play(&game, &assets, &self.intro_font)
+                .await;
+
             assets.music.play(playback_rate, VOLUME);
 
             let mut drawn_text = HashMap::new();
@@ -1598,6 +1838,8 @@ impl MainGame<Interlude> {
                         high_scores: self.high_scores,
                         played_games: self.played_games,
                         rng: self.rng,
+                        transitions: self.transitions,
+                        host_text: self.host_text,
                     }));
                 }
                 // while (game.frames.remaining() != FrameCount::Frames(0) && !game.end_early)
@@ -1623,6 +1865,9 @@ impl MainGame<Interlude> {
                 next_frame().await;
             }
 
+            self.transitions
+                .capture(&game, &assets, &self.intro_font, &drawn_text);
+
             assets.stop_sounds();
 
             let assets = dispenser::take::<WeeResult<Assets>>()?;
@@ -1643,6 +1888,8 @@ impl MainGame<Interlude> {
                 high_scores: self.high_scores,
                 played_games: self.played_games,
                 rng: self.rng,
+                transitions: self.transitions,
+                host_text: self.host_text,
             });
             Ok(next_step)
         }
@@ -1679,6 +1926,7 @@ async fn run_pause_menu(
             preloaded_game(games, preloaded_assets, "games/system/", "pause-menu.json");
 
         let mut game = Game::from_data(game, rng)?;
+        game.set_collision_masks(pause_menu_assets.masks.clone());
 
         pause_menu_assets.music.play(DEFAULT_PLAYBACK_RATE, VOLUME);
 
@@ -1776,6 +2024,7 @@ impl MainGame<Play> {
 
         // TODO: Remove clone
         let mut game = Game::from_data(self.state.game_data.clone(), &mut self.rng)?;
+        game.set_collision_masks(self.state.assets.masks.clone());
         game.difficulty = self.state.progress.difficulty;
 
         let playback_rate = if self.state.is_boss_game {
@@ -1785,6 +2034,10 @@ impl MainGame<Play> {
         };
 
         let mut drawn_text = HashMap::new();
+        self.transitions
+            .play(&game, &self.state.assets, &self.intro_font)
+            .await;
+
         self.state.assets.music.play(playback_rate, VOLUME);
 
         'play_loop: while game.frames.remaining() != FrameCount::Frames(0) {
@@ -1800,6 +2053,8 @@ impl MainGame<Play> {
                     high_scores: self.high_scores,
                     played_games: self.played_games,
                     rng: self.rng,
+                    transitions: self.transitions,
+                    host_text: self.host_text,
                 }));
             }
 
@@ -1823,6 +2078,9 @@ impl MainGame<Play> {
             next_frame().await;
         }
 
+        self.transitions
+            .capture(&game, &self.state.assets, &self.intro_font, &drawn_text);
+
         self.state.assets.stop_sounds();
 
         let has_won = matches!(game.status.next_frame, WinStatus::Won | WinStatus::JustWon);
@@ -1839,6 +2097,8 @@ impl MainGame<Play> {
             high_scores: self.high_scores,
             played_games: self.played_games,
             rng: self.rng,
+            transitions: self.transitions,
+            host_text: self.host_text,
         }))
     }
 }
@@ -1978,9 +2238,14 @@ impl MainGame<GameOver> {
         }
 
         let mut game = Game::from_data(game_data, &mut self.rng)?;
+        game.set_collision_masks(assets.masks.clone());
 
         let mut drawn_text = HashMap::new();
 
+        self.transitions
+            .play(&game, &assets, &self.intro_font)
+            .await;
+
         assets.music.play(1.0, VOLUME);
 
         while game.frames.remaining() != FrameCount::Frames(0) {
@@ -2004,6 +2269,9 @@ impl MainGame<GameOver> {
             }
         }
 
+        self.transitions
+            .capture(&game, &assets, &self.intro_font, &drawn_text);
+
         assets.stop_sounds();
 
         Ok(MainGame {
@@ -2014,6 +2282,8 @@ impl MainGame<GameOver> {
             high_scores: self.high_scores,
             played_games: self.played_games,
             rng: self.rng,
+            transitions: self.transitions,
+            host_text: self.host_text,
         })
     }
 }
@@ -0,0 +1,231 @@
+use crate::{
+    draw_game_contents, json_from_str, set_game_camera, Assets, PROJECTION_HEIGHT, PROJECTION_WIDTH,
+};
+use macroquad::logging as log;
+use macroquad::prelude::*;
+use macroquad::{
+    camera::{self, Camera2D},
+    math::Rect as MacroRect,
+    texture::{self, FilterMode, RenderTarget},
+    window,
+};
+use serde::Deserialize;
+use std::{collections::HashMap, f32::consts::PI, path::Path};
+use wee::{DrawnText, Game};
+use wee_common::Vec2 as WeeVec2;
+
+const SETTINGS_FILENAME: &str = "transition.json";
+const IRIS_SEGMENTS: usize = 48;
+const TV_START_SCALE: f32 = 0.2;
+const TV_BEZEL: f32 = 60.0;
+
+#[derive(Debug, Copy, Clone, Deserialize)]
+pub enum TransitionStyle {
+    Cut,
+    Wipe,
+    Iris,
+    ZoomIntoTv,
+    Crossfade,
+}
+
+#[derive(Debug, Copy, Clone, Deserialize)]
+pub struct TransitionSettings {
+    pub style: TransitionStyle,
+    pub seconds: f64,
+}
+
+impl Default for TransitionSettings {
+    fn default() -> TransitionSettings {
+        TransitionSettings {
+            style: TransitionStyle::Cut,
+            seconds: 0.0,
+        }
+    }
+}
+
+impl TransitionSettings {
+    // Each game pack can have its own transition.json, otherwise the system one is used
+    pub async fn load(directory: &str) -> TransitionSettings {
+        let paths = [
+            Path::new(directory).join(SETTINGS_FILENAME),
+            Path::new("games/system").join(SETTINGS_FILENAME),
+        ];
+        for path in paths.iter() {
+            let path = path.to_string_lossy().replace("\\", "/");
+            if let Ok(json) = macroquad::file::load_string(&path).await {
+                match json_from_str(&json) {
+                    Ok(settings) => return settings,
+                    Err(error) => {
+                        log::error!("Couldn't read transition from {}: {}", path, error)
+                    }
+                }
+            }
+        }
+        TransitionSettings::default()
+    }
+}
+
+pub struct Transitions {
+    pub settings: TransitionSettings,
+    outgoing: RenderTarget,
+    incoming: RenderTarget,
+    has_outgoing: bool,
+}
+
+impl Transitions {
+    pub fn new() -> Transitions {
+        let target = || {
+            let target = texture::render_target(PROJECTION_WIDTH as u32, PROJECTION_HEIGHT as u32);
+            target.texture.set_filter(FilterMode::Linear);
+            target
+        };
+        Transitions {
+            settings: TransitionSettings::default(),
+            outgoing: target(),
+            incoming: target(),
+            has_outgoing: false,
+        }
+    }
+
+    // Keeps the last frame of the game that's ending for the next transition
+    pub fn capture(
+        &mut self,
+        game: &Game,
+        assets: &Assets,
+        intro_font: &Font,
+        drawn_text: &HashMap<String, DrawnText>,
+    ) {
+        draw_to_target(self.outgoing, game, assets, intro_font, drawn_text);
+        self.has_outgoing = true;
+    }
+
+    // The incoming game isn't updated until this returns so its frame timing starts afterwards
+    pub async fn play(&mut self, game: &Game, assets: &Assets, intro_font: &Font) {
+        if !self.has_outgoing {
+            return;
+        }
+        self.has_outgoing = false;
+        if matches!(self.settings.style, TransitionStyle::Cut) || self.settings.seconds <= 0.0 {
+            return;
+        }
+
+        draw_to_target(self.incoming, game, assets, intro_font, &HashMap::new());
+
+        let start = get_time();
+        loop {
+            let progress = ((get_time() - start) / self.settings.seconds) as f32;
+            if progress >= 1.0 {
+                break;
+            }
+            set_game_camera(WeeVec2::zero());
+            clear_background(BLACK);
+            self.draw(progress);
+            next_frame().await;
+        }
+    }
+
+    fn draw(&self, progress: f32) {
+        let outgoing = self.outgoing.texture;
+        let incoming = self.incoming.texture;
+        let full_size = vec2(PROJECTION_WIDTH, PROJECTION_HEIGHT);
+        match self.settings.style {
+            TransitionStyle::Cut => draw_target(incoming, Vec2::zero(), full_size, None, WHITE),
+            TransitionStyle::Crossfade => {
+                draw_target(outgoing, Vec2::zero(), full_size, None, WHITE);
+                let fade = Color::new(1.0, 1.0, 1.0, progress);
+                draw_target(incoming, Vec2::zero(), full_size, None, fade);
+            }
+            TransitionStyle::Wipe => {
+                draw_target(outgoing, Vec2::zero(), full_size, None, WHITE);
+                let size = vec2(PROJECTION_WIDTH * progress, PROJECTION_HEIGHT);
+                let source = MacroRect::new(0.0, 0.0, size.x, size.y);
+                draw_target(incoming, Vec2::zero(), size, Some(source), WHITE);
+            }
+            TransitionStyle::Iris => {
+                // Closes on the outgoing game then opens on the incoming one
+                let (texture, openness) = if progress < 0.5 {
+                    (outgoing, 1.0 - progress * 2.0)
+                } else {
+                    (incoming, progress * 2.0 - 1.0)
+                };
+                draw_target(texture, Vec2::zero(), full_size, None, WHITE);
+                draw_iris(openness);
+            }
+            TransitionStyle::ZoomIntoTv => {
+                draw_target(outgoing, Vec2::zero(), full_size, None, WHITE);
+                let eased = progress * progress * (3.0 - 2.0 * progress);
+                let scale = TV_START_SCALE + (1.0 - TV_START_SCALE) * eased;
+                let size = full_size * scale;
+                let top_left = (full_size - size) / 2.0;
+                let bezel = TV_BEZEL * (1.0 - eased);
+                draw_rectangle(
+                    top_left.x - bezel,
+                    top_left.y - bezel,
+                    size.x + bezel * 2.0,
+                    size.y + bezel * 2.0,
+                    DARKGRAY,
+                );
+                draw_target(incoming, top_left, size, None, WHITE);
+            }
+        }
+    }
+}
+
+fn draw_to_target(
+    target: RenderTarget,
+    game: &Game,
+    assets: &Assets,
+    intro_font: &Font,
+    drawn_text: &HashMap<String, DrawnText>,
+) {
+    unsafe {
+        window::get_internal_gl().quad_gl.scissor(None);
+    }
+    let shake = game.screen_effects().offset;
+    let camera = Camera2D {
+        render_target: Some(target),
+        ..Camera2D::from_display_rect(MacroRect::new(
+            -shake.x,
+            -shake.y,
+            PROJECTION_WIDTH,
+            PROJECTION_HEIGHT,
+        ))
+    };
+    camera::set_camera(&camera);
+    draw_game_contents(game, &assets.images, &assets.fonts, intro_font, drawn_text);
+}
+
+// Display rect cameras flip y, so render targets come out upside down
+fn draw_target(
+    texture: Texture2D,
+    position: Vec2,
+    size: Vec2,
+    source: Option<MacroRect>,
+    colour: Color,
+) {
+    let params = DrawTextureParams {
+        dest_size: Some(size),
+        source,
+        rotation: 0.0,
+        pivot: None,
+        flip_x: false,
+        flip_y: true,
+    };
+    draw_texture_ex(texture, position.x, position.y, colour, params);
+}
+
+fn draw_iris(openness: f32) {
+    let centre = vec2(PROJECTION_WIDTH / 2.0, PROJECTION_HEIGHT / 2.0);
+    let inner = centre.length() * openness;
+    let outer = centre.length() * 3.0;
+    let point = |radius: f32, i: usize| {
+        let angle = i as f32 / IRIS_SEGMENTS as f32 * PI * 2.0;
+        centre + vec2(angle.cos(), angle.sin()) * radius
+    };
+    for i in 0..IRIS_SEGMENTS {
+        let (inner_start, inner_end) = (point(inner, i), point(inner, i + 1));
+        let (outer_start, outer_end) = (point(outer, i), point(outer, i + 1));
+        draw_triangle(inner_start, outer_start, outer_end, BLACK);
+        draw_triangle(inner_start, outer_end, inner_end, BLACK);
+    }
+}
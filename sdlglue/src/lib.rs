@@ -14,6 +14,7 @@ use sdl2::{
     EventPump,
 };
 use std::{
+    cell::Cell,
     ffi::{CStr, CString},
     fs::File,
     io::Read,
@@ -23,7 +24,9 @@ use std::{
 };
 
 use shader::Shader;
-use wee_common::{Colour, Flip, Rect, Size, Vec2, WeeResult, PROJECTION_HEIGHT, PROJECTION_WIDTH};
+use wee_common::{
+    Colour, Flip, Rect, Size, Vec2, WeeResult, AABB, PROJECTION_HEIGHT, PROJECTION_WIDTH,
+};
 #[derive(Debug)]
 pub struct Texture {
     pub id: GLuint,
@@ -242,8 +245,10 @@ struct FullscreenInfo {
 pub struct Renderer {
     sprite_shader: Shader,
     rect_shader: Shader,
+    shape_shader: Shader,
     quad_vao: u32,
     projection: Matrix4<f32>,
+    offset: Cell<Vec2>,
     pub window: SdlWindow,
     fullscreen_info: FullscreenInfo,
     mouse: Texture,
@@ -407,6 +412,17 @@ impl Renderer {
             shader
         };
 
+        let shape_shader = {
+            let shader = Shader::new("shaders/shape.vert", "shaders/shape.frag");
+
+            unsafe {
+                shader.use_program();
+                shader.set_mat4(c_str!("projection"), &projection);
+            }
+
+            shader
+        };
+
         fn init() -> u32 {
             let mut quad_vao = 0;
             let mut vbo = 0 as GLuint;
@@ -456,8 +472,10 @@ impl Renderer {
         Renderer {
             sprite_shader,
             rect_shader,
+            shape_shader,
             quad_vao,
             projection,
+            offset: Cell::new(Vec2::zero()),
             window,
             fullscreen_info,
             mouse,
@@ -478,6 +496,42 @@ impl Renderer {
         Self::set_viewport_from_window_size(self.window.size());
     }
 
+    fn projection(&self) -> Matrix4<f32> {
+        let offset = self.offset.get();
+        self.projection * Matrix4::from_translation(vec3(offset.x, offset.y, 0.0))
+    }
+
+    // Moves everything drawn afterwards, used to shake the screen
+    pub fn set_offset(&self, offset: Vec2) {
+        self.offset.set(offset);
+    }
+
+    // Larger than the projection so an offset screen is still covered
+    pub fn cover_screen(&self, colour: Colour) {
+        let model = Model::new(
+            Rect::new(
+                PROJECTION_WIDTH / 2.0,
+                PROJECTION_HEIGHT / 2.0,
+                PROJECTION_WIDTH * 3.0,
+                PROJECTION_HEIGHT * 3.0,
+            ),
+            None,
+            0.0,
+            Flip::default(),
+        );
+        self.fill_rectangle(model, colour);
+    }
+
+    pub fn invert_screen(&self) {
+        unsafe {
+            gl::BlendFunc(gl::ONE_MINUS_DST_COLOR, gl::ZERO);
+        }
+        self.cover_screen(Colour::white());
+        unsafe {
+            gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+        }
+    }
+
     pub fn prepare<'a>(&'a self, texture: &'a Texture) -> TextureDrawer<'a> {
         TextureDrawer::new(self, texture)
     }
@@ -521,7 +575,7 @@ impl Renderer {
             let colour = colour.to_vec4();
             self.rect_shader.use_program();
             self.rect_shader
-                .set_mat4(c_str!("projection"), &self.projection);
+                .set_mat4(c_str!("projection"), &self.projection());
             self.rect_shader.set_vector4(c_str!("rect_colour"), &colour);
 
             self.rect_shader.set_mat4(c_str!("model"), &model.0);
@@ -582,7 +636,7 @@ impl Renderer {
 
             self.rect_shader.use_program();
             self.rect_shader
-                .set_mat4(c_str!("projection"), &self.projection);
+                .set_mat4(c_str!("projection"), &self.projection());
             self.rect_shader
                 .set_vector4(c_str!("rect_colour"), &colour.to_vec4());
             self.rect_shader.set_mat4(c_str!("model"), &model.0);
@@ -598,38 +652,173 @@ impl Renderer {
         }
     }
 
-    fn draw_texture(
-        &self,
-        texture: &Texture,
-        dest: Rect,
-        colour: Colour,
-        angle: f32,
-        origin: Option<Vec2>,
-        flip: Flip,
-    ) {
+    // Points are in projection space, the polygon must be convex to be filled
+    pub fn fill_polygon(&self, points: &[Vec2], colour: Colour) {
+        self.draw_polygon(points, colour, gl::TRIANGLE_FAN);
+    }
+
+    pub fn draw_polygon_lines(&self, points: &[Vec2], colour: Colour) {
+        self.draw_polygon(points, colour, gl::LINE_LOOP);
+    }
+
+    fn draw_polygon(&self, points: &[Vec2], colour: Colour, mode: GLenum) {
+        if points.is_empty() {
+            return;
+        }
+        let vertices: Vec<f32> = points
+            .iter()
+            .flat_map(|point| vec![point.x, point.y, 0.5])
+            .collect();
+        let mut vbo: u32 = 0;
+        let mut vao: u32 = 0;
         unsafe {
-            self.sprite_shader.use_program();
-            self.sprite_shader
-                .set_mat4(c_str!("projection"), &self.projection);
+            gl::GenVertexArrays(1, &mut vao);
+            gl::GenBuffers(1, &mut vbo);
+            gl::BindVertexArray(vao);
+
+            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+            gl::BufferData(
+                gl::ARRAY_BUFFER,
+                (vertices.len() * std::mem::size_of::<GLfloat>()) as isize,
+                vertices.as_ptr() as *const c_void,
+                gl::STATIC_DRAW,
+            );
+
+            gl::VertexAttribPointer(
+                0,
+                3,
+                gl::FLOAT,
+                gl::FALSE,
+                (3 * std::mem::size_of::<GLfloat>()) as i32,
+                std::ptr::null(),
+            );
+
+            gl::EnableVertexAttribArray(0);
+
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+            gl::BindVertexArray(0);
+
+            gl::LineWidth(2.0);
+
+            self.rect_shader.use_program();
+            self.rect_shader
+                .set_mat4(c_str!("projection"), &self.projection());
+            self.rect_shader
+                .set_vector4(c_str!("rect_colour"), &colour.to_vec4());
+            self.rect_shader
+                .set_mat4(c_str!("model"), &Matrix4::identity());
+
+            gl::BindVertexArray(vao);
+            gl::DrawArrays(mode, 0, points.len() as i32);
+
+            gl::DeleteVertexArrays(1, &vao);
+            gl::DeleteBuffers(1, &vbo);
+        }
+    }
+
+    // Every three vertices are a triangle, with colours blended between the corners
+    pub fn fill_triangles(&self, vertices: &[(Vec2, Colour)]) {
+        if vertices.is_empty() {
+            return;
         }
+        let data: Vec<f32> = vertices
+            .iter()
+            .flat_map(|(point, colour)| {
+                vec![point.x, point.y, colour.r, colour.g, colour.b, colour.a]
+            })
+            .collect();
+        let stride = (6 * std::mem::size_of::<GLfloat>()) as i32;
+        let mut vbo: u32 = 0;
+        let mut vao: u32 = 0;
+        unsafe {
+            gl::GenVertexArrays(1, &mut vao);
+            gl::GenBuffers(1, &mut vbo);
+            gl::BindVertexArray(vao);
+
+            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+            gl::BufferData(
+                gl::ARRAY_BUFFER,
+                (data.len() * std::mem::size_of::<GLfloat>()) as isize,
+                data.as_ptr() as *const c_void,
+                gl::STATIC_DRAW,
+            );
 
-        let model = Model::new(dest, origin, angle, flip);
+            gl::VertexAttribPointer(0, 2, gl::FLOAT, gl::FALSE, stride, std::ptr::null());
+            gl::EnableVertexAttribArray(0);
+            gl::VertexAttribPointer(
+                1,
+                4,
+                gl::FLOAT,
+                gl::FALSE,
+                stride,
+                (2 * std::mem::size_of::<GLfloat>()) as *const c_void,
+            );
+            gl::EnableVertexAttribArray(1);
 
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+            gl::BindVertexArray(0);
+
+            self.shape_shader.use_program();
+            self.shape_shader
+                .set_mat4(c_str!("projection"), &self.projection());
+
+            gl::BindVertexArray(vao);
+            gl::DrawArrays(gl::TRIANGLES, 0, vertices.len() as i32);
+
+            gl::DeleteVertexArrays(1, &vao);
+            gl::DeleteBuffers(1, &vbo);
+        }
+    }
+
+    fn draw_texture(&self, texture: &Texture, model: Model, colour: Colour, source: Option<AABB>) {
         unsafe {
+            self.sprite_shader.use_program();
+            self.sprite_shader
+                .set_mat4(c_str!("projection"), &self.projection());
             let colour = colour.to_vec4();
-            self.draw_texture_using_model(texture, model, colour);
+            self.draw_texture_using_model(texture, model, colour, source);
         }
     }
 
+    // The source is the area of the texture to draw in pixels, or the whole texture if it's None
+    pub fn draw_texture_corners(
+        &self,
+        texture: &Texture,
+        source: Option<AABB>,
+        corners: [Vec2; 4],
+    ) {
+        self.draw_texture(
+            texture,
+            Model::from_corners(corners),
+            Colour::white(),
+            source,
+        );
+    }
+
     unsafe fn draw_texture_using_model(
         &self,
         texture: &Texture,
         model: Model,
         colour: Vector4<f32>,
+        source: Option<AABB>,
     ) {
+        let region = match source {
+            Some(source) if texture.width > 0 && texture.height > 0 => {
+                let size = texture.size();
+                vec4(
+                    source.min.x / size.width,
+                    source.min.y / size.height,
+                    source.width() / size.width,
+                    source.height() / size.height,
+                )
+            }
+            _ => vec4(0.0, 0.0, 1.0, 1.0),
+        };
         self.sprite_shader.set_mat4(c_str!("model"), &model.0);
         self.sprite_shader
             .set_vector4(c_str!("sprite_colour"), &colour);
+        self.sprite_shader
+            .set_vector4(c_str!("texture_region"), &region);
         gl::ActiveTexture(gl::TEXTURE0);
         texture.bind();
         gl::BindVertexArray(self.quad_vao);
@@ -645,20 +834,15 @@ impl Renderer {
                     position.x / self.window.size().0 as f32 * PROJECTION_WIDTH as f32,
                     position.y / self.window.size().1 as f32 * PROJECTION_HEIGHT as f32,
                 );
-                self.draw_texture(
-                    &self.mouse,
-                    wee_common::AABB::new(
-                        position.x,
-                        position.y,
-                        position.x + self.mouse.width as f32,
-                        position.y + self.mouse.height as f32,
-                    )
-                    .to_rect(),
-                    Colour::white(),
-                    0.0,
-                    None,
-                    Flip::default(),
-                );
+                let dest = AABB::new(
+                    position.x,
+                    position.y,
+                    position.x + self.mouse.width as f32,
+                    position.y + self.mouse.height as f32,
+                )
+                .to_rect();
+                let model = Model::new(dest, None, 0.0, Flip::default());
+                self.draw_texture(&self.mouse, model, Colour::white(), None);
             }
             _ => {}
         }
@@ -740,6 +924,21 @@ impl Renderer {
 pub struct Model(Matrix4<f32>);
 
 impl Model {
+    // Maps the unit square onto the corners, starting at the top left and going clockwise
+    pub fn from_corners(corners: [Vec2; 4]) -> Model {
+        let across = corners[1] - corners[0];
+        let down = corners[3] - corners[0];
+        let top_left = corners[0];
+        #[rustfmt::skip]
+        let model = Matrix4::new(
+            across.x, across.y, 0.0, 0.0,
+            down.x, down.y, 0.0, 0.0,
+            0.0, 0.0, 1.0, 0.0,
+            top_left.x, top_left.y, 0.0, 1.0,
+        );
+        Model(model)
+    }
+
     pub fn new(dest: Rect, origin: Option<Vec2>, angle: f32, flip: Flip) -> Model {
         let size = Vec2::new(dest.w, dest.h);
 
@@ -795,6 +994,7 @@ pub struct TextureDrawer<'a> {
     angle: f32,
     origin: Option<Vec2>,
     flip: Flip,
+    source: Option<AABB>,
 }
 
 impl<'a> TextureDrawer<'a> {
@@ -812,6 +1012,7 @@ impl<'a> TextureDrawer<'a> {
             angle: 0.0,
             origin: None,
             flip: Flip::default(),
+            source: None,
         }
     }
 
@@ -835,15 +1036,16 @@ impl<'a> TextureDrawer<'a> {
         self
     }
 
+    // Draws part of the texture, such as one frame of a sprite sheet
+    pub fn set_source(mut self, source: Option<AABB>) -> TextureDrawer<'a> {
+        self.source = source;
+        self
+    }
+
     pub fn draw(self) {
-        self.renderer.draw_texture(
-            self.texture,
-            self.dest,
-            self.colour,
-            self.angle,
-            self.origin,
-            self.flip,
-        );
+        let model = Model::new(self.dest, self.origin, self.angle, self.flip);
+        self.renderer
+            .draw_texture(self.texture, model, self.colour, self.source);
     }
 }
 
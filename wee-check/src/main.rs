@@ -0,0 +1,771 @@
+// Plays games headlessly with searched inputs to estimate how often they can be won.
+//
+// cargo run --release -- games/yeah --difficulty 3 --playback-rate 2 --fixtures ../wee/saved-runs
+
+use bracket_random::prelude::*;
+use std::{
+    env, fmt, fs,
+    path::{Path, PathBuf},
+    process,
+};
+use walkdir::WalkDir;
+use wee::*;
+use wee_common::{Vec2, WeeResult, PROJECTION_HEIGHT, PROJECTION_WIDTH};
+
+const DEFAULT_SEEDS: u32 = 10;
+// Roughly how long a player takes to react at normal speed
+const REACTION_FRAMES: f32 = 6.0;
+// Infinite games are given up on after this many frames
+const MAX_FRAMES: u32 = 60 * 60;
+const RANDOM_ATTEMPTS: u32 = 20;
+// Each strategy gives up after simulating about this many frames for a seed
+const SEARCH_FRAME_BUDGET: u32 = 200_000;
+const BEAM_WIDTH: usize = 3;
+const GRID_COLUMNS: u32 = 4;
+const GRID_ROWS: u32 = 3;
+
+#[derive(Clone)]
+struct CheckRng(RandomNumberGenerator);
+
+impl WeeRng for CheckRng {
+    fn random_in_range(&mut self, min: f32, max: f32) -> f32 {
+        self.0.range(min, max)
+    }
+
+    fn random_in_range_u32(&mut self, min: u32, max: u32) -> u32 {
+        self.0.range(min, max)
+    }
+
+    fn random_in_slice<'a, T>(&mut self, slice: &'a [T]) -> Option<&'a T> {
+        self.0.random_slice_entry(slice)
+    }
+
+    fn coin_flip(&mut self) -> bool {
+        self.0.roll_dice(1, 2) == 1
+    }
+}
+
+struct Options {
+    root: PathBuf,
+    paths: Vec<String>,
+    difficulties: Vec<u32>,
+    seeds: u32,
+    fixed_seeds: Vec<u64>,
+    playback_rate: f32,
+    fixtures: Option<PathBuf>,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            root: PathBuf::from("../main-game"),
+            paths: Vec::new(),
+            difficulties: vec![1, 2, 3],
+            seeds: DEFAULT_SEEDS,
+            fixed_seeds: Vec::new(),
+            playback_rate: 1.0,
+            fixtures: None,
+        }
+    }
+}
+
+const USAGE: &str = "Usage: wee-check [OPTIONS] [PATHS...]
+
+Paths are game files or directories relative to --root (default: games)
+
+Options:
+    --root <DIR>           Directory game paths are relative to (default: ../main-game)
+    --difficulty <LEVEL>   Only check this difficulty, can be repeated (default: 1, 2 and 3)
+    --seeds <COUNT>        Number of random seeds to try per difficulty (default: 10)
+    --seed <SEED>          Check this seed instead of random ones, can be repeated
+    --playback-rate <RATE> Playback rate the player's reactions are slowed to (default: 1.0)
+    --fixtures <DIR>       Save a winning run per game and difficulty to this directory";
+
+impl Options {
+    fn from_args() -> WeeResult<Options> {
+        let mut options = Options::default();
+        let mut difficulties = Vec::new();
+        let mut args = env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("Missing value for {}", arg))
+            };
+            match arg.as_str() {
+                "--root" => options.root = PathBuf::from(value()?),
+                "--difficulty" => {
+                    let level = value()?.parse()?;
+                    if !(1..=3).contains(&level) {
+                        return Err(format!("Difficulty {} is not between 1 and 3", level).into());
+                    }
+                    difficulties.push(level);
+                }
+                "--seeds" => options.seeds = value()?.parse()?,
+                "--seed" => options.fixed_seeds.push(value()?.parse()?),
+                "--playback-rate" => options.playback_rate = value()?.parse()?,
+                "--fixtures" => options.fixtures = Some(PathBuf::from(value()?)),
+                "--help" | "-h" => {
+                    println!("{}", USAGE);
+                    process::exit(0);
+                }
+                _ if arg.starts_with("--") => {
+                    return Err(format!("Unknown option {}\n\n{}", arg, USAGE).into())
+                }
+                _ => options.paths.push(arg),
+            }
+        }
+        if !difficulties.is_empty() {
+            options.difficulties = difficulties;
+        }
+        if options.paths.is_empty() {
+            options.paths.push("games".to_string());
+        }
+        if options.playback_rate <= 0.0 {
+            return Err("Playback rate must be above 0".into());
+        }
+        Ok(options)
+    }
+
+    fn seeds(&self) -> Vec<u64> {
+        if self.fixed_seeds.is_empty() {
+            let mut rng = RandomNumberGenerator::new();
+            (0..self.seeds).map(|_| rng.next_u64()).collect()
+        } else {
+            self.fixed_seeds.clone()
+        }
+    }
+
+    // Game frames between each change of input for a player at this playback rate
+    fn reaction_frames(&self) -> u32 {
+        ((REACTION_FRAMES * self.playback_rate).round() as u32).max(1)
+    }
+
+    fn game_files(&self) -> WeeResult<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        for path in &self.paths {
+            let path = self.root.join(path);
+            if path.is_dir() {
+                let entries = WalkDir::new(&path)
+                    .sort_by(|a, b| a.file_name().cmp(b.file_name()))
+                    .into_iter()
+                    // String tables are JSON too
+                    .filter_entry(|entry| entry.file_name() != TEXT_DIRECTORY);
+                for entry in entries {
+                    let entry = entry?;
+                    if entry.path().extension() == Some("json".as_ref()) {
+                        files.push(entry.path().to_path_buf());
+                    }
+                }
+            } else if path.is_file() {
+                files.push(path);
+            } else {
+                return Err(format!("Couldn't find {:?}", path).into());
+            }
+        }
+        Ok(files)
+    }
+}
+
+struct LoadedGame {
+    data: GameData,
+    masks: CollisionMasks,
+}
+
+fn load_game(path: impl AsRef<Path>) -> WeeResult<LoadedGame> {
+    let json_string = fs::read_to_string(&path)?;
+    let data = serde_json::from_str(&json_string)?;
+    let base_path = path
+        .as_ref()
+        .parent()
+        .ok_or("Could not get game directory")?;
+    let masks = CollisionMasks::load(&data, base_path)?;
+    Ok(LoadedGame { data, masks })
+}
+
+#[derive(Clone)]
+struct Run {
+    game: Game,
+    rng: CheckRng,
+    mouse: Mouse,
+    inputs: Vec<Mouse>,
+    ended_early: bool,
+}
+
+impl Run {
+    fn start(loaded_game: &LoadedGame, difficulty: u32, seed: u64) -> WeeResult<Run> {
+        let mut rng = CheckRng(RandomNumberGenerator::seeded(seed));
+        let mut game = Game::from_data(loaded_game.data.clone(), &mut rng)?;
+        game.difficulty = difficulty;
+        game.set_collision_masks(loaded_game.masks.clone());
+
+        Ok(Run {
+            game,
+            rng,
+            mouse: Mouse::default(),
+            inputs: Vec::new(),
+            ended_early: false,
+        })
+    }
+
+    fn has_finished(&self) -> bool {
+        self.ended_early || self.game.frames.remaining() == FrameCount::Frames(0)
+    }
+
+    fn is_over(&self) -> bool {
+        self.has_finished() || self.game.frames.ran >= MAX_FRAMES
+    }
+
+    fn has_been_won(&self) -> bool {
+        matches!(
+            self.game.status.current,
+            WinStatus::Won | WinStatus::JustWon
+        )
+    }
+
+    fn has_been_lost(&self) -> bool {
+        matches!(
+            self.game.status.current,
+            WinStatus::Lost | WinStatus::JustLost
+        )
+    }
+
+    fn step(&mut self, position: Vec2, pressed: bool) -> WeeResult<()> {
+        if self.is_over() {
+            return Ok(());
+        }
+        self.mouse.position = position;
+        self.mouse.state.update(pressed);
+
+        let world_actions = self.game.update_frame(self.mouse, &mut self.rng)?;
+        self.inputs.push(self.mouse);
+
+        if world_actions
+            .iter()
+            .any(|action| matches!(action, WorldAction::EndEarly))
+        {
+            self.ended_early = true;
+        }
+        Ok(())
+    }
+
+    fn hold(&mut self, position: Vec2, pressed: bool, frames: u32) -> WeeResult<()> {
+        for _ in 0..frames {
+            self.step(position, pressed)?;
+        }
+        Ok(())
+    }
+
+    fn drag(&mut self, from: Vec2, to: Vec2, frames: u32) -> WeeResult<()> {
+        for frame in 0..frames {
+            let t = (frame + 1) as f32 / frames as f32;
+            self.step(from + (to - from) * t, true)?;
+        }
+        self.step(to, false)
+    }
+
+    fn follow(&mut self, name: &str, pressed: bool, frames_per_input: u32) -> WeeResult<()> {
+        while !self.is_over() {
+            let position = self.object_position(name).unwrap_or(self.mouse.position);
+            self.hold(position, pressed, frames_per_input)?;
+        }
+        Ok(())
+    }
+
+    fn finish(mut self) -> WeeResult<Run> {
+        let position = self.mouse.position;
+        while !self.is_over() {
+            self.step(position, false)?;
+        }
+        Ok(self)
+    }
+
+    fn object_position(&self, name: &str) -> Option<Vec2> {
+        self.game.objects.get(name).map(|object| object.position)
+    }
+
+    fn clickable_positions(&self) -> Vec<Vec2> {
+        let mut positions: Vec<Vec2> = Vec::new();
+        for object in self.game.objects.values() {
+            let position = object.position;
+            let on_screen = position.x >= 0.0
+                && position.y >= 0.0
+                && position.x < PROJECTION_WIDTH
+                && position.y < PROJECTION_HEIGHT;
+            if on_screen && !positions.contains(&position) {
+                positions.push(position);
+            }
+        }
+        positions
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Strategy {
+    Idle,
+    RandomClicks,
+    Sweep,
+    BeamSearch,
+}
+
+impl Strategy {
+    const ALL: [Strategy; 4] = [
+        Strategy::Idle,
+        Strategy::RandomClicks,
+        Strategy::Sweep,
+        Strategy::BeamSearch,
+    ];
+}
+
+impl fmt::Display for Strategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Strategy::Idle => write!(f, "idle"),
+            Strategy::RandomClicks => write!(f, "random"),
+            Strategy::Sweep => write!(f, "sweep"),
+            Strategy::BeamSearch => write!(f, "beam"),
+        }
+    }
+}
+
+struct Search<'a> {
+    loaded_game: &'a LoadedGame,
+    difficulty: u32,
+    seed: u64,
+    reaction_frames: u32,
+    frames_per_input: u32,
+    frames_left: u32,
+    rng: RandomNumberGenerator,
+}
+
+impl<'a> Search<'a> {
+    fn new(
+        loaded_game: &'a LoadedGame,
+        difficulty: u32,
+        seed: u64,
+        options: &Options,
+    ) -> Search<'a> {
+        Search {
+            loaded_game,
+            difficulty,
+            seed,
+            reaction_frames: options.reaction_frames(),
+            frames_per_input: options.playback_rate.ceil() as u32,
+            frames_left: SEARCH_FRAME_BUDGET,
+            rng: RandomNumberGenerator::seeded(seed),
+        }
+    }
+
+    fn start(&self) -> WeeResult<Run> {
+        Run::start(self.loaded_game, self.difficulty, self.seed)
+    }
+
+    fn find_win(&mut self, strategy: Strategy) -> WeeResult<Option<Run>> {
+        self.frames_left = SEARCH_FRAME_BUDGET;
+        match strategy {
+            Strategy::Idle => self.idle(),
+            Strategy::RandomClicks => self.random_clicks(),
+            Strategy::Sweep => self.sweep(),
+            Strategy::BeamSearch => self.beam_search(),
+        }
+    }
+
+    // Counts the frames a finished run took towards the budget
+    fn spend(&mut self, run: &Run) -> bool {
+        self.frames_left = self.frames_left.saturating_sub(run.game.frames.ran);
+        self.frames_left > 0
+    }
+
+    fn random_position(&mut self) -> Vec2 {
+        Vec2::new(
+            self.rng.range(0.0, PROJECTION_WIDTH),
+            self.rng.range(0.0, PROJECTION_HEIGHT),
+        )
+    }
+
+    fn random_input(&mut self, run: &mut Run) -> WeeResult<()> {
+        let position = self.random_position();
+        match self.rng.range(0, 4) {
+            0 => run.hold(position, false, self.reaction_frames),
+            1 => {
+                run.hold(position, true, self.frames_per_input)?;
+                run.step(position, false)
+            }
+            2 => run.hold(position, true, self.reaction_frames),
+            _ => {
+                let to = self.random_position();
+                run.drag(position, to, self.reaction_frames * 3)
+            }
+        }
+    }
+
+    fn idle(&mut self) -> WeeResult<Option<Run>> {
+        let run = self.start()?.finish()?;
+        Ok(Some(run).filter(Run::has_been_won))
+    }
+
+    fn random_clicks(&mut self) -> WeeResult<Option<Run>> {
+        for _ in 0..RANDOM_ATTEMPTS {
+            let mut run = self.start()?;
+            while !run.is_over() {
+                self.random_input(&mut run)?;
+            }
+            if run.has_been_won() {
+                return Ok(Some(run));
+            }
+            if !self.spend(&run) {
+                break;
+            }
+        }
+        Ok(None)
+    }
+
+    // Waits a while then clicks, follows or drags each object in turn
+    fn sweep(&mut self) -> WeeResult<Option<Run>> {
+        let names: Vec<String> = self
+            .loaded_game
+            .data
+            .objects
+            .iter()
+            .map(|object| object.name.clone())
+            .collect();
+
+        let mut waiting = self.start()?;
+        while !waiting.is_over() {
+            let positions = waiting.clickable_positions();
+            for name in &names {
+                let position = match waiting.object_position(name) {
+                    Some(position) if positions.contains(&position) => position,
+                    _ => continue,
+                };
+
+                let mut attempts = Vec::new();
+
+                let mut run = waiting.clone();
+                run.hold(position, true, self.frames_per_input)?;
+                attempts.push(run);
+
+                for &pressed in &[false, true] {
+                    let mut run = waiting.clone();
+                    run.follow(name, pressed, self.frames_per_input)?;
+                    attempts.push(run);
+                }
+
+                for &to in positions.iter().filter(|&&to| to != position) {
+                    let mut run = waiting.clone();
+                    run.drag(position, to, self.reaction_frames * 3)?;
+                    attempts.push(run);
+                }
+
+                for run in attempts {
+                    let run = run.finish()?;
+                    if run.has_been_won() {
+                        return Ok(Some(run));
+                    }
+                    if !self.spend(&run) {
+                        return Ok(None);
+                    }
+                }
+            }
+            waiting.hold(waiting.mouse.position, false, self.reaction_frames * 2)?;
+        }
+        Ok(None)
+    }
+
+    fn candidate_inputs(&self, run: &Run) -> Vec<(Vec2, bool)> {
+        let mut positions = run.clickable_positions();
+        for column in 0..GRID_COLUMNS {
+            for row in 0..GRID_ROWS {
+                positions.push(Vec2::new(
+                    (column as f32 + 0.5) * PROJECTION_WIDTH / GRID_COLUMNS as f32,
+                    (row as f32 + 0.5) * PROJECTION_HEIGHT / GRID_ROWS as f32,
+                ));
+            }
+        }
+        positions
+            .into_iter()
+            .flat_map(|position| vec![(position, false), (position, true)])
+            .collect()
+    }
+
+    // Plays randomly until the end, returning the finished run and a score
+    // that favours runs which avoid losing for longer
+    fn rollout(&mut self, run: &Run) -> WeeResult<(Run, u32)> {
+        let mut rollout = run.clone();
+        let mut lost_at = None;
+        while !rollout.is_over() {
+            self.random_input(&mut rollout)?;
+            if lost_at.is_none() && rollout.has_been_lost() {
+                lost_at = Some(rollout.game.frames.ran);
+            }
+        }
+        let score = lost_at.unwrap_or(MAX_FRAMES + 1);
+        Ok((rollout, score))
+    }
+
+    // Keeps the few most promising input sequences, extending each with every
+    // candidate input for one reaction time and scoring them by random rollouts
+    fn beam_search(&mut self) -> WeeResult<Option<Run>> {
+        let mut beam = vec![self.start()?];
+        while !beam.is_empty() {
+            let mut scored = Vec::new();
+            for run in &beam {
+                for (position, pressed) in self.candidate_inputs(run) {
+                    let mut next = run.clone();
+                    next.hold(position, pressed, self.reaction_frames)?;
+                    if next.has_been_lost() {
+                        continue;
+                    }
+                    if next.has_been_won() {
+                        return Ok(Some(next.finish()?));
+                    }
+                    let idle = next.clone().finish()?;
+                    if idle.has_been_won() {
+                        return Ok(Some(idle));
+                    }
+                    let (rollout, score) = self.rollout(&next)?;
+                    if rollout.has_been_won() {
+                        return Ok(Some(rollout));
+                    }
+                    if !self.spend(&idle) || !self.spend(&rollout) {
+                        return Ok(None);
+                    }
+                    if !next.is_over() {
+                        scored.push((score, self.rng.next_u64(), next));
+                    }
+                }
+            }
+            scored.sort_by_key(|(score, tie_break, _)| std::cmp::Reverse((*score, *tie_break)));
+            beam = scored
+                .into_iter()
+                .take(BEAM_WIDTH)
+                .map(|(_, _, run)| run)
+                .collect();
+        }
+        Ok(None)
+    }
+}
+
+struct Report {
+    path: String,
+    difficulty: u32,
+    seeds: usize,
+    wins: Vec<Strategy>,
+    fixture: Option<SavedRun>,
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let percent = self.wins.len() as f32 / self.seeds as f32 * 100.0;
+        write!(
+            f,
+            "{} difficulty {}: won {}/{} seeds ({:.0}%)",
+            self.path,
+            self.difficulty,
+            self.wins.len(),
+            self.seeds,
+            percent
+        )?;
+        if self.wins.is_empty() {
+            write!(f, " - NO WINNING INPUTS FOUND")
+        } else {
+            let found_by: Vec<String> = Strategy::ALL
+                .iter()
+                .map(|strategy| {
+                    let count = self.wins.iter().filter(|win| *win == strategy).count();
+                    format!("{} {}", strategy, count)
+                })
+                .collect();
+            write!(f, " [{}]", found_by.join(", "))
+        }
+    }
+}
+
+fn relative_path(root: &Path, path: &Path) -> String {
+    let path = path.strip_prefix(root).unwrap_or(path);
+    let parts: Vec<String> = path
+        .components()
+        .map(|part| part.as_os_str().to_string_lossy().to_string())
+        .collect();
+    parts.join("/")
+}
+
+fn check_game(
+    loaded_game: &LoadedGame,
+    path: String,
+    difficulty: u32,
+    seeds: &[u64],
+    options: &Options,
+) -> WeeResult<Report> {
+    let mut report = Report {
+        path,
+        difficulty,
+        seeds: seeds.len(),
+        wins: Vec::new(),
+        fixture: None,
+    };
+    for &seed in seeds {
+        let mut search = Search::new(loaded_game, difficulty, seed, options);
+        for &strategy in Strategy::ALL.iter() {
+            if let Some(run) = search.find_win(strategy)? {
+                report.wins.push(strategy);
+                if report.fixture.is_none() && run.has_finished() {
+                    report.fixture = Some(SavedRun {
+                        path: report.path.clone(),
+                        inputs: run.inputs,
+                        difficulty,
+                        seed,
+                        has_been_won: true,
+                    });
+                }
+                break;
+            }
+        }
+    }
+    Ok(report)
+}
+
+// Plays a saved run back the same way the saved run tests do
+fn replay(loaded_game: &LoadedGame, saved_run: &SavedRun) -> WeeResult<bool> {
+    let mut run = Run::start(loaded_game, saved_run.difficulty, saved_run.seed)?;
+    let mut inputs = saved_run.inputs.iter();
+    while !run.has_finished() {
+        let mouse = inputs.next().copied().unwrap_or_default();
+        let world_actions = run.game.update_frame(mouse, &mut run.rng)?;
+        if world_actions
+            .iter()
+            .any(|action| matches!(action, WorldAction::EndEarly))
+        {
+            break;
+        }
+    }
+    Ok(run.has_been_won())
+}
+
+fn save_fixture(loaded_game: &LoadedGame, report: &Report, directory: &Path) -> WeeResult<()> {
+    if let Some(saved_run) = &report.fixture {
+        if !replay(loaded_game, saved_run)? {
+            return Err(format!("Winning run for {} didn't replay as a win", report.path).into());
+        }
+        let stem = Path::new(&report.path)
+            .file_stem()
+            .ok_or("Could not get file stem")?
+            .to_string_lossy()
+            .to_string();
+        let filename = directory.join(format!(
+            "{}-difficulty-{}-won.savedrun",
+            stem, report.difficulty
+        ));
+        if filename.exists() {
+            println!("  {:?} already exists, not overwriting it", filename);
+        } else {
+            fs::write(&filename, bincode::serialize(saved_run)?)?;
+            println!("  Saved winning run to {:?}", filename);
+        }
+    }
+    Ok(())
+}
+
+fn run() -> WeeResult<bool> {
+    let options = Options::from_args()?;
+    let mut all_winnable = true;
+
+    for file in options.game_files()? {
+        let loaded_game = match load_game(&file) {
+            Ok(loaded_game) => loaded_game,
+            Err(error) => {
+                eprintln!("Skipping {:?}: {}", file, error);
+                continue;
+            }
+        };
+        if loaded_game.data.game_type == GameType::Other {
+            continue;
+        }
+        let path = relative_path(&options.root, &file);
+
+        for &difficulty in &options.difficulties {
+            let seeds = options.seeds();
+            let report = check_game(&loaded_game, path.clone(), difficulty, &seeds, &options)?;
+            println!("{}", report);
+
+            all_winnable &= !report.wins.is_empty();
+            if let Some(directory) = &options.fixtures {
+                save_fixture(&loaded_game, &report, directory)?;
+            }
+        }
+    }
+
+    Ok(all_winnable)
+}
+
+fn main() {
+    match run() {
+        Ok(true) => {}
+        Ok(false) => process::exit(1),
+        Err(error) => {
+            eprintln!("{}", error);
+            process::exit(2);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wee_common::{Colour, Size};
+
+    // A button that has to be clicked to win
+    fn button_game() -> LoadedGame {
+        let mut data = GameData {
+            length: Length::Seconds(1.0),
+            ..GameData::default()
+        };
+        data.objects.push(SerialiseObject {
+            name: "Button".to_string(),
+            sprite: Sprite::Colour(Colour::black()),
+            position: Vec2::new(400.0, 300.0),
+            size: Size::new(100.0, 100.0),
+            instructions: vec![Instruction {
+                triggers: vec![Trigger::Input(Input::Mouse {
+                    over: MouseOver::Object {
+                        name: "Button".to_string(),
+                    },
+                    interaction: MouseInteraction::Button {
+                        state: ButtonState::Press,
+                    },
+                })],
+                actions: vec![Action::Win],
+            }],
+            ..SerialiseObject::default()
+        });
+        LoadedGame {
+            data,
+            masks: CollisionMasks::default(),
+        }
+    }
+
+    #[test]
+    fn sweep_clicks_the_button_and_saves_a_fixture_that_replays() {
+        let loaded_game = button_game();
+        let options = Options::default();
+        let mut search = Search::new(&loaded_game, 1, 7, &options);
+        assert!(search.find_win(Strategy::Idle).unwrap().is_none());
+        let run = search.find_win(Strategy::Sweep).unwrap().unwrap();
+        assert!(run.has_finished());
+
+        let report =
+            check_game(&loaded_game, "button.json".to_string(), 1, &[7], &options).unwrap();
+        assert_eq!(report.wins, vec![Strategy::Sweep]);
+
+        let directory = env::temp_dir().join(format!("wee-check-test-{}", process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        save_fixture(&loaded_game, &report, &directory).unwrap();
+        let saved = fs::read(directory.join("button-difficulty-1-won.savedrun"));
+        fs::remove_dir_all(&directory).unwrap();
+
+        let saved_run: SavedRun = bincode::deserialize(&saved.unwrap()).unwrap();
+        let fixture = report.fixture.as_ref().unwrap();
+        assert_eq!(saved_run.inputs.len(), fixture.inputs.len());
+        assert_eq!((saved_run.seed, saved_run.difficulty), (7, 1));
+        assert!(replay(&loaded_game, &saved_run).unwrap());
+    }
+}
@@ -220,17 +220,8 @@ impl std::ops::Div<f32> for Size {
     }
 }
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Default, Copy, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Flip {
     pub horizontal: bool,
     pub vertical: bool,
 }
-
-impl Default for Flip {
-    fn default() -> Flip {
-        Flip {
-            horizontal: false,
-            vertical: false,
-        }
-    }
-}
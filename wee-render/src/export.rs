@@ -0,0 +1,304 @@
+// Turns saved runs into animated GIFs or numbered PNGs for sharing clips of games
+
+use crate::{save_png, Replay};
+use image::{Rgba, RgbaImage};
+use serde_json::json;
+use std::{
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+use wee::{ButtonState, Mouse, SavedRun};
+use wee_common::WeeResult;
+
+const FRAMES_PER_SECOND: u32 = 60;
+const MANIFEST_FILENAME: &str = "clip.json";
+// Lower is better quality but much slower to encode
+const GIF_SPEED: i32 = 10;
+const CURSOR_PIXEL_SIZE: f32 = 2.0;
+// X is the outline and O is the fill
+const CURSOR: [&str; 17] = [
+    "X..........",
+    "XX.........",
+    "XOX........",
+    "XOOX.......",
+    "XOOOX......",
+    "XOOOOX.....",
+    "XOOOOOX....",
+    "XOOOOOOX...",
+    "XOOOOOOOX..",
+    "XOOOOOOOOX.",
+    "XOOOOOXXXXX",
+    "XOOXOOX....",
+    "XOX.XOOX...",
+    "XX..XOOX...",
+    "X....XOOX..",
+    ".....XOOX..",
+    "......XX...",
+];
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ExportFormat {
+    Gif,
+    // A directory of numbered PNGs with a clip.json saying how to play them back
+    PngSequence,
+}
+
+impl ExportFormat {
+    pub fn from_path(path: impl AsRef<Path>) -> ExportFormat {
+        let is_gif = path
+            .as_ref()
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("gif"));
+        if is_gif {
+            ExportFormat::Gif
+        } else {
+            ExportFormat::PngSequence
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ExportSettings {
+    pub scale: f32,
+    // How many frames are left out after each one that's kept
+    pub frame_skip: u32,
+    pub show_cursor: bool,
+}
+
+impl Default for ExportSettings {
+    fn default() -> ExportSettings {
+        ExportSettings {
+            scale: 0.5,
+            frame_skip: 2,
+            show_cursor: false,
+        }
+    }
+}
+
+impl ExportSettings {
+    pub fn frame_delay_ms(&self) -> f32 {
+        (self.frame_skip + 1) as f32 * 1000.0 / FRAMES_PER_SECOND as f32
+    }
+}
+
+// Each kept frame is handed over as soon as it's drawn so whole clips are never held in memory
+pub fn record(
+    replay: &mut Replay,
+    settings: ExportSettings,
+    mut write_frame: impl FnMut(RgbaImage) -> WeeResult<()>,
+) -> WeeResult<()> {
+    while replay.next_frame()? {
+        if (replay.game.frames.ran - 1).is_multiple_of(settings.frame_skip + 1) {
+            let mut frame = replay.render_scaled(settings.scale);
+            if settings.show_cursor {
+                draw_cursor(&mut frame, replay.mouse, settings.scale);
+            }
+            write_frame(frame)?;
+        }
+    }
+    Ok(())
+}
+
+// Saves a GIF if the output ends in .gif, otherwise a directory of PNGs. Returns the frame count
+pub fn export(
+    saved_run: &SavedRun,
+    root: impl AsRef<Path>,
+    intro_font: impl AsRef<Path>,
+    language: &str,
+    output: impl AsRef<Path>,
+    settings: ExportSettings,
+) -> WeeResult<usize> {
+    let replay = Replay::load(saved_run, root, intro_font, language)?;
+    export_replay(replay, output, settings)
+}
+
+pub fn export_replay(
+    mut replay: Replay,
+    output: impl AsRef<Path>,
+    settings: ExportSettings,
+) -> WeeResult<usize> {
+    match ExportFormat::from_path(&output) {
+        ExportFormat::Gif => {
+            let mut gif = GifWriter::new(output, settings);
+            record(&mut replay, settings, |frame| gif.write_frame(frame))?;
+            gif.finish()
+        }
+        ExportFormat::PngSequence => {
+            let mut pngs = PngSequenceWriter::new(output, settings)?;
+            record(&mut replay, settings, |frame| pngs.write_frame(&frame))?;
+            pngs.finish()
+        }
+    }
+}
+
+// Only the part of each frame that changed is stored, since most games have still backgrounds
+pub struct GifWriter {
+    path: PathBuf,
+    settings: ExportSettings,
+    // Made with the size of the first frame
+    encoder: Option<gif::Encoder<BufWriter<File>>>,
+    previous: Option<RgbaImage>,
+    frame_count: usize,
+    // Kept as a total so long clips don't run past what one frame's delay can hold
+    elapsed_centiseconds: u64,
+}
+
+impl GifWriter {
+    pub fn new(path: impl AsRef<Path>, settings: ExportSettings) -> GifWriter {
+        GifWriter {
+            path: path.as_ref().to_path_buf(),
+            settings,
+            encoder: None,
+            previous: None,
+            frame_count: 0,
+            elapsed_centiseconds: 0,
+        }
+    }
+
+    pub fn write_frame(&mut self, frame: RgbaImage) -> WeeResult<()> {
+        let (width, height) = frame.dimensions();
+        let encoder = match &mut self.encoder {
+            Some(encoder) => encoder,
+            None => {
+                let file = BufWriter::new(File::create(&self.path)?);
+                let mut encoder = gif::Encoder::new(file, width as u16, height as u16, &[])?;
+                encoder.set_repeat(gif::Repeat::Infinite)?;
+                self.encoder.get_or_insert(encoder)
+            }
+        };
+        let area = self
+            .previous
+            .as_ref()
+            .map_or(Some((0, 0, width, height)), |previous| {
+                changed_area(previous, &frame)
+            })
+            .unwrap_or((0, 0, 1, 1));
+        let (left, top, area_width, area_height) = area;
+        let mut pixels = image::imageops::crop_imm(&frame, left, top, area_width, area_height)
+            .to_image()
+            .into_raw();
+        let mut gif_frame = gif::Frame::from_rgba_speed(
+            area_width as u16,
+            area_height as u16,
+            &mut pixels,
+            GIF_SPEED,
+        );
+        gif_frame.left = left as u16;
+        gif_frame.top = top as u16;
+        self.frame_count += 1;
+        // Rounding each delay would make short frames drift, so the total time is rounded instead
+        let end =
+            (self.frame_count as f64 * self.settings.frame_delay_ms() as f64 / 10.0).round() as u64;
+        gif_frame.delay = (end - self.elapsed_centiseconds) as u16;
+        self.elapsed_centiseconds = end;
+        encoder.write_frame(&gif_frame)?;
+        self.previous = Some(frame);
+        Ok(())
+    }
+
+    // Returns the frame count
+    pub fn finish(self) -> WeeResult<usize> {
+        match self.encoder {
+            Some(encoder) => {
+                encoder.into_inner()?.flush()?;
+                Ok(self.frame_count)
+            }
+            None => Err("There are no frames to save".into()),
+        }
+    }
+}
+
+pub struct PngSequenceWriter {
+    directory: PathBuf,
+    settings: ExportSettings,
+    filenames: Vec<String>,
+    dimensions: (u32, u32),
+}
+
+impl PngSequenceWriter {
+    pub fn new(
+        directory: impl AsRef<Path>,
+        settings: ExportSettings,
+    ) -> WeeResult<PngSequenceWriter> {
+        fs::create_dir_all(&directory)?;
+        Ok(PngSequenceWriter {
+            directory: directory.as_ref().to_path_buf(),
+            settings,
+            filenames: Vec::new(),
+            dimensions: (0, 0),
+        })
+    }
+
+    pub fn write_frame(&mut self, frame: &RgbaImage) -> WeeResult<()> {
+        let filename = format!("frame-{:04}.png", self.filenames.len());
+        save_png(frame, self.directory.join(&filename))?;
+        if self.filenames.is_empty() {
+            self.dimensions = frame.dimensions();
+        }
+        self.filenames.push(filename);
+        Ok(())
+    }
+
+    // Writes the manifest and returns the frame count
+    pub fn finish(self) -> WeeResult<usize> {
+        let (width, height) = self.dimensions;
+        let manifest = json!({
+            "width": width,
+            "height": height,
+            "frame_delay_ms": self.settings.frame_delay_ms(),
+            "frames": self.filenames,
+        });
+        fs::write(
+            self.directory.join(MANIFEST_FILENAME),
+            serde_json::to_string_pretty(&manifest)?,
+        )?;
+        Ok(self.filenames.len())
+    }
+}
+
+// The left, top, width and height of the pixels that are different, or None if none are
+fn changed_area(previous: &RgbaImage, frame: &RgbaImage) -> Option<(u32, u32, u32, u32)> {
+    let mut min = (u32::MAX, u32::MAX);
+    let mut max = (0, 0);
+    for (x, y, pixel) in frame.enumerate_pixels() {
+        if previous.get_pixel(x, y) != pixel {
+            min = (min.0.min(x), min.1.min(y));
+            max = (max.0.max(x), max.1.max(y));
+        }
+    }
+    if min.0 > max.0 {
+        None
+    } else {
+        Some((min.0, min.1, max.0 - min.0 + 1, max.1 - min.1 + 1))
+    }
+}
+
+// The cursor isn't shaken with the game since it's drawn over the window
+fn draw_cursor(frame: &mut RgbaImage, mouse: Mouse, scale: f32) {
+    let pixel_size = (CURSOR_PIXEL_SIZE * scale).round().max(1.0) as i64;
+    let fill = match mouse.state {
+        ButtonState::Down | ButtonState::Press => Rgba([255, 220, 0, 255]),
+        ButtonState::Up | ButtonState::Release => Rgba([255, 255, 255, 255]),
+    };
+    let left = (mouse.position.x * scale).round() as i64;
+    let top = (mouse.position.y * scale).round() as i64;
+    for (row, line) in CURSOR.iter().enumerate() {
+        for (column, cell) in line.chars().enumerate() {
+            let colour = match cell {
+                'X' => Rgba([0, 0, 0, 255]),
+                'O' => fill,
+                _ => continue,
+            };
+            for y in 0..pixel_size {
+                for x in 0..pixel_size {
+                    let x = left + column as i64 * pixel_size + x;
+                    let y = top + row as i64 * pixel_size + y;
+                    if x >= 0 && y >= 0 && x < frame.width() as i64 && y < frame.height() as i64 {
+                        frame.put_pixel(x as u32, y as u32, colour);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,280 @@
+// Replays saved runs and compares frames from them against stored golden images
+//
+// Run with: cargo test --release -- --ignored
+// Rebless with: cargo run --release -- --bless-goldens
+
+use crate::{load_saved_run, save_png, Replay};
+use image::{Rgba, RgbaImage};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use wee::{SavedRun, DEFAULT_LANGUAGE};
+use wee_common::WeeResult;
+
+// A quarter size shows small sprites and text while keeping goldens quick to draw
+pub const GOLDEN_SCALE: f32 = 0.25;
+// Used unless a saved run has a frames file beside its goldens, like bird-won.frames.json
+pub const CAPTURED_FRAMES: [u32; 2] = [1, 60];
+const LAST_FRAME_LABEL: &str = "last";
+
+pub struct CapturedFrame {
+    pub label: String,
+    pub image: RgbaImage,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Tolerance {
+    // How far apart a channel can be before the pixel counts as different
+    pub channel: u8,
+    // The fraction of pixels that can be different before the frames don't match
+    pub differing_pixels: f32,
+}
+
+impl Default for Tolerance {
+    fn default() -> Tolerance {
+        Tolerance {
+            channel: 8,
+            differing_pixels: 0.002,
+        }
+    }
+}
+
+pub struct Comparison {
+    pub differing_pixels: usize,
+    pub total_pixels: usize,
+    pub diff: RgbaImage,
+}
+
+impl Comparison {
+    pub fn matches(&self, tolerance: Tolerance) -> bool {
+        self.differing_pixels as f32 <= self.total_pixels as f32 * tolerance.differing_pixels
+    }
+}
+
+// Saved runs sorted by filename so failures are always listed in the same order
+pub fn saved_run_paths(directory: impl AsRef<Path>) -> WeeResult<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(directory)? {
+        let path = entry?.path();
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "savedrun")
+        {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+fn run_name(saved_run_path: &Path) -> String {
+    saved_run_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+pub fn golden_path(goldens: impl AsRef<Path>, saved_run_path: &Path, label: &str) -> PathBuf {
+    let name = run_name(saved_run_path);
+    goldens.as_ref().join(format!("{}-{}.png", name, label))
+}
+
+pub fn frames_path(goldens: impl AsRef<Path>, saved_run_path: &Path) -> PathBuf {
+    let name = run_name(saved_run_path);
+    goldens.as_ref().join(format!("{}.frames.json", name))
+}
+
+// A JSON list of frame numbers, so a run can capture the moments that matter in its game
+pub fn captured_frames(goldens: impl AsRef<Path>, saved_run_path: &Path) -> WeeResult<Vec<u32>> {
+    let path = frames_path(goldens, saved_run_path);
+    if !path.exists() {
+        return Ok(CAPTURED_FRAMES.to_vec());
+    }
+    serde_json::from_str(&fs::read_to_string(&path)?)
+        .map_err(|error| format!("Couldn't read {:?}: {}", path, error).into())
+}
+
+// Plays the inputs the same way the saved run tests do, capturing the chosen frames on the way
+pub fn replay(
+    saved_run: &SavedRun,
+    root: impl AsRef<Path>,
+    intro_font: impl AsRef<Path>,
+    captured_frames: &[u32],
+) -> WeeResult<Vec<CapturedFrame>> {
+    let mut replay = Replay::load(saved_run, root, intro_font, DEFAULT_LANGUAGE)?;
+    let mut frames = Vec::new();
+    while replay.next_frame()? {
+        if captured_frames.contains(&replay.game.frames.ran) {
+            frames.push(CapturedFrame {
+                label: replay.game.frames.ran.to_string(),
+                image: replay.render_scaled(GOLDEN_SCALE),
+            });
+        }
+    }
+    frames.push(CapturedFrame {
+        label: LAST_FRAME_LABEL.to_string(),
+        image: replay.render_scaled(GOLDEN_SCALE),
+    });
+
+    Ok(frames)
+}
+
+// The diff shows the expected frame faded to grey with the differing pixels in red
+pub fn compare(expected: &RgbaImage, actual: &RgbaImage, tolerance: Tolerance) -> Comparison {
+    let total_pixels = (expected.width() * expected.height()) as usize;
+    if expected.dimensions() != actual.dimensions() {
+        return Comparison {
+            differing_pixels: total_pixels,
+            total_pixels,
+            diff: RgbaImage::from_pixel(
+                expected.width(),
+                expected.height(),
+                Rgba([255, 0, 0, 255]),
+            ),
+        };
+    }
+
+    let mut differing_pixels = 0;
+    let mut diff = RgbaImage::new(expected.width(), expected.height());
+    for ((expected, actual), diff) in expected
+        .pixels()
+        .zip(actual.pixels())
+        .zip(diff.pixels_mut())
+    {
+        let differs = expected
+            .0
+            .iter()
+            .zip(actual.0.iter())
+            .any(|(a, b)| (*a as i16 - *b as i16).abs() > tolerance.channel as i16);
+        *diff = if differs {
+            differing_pixels += 1;
+            Rgba([255, 0, 0, 255])
+        } else {
+            let [r, g, b, _] = expected.0;
+            let grey = (r as u32 * 3 + g as u32 * 6 + b as u32) / 10;
+            let faded = (grey / 3 + 170) as u8;
+            Rgba([faded, faded, faded, 255])
+        };
+    }
+
+    Comparison {
+        differing_pixels,
+        total_pixels,
+        diff,
+    }
+}
+
+// Replaces the goldens for every saved run, returning how many images were written
+pub fn bless(
+    saved_runs: impl AsRef<Path>,
+    goldens: impl AsRef<Path>,
+    root: impl AsRef<Path>,
+    intro_font: impl AsRef<Path>,
+) -> WeeResult<usize> {
+    fs::create_dir_all(&goldens)?;
+    let mut written = 0;
+    for path in saved_run_paths(saved_runs)? {
+        let saved_run = load_saved_run(&path)?;
+        let captured_frames = captured_frames(&goldens, &path)?;
+        let frames = replay(&saved_run, &root, &intro_font, &captured_frames)
+            .map_err(|error| format!("Couldn't replay {:?}: {}", path, error))?;
+        for frame in frames {
+            save_png(&frame.image, golden_path(&goldens, &path, &frame.label))?;
+            written += 1;
+        }
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAVED_RUNS: &str = "../wee/saved-runs";
+    const GOLDENS: &str = "goldens";
+    const ROOT: &str = "../main-game";
+    const INTRO_FONT: &str = "../main-game/fonts/Roboto-Medium.ttf";
+    const DIFFS: &str = "target/golden-diffs";
+
+    #[test]
+    #[ignore = "replays every saved run, run with cargo test --release -- --ignored"]
+    fn saved_runs_match_golden_frames() {
+        let tolerance = Tolerance::default();
+        let mut failures = Vec::new();
+
+        for path in saved_run_paths(SAVED_RUNS).unwrap() {
+            let saved_run = load_saved_run(&path).unwrap();
+            let frames = match captured_frames(GOLDENS, &path)
+                .and_then(|captured| replay(&saved_run, ROOT, INTRO_FONT, &captured))
+            {
+                Ok(frames) => frames,
+                Err(error) => {
+                    failures.push(format!("{:?}: {}", path, error));
+                    continue;
+                }
+            };
+            for frame in frames {
+                let golden = golden_path(GOLDENS, &path, &frame.label);
+                let expected = match image::open(&golden) {
+                    Ok(expected) => expected.into_rgba8(),
+                    Err(error) => {
+                        failures.push(format!("{:?}: {}", golden, error));
+                        continue;
+                    }
+                };
+                let comparison = compare(&expected, &frame.image, tolerance);
+                if !comparison.matches(tolerance) {
+                    fs::create_dir_all(DIFFS).unwrap();
+                    let diff_path = golden_path(DIFFS, &path, &format!("{}-diff", frame.label));
+                    let actual_path = golden_path(DIFFS, &path, &format!("{}-actual", frame.label));
+                    save_png(&comparison.diff, &diff_path).unwrap();
+                    save_png(&frame.image, &actual_path).unwrap();
+                    failures.push(format!(
+                        "{:?}: {} of {} pixels differ, see {:?}",
+                        golden, comparison.differing_pixels, comparison.total_pixels, diff_path
+                    ));
+                }
+            }
+        }
+
+        assert!(
+            failures.is_empty(),
+            "{}\n\nIf these changes are expected rebless with: cargo run --release -- --bless-goldens",
+            failures.join("\n")
+        );
+    }
+
+    #[test]
+    fn saved_runs_capture_their_listed_frames_or_the_defaults() {
+        let goldens = std::env::temp_dir().join("wee-render-captured-frames");
+        fs::create_dir_all(&goldens).unwrap();
+        let listed = Path::new("saved-runs/listed.savedrun");
+        fs::write(frames_path(&goldens, listed), "[5, 90, 120]").unwrap();
+
+        assert_eq!(captured_frames(&goldens, listed).unwrap(), vec![5, 90, 120]);
+        assert_eq!(
+            captured_frames(&goldens, Path::new("unlisted.savedrun")).unwrap(),
+            CAPTURED_FRAMES.to_vec()
+        );
+        fs::write(frames_path(&goldens, listed), "[5, ").unwrap();
+        assert!(captured_frames(&goldens, listed).is_err());
+    }
+
+    #[test]
+    fn compare_allows_small_differences() {
+        let expected = RgbaImage::from_pixel(10, 10, Rgba([100, 100, 100, 255]));
+        let mut actual = RgbaImage::from_pixel(10, 10, Rgba([104, 100, 96, 255]));
+        let tolerance = Tolerance {
+            channel: 8,
+            differing_pixels: 0.0,
+        };
+        assert!(compare(&expected, &actual, tolerance).matches(tolerance));
+
+        actual.put_pixel(3, 4, Rgba([0, 0, 0, 255]));
+        let comparison = compare(&expected, &actual, tolerance);
+        assert_eq!(comparison.differing_pixels, 1);
+        assert!(!comparison.matches(tolerance));
+        assert_eq!(*comparison.diff.get_pixel(3, 4), Rgba([255, 0, 0, 255]));
+    }
+}
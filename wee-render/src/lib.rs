@@ -0,0 +1,529 @@
+// Draws games on the CPU so frames can be looked at without a window or a GPU
+
+use bracket_random::prelude::*;
+use image::{
+    codecs::png::{CompressionType, FilterType, PngEncoder},
+    ColorType, DynamicImage, Rgba, RgbaImage,
+};
+use rusttype::{point, Font, Scale};
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::BufWriter,
+    path::Path,
+};
+use wee::{
+    CollisionMasks, DrawCommand, DrawnText, FontMetrics, FrameCount, Game, GameData, ImageRect,
+    Mouse, SavedRun, ShapeVertex, SpriteTransform, Translations, WeeRng, WorldAction,
+};
+use wee_common::{Colour, Size, Vec2, WeeResult, AABB, PROJECTION_HEIGHT, PROJECTION_WIDTH};
+
+pub mod export;
+pub mod golden;
+
+pub const FRAME_WIDTH: u32 = PROJECTION_WIDTH as u32;
+pub const FRAME_HEIGHT: u32 = PROJECTION_HEIGHT as u32;
+
+const INTRO_FONT_SIZE: f32 = 174.0;
+const INTRO_TEXT_OUTLINE: f32 = 2.0;
+
+// Shaking moves everything by the offset, then the frame is scaled down from the projection
+#[derive(Debug, Copy, Clone)]
+struct View {
+    offset: Vec2,
+    scale: f32,
+}
+
+struct LoadedFont {
+    font: Font<'static>,
+    scale: Scale,
+}
+
+impl LoadedFont {
+    fn load(path: impl AsRef<Path>, size: f32) -> WeeResult<LoadedFont> {
+        let bytes = fs::read(&path)?;
+        let font = Font::try_from_vec(bytes)
+            .ok_or_else(|| format!("Couldn't read font {:?}", path.as_ref()))?;
+        Ok(LoadedFont {
+            font,
+            scale: Scale::uniform(size),
+        })
+    }
+
+    fn measure(&self, text: &str) -> Size {
+        self.measure_scaled(text, 1.0)
+    }
+
+    fn scaled(&self, scale: f32) -> Scale {
+        Scale {
+            x: self.scale.x * scale,
+            y: self.scale.y * scale,
+        }
+    }
+
+    fn measure_scaled(&self, text: &str, scale: f32) -> Size {
+        let scale = self.scaled(scale);
+        let v_metrics = self.font.v_metrics(scale);
+        let width = self
+            .font
+            .layout(text, scale, point(0.0, 0.0))
+            .last()
+            .map(|glyph| glyph.position().x + glyph.unpositioned().h_metrics().advance_width)
+            .unwrap_or(0.0);
+        Size::new(width.ceil(), (v_metrics.ascent - v_metrics.descent).ceil())
+    }
+
+    // Text gets its own image so it can be drawn like any other sprite
+    fn rasterise(&self, text: &str, colour: Colour, scale: f32) -> Option<RgbaImage> {
+        let size = self.measure_scaled(text, scale);
+        if size.width < 1.0 || size.height < 1.0 {
+            return None;
+        }
+        let mut image = RgbaImage::new(size.width as u32, size.height as u32);
+        let scale = self.scaled(scale);
+        let ascent = self.font.v_metrics(scale).ascent;
+        for glyph in self.font.layout(text, scale, point(0.0, ascent)) {
+            let bounds = match glyph.pixel_bounding_box() {
+                Some(bounds) => bounds,
+                None => continue,
+            };
+            glyph.draw(|x, y, coverage| {
+                let x = x as i32 + bounds.min.x;
+                let y = y as i32 + bounds.min.y;
+                if x < 0 || y < 0 || x as u32 >= image.width() || y as u32 >= image.height() {
+                    return;
+                }
+                let pixel = image.get_pixel_mut(x as u32, y as u32);
+                let alpha = (coverage * colour.a).max(pixel[3] as f32 / 255.0);
+                *pixel = to_rgba(Colour { a: alpha, ..colour });
+            });
+        }
+        Some(image)
+    }
+}
+
+impl FontMetrics for LoadedFont {
+    fn text_width(&self, text: &str) -> f32 {
+        self.measure(text).width
+    }
+
+    fn line_height(&self) -> f32 {
+        self.measure("").height
+    }
+}
+
+#[derive(Clone)]
+pub struct SeededRng(RandomNumberGenerator);
+
+impl SeededRng {
+    pub fn new(seed: u64) -> SeededRng {
+        SeededRng(RandomNumberGenerator::seeded(seed))
+    }
+}
+
+impl WeeRng for SeededRng {
+    fn random_in_range(&mut self, min: f32, max: f32) -> f32 {
+        self.0.range(min, max)
+    }
+
+    fn random_in_range_u32(&mut self, min: u32, max: u32) -> u32 {
+        self.0.range(min, max)
+    }
+
+    fn random_in_slice<'a, T>(&mut self, slice: &'a [T]) -> Option<&'a T> {
+        self.0.random_slice_entry(slice)
+    }
+
+    fn coin_flip(&mut self) -> bool {
+        self.0.roll_dice(1, 2) == 1
+    }
+}
+
+pub struct SoftwareRenderer {
+    images: HashMap<String, RgbaImage>,
+    fonts: HashMap<String, LoadedFont>,
+    intro_font: Option<LoadedFont>,
+}
+
+impl SoftwareRenderer {
+    pub fn load(game_data: &GameData, base_path: impl AsRef<Path>) -> WeeResult<SoftwareRenderer> {
+        let base_path = base_path.as_ref();
+
+        let mut images = HashMap::new();
+        for (name, filename) in &game_data.asset_files.images {
+            let path = base_path.join("images").join(filename);
+            let image = image::open(&path)
+                .map_err(|error| format!("Couldn't load image {:?}: {}", path, error))?;
+            images.insert(name.clone(), image.into_rgba8());
+        }
+
+        let mut fonts = HashMap::new();
+        for (name, font_info) in &game_data.asset_files.fonts {
+            let path = base_path.join("fonts").join(&font_info.filename);
+            fonts.insert(name.clone(), LoadedFont::load(path, font_info.size)?);
+        }
+
+        Ok(SoftwareRenderer {
+            images,
+            fonts,
+            intro_font: None,
+        })
+    }
+
+    // Intro text is left out until there's a font to draw it with
+    pub fn load_intro_font(&mut self, path: impl AsRef<Path>) -> WeeResult<()> {
+        self.intro_font = Some(LoadedFont::load(path, INTRO_FONT_SIZE)?);
+        Ok(())
+    }
+
+    pub fn render(&self, game: &Game, drawn_text: &HashMap<String, DrawnText>) -> RgbaImage {
+        self.render_scaled(game, drawn_text, 1.0)
+    }
+
+    // Smaller frames are much quicker to draw when the detail isn't needed
+    pub fn render_scaled(
+        &self,
+        game: &Game,
+        drawn_text: &HashMap<String, DrawnText>,
+        scale: f32,
+    ) -> RgbaImage {
+        let size = |length: u32| ((length as f32 * scale).round() as u32).max(1);
+        let mut frame =
+            RgbaImage::from_pixel(size(FRAME_WIDTH), size(FRAME_HEIGHT), Rgba([0, 0, 0, 255]));
+        let view = View {
+            offset: game.screen_effects().offset,
+            scale,
+        };
+
+        let projection = AABB::new(0.0, 0.0, PROJECTION_WIDTH, PROJECTION_HEIGHT);
+        fill(&mut frame, SpriteTransform::new(projection), view, |_| {
+            Colour::white()
+        });
+
+        for command in game.draw_commands(drawn_text, |font| self.fonts.get(font)) {
+            match command {
+                DrawCommand::Image {
+                    name,
+                    region,
+                    transform,
+                } => {
+                    if let Some(image) = self.images.get(&name) {
+                        let whole = ImageRect::new(0, 0, image.width(), image.height());
+                        let region = region
+                            .map(|region| region.clamped(image.width(), image.height()))
+                            .unwrap_or(whole);
+                        draw_image_region(&mut frame, image, region, transform, view);
+                    }
+                }
+                DrawCommand::Rectangle { colour, transform } => {
+                    fill(&mut frame, transform, view, |_| colour)
+                }
+                DrawCommand::Triangles { vertices } => {
+                    for triangle in vertices.chunks_exact(3) {
+                        fill_triangle(&mut frame, triangle, view);
+                    }
+                }
+                DrawCommand::Text {
+                    text,
+                    font,
+                    colour,
+                    scale,
+                    transform,
+                    ..
+                } => {
+                    let image = self
+                        .fonts
+                        .get(&font)
+                        .and_then(|font| font.rasterise(&text, colour, scale));
+                    if let Some(image) = image {
+                        draw_image(&mut frame, &image, transform, view);
+                    }
+                }
+                DrawCommand::IntroText { text, centre } => {
+                    if let Some(font) = &self.intro_font {
+                        draw_intro_text(&mut frame, font, &text, centre, view);
+                    }
+                }
+                DrawCommand::Invert => {
+                    for pixel in frame.pixels_mut() {
+                        let [r, g, b, a] = pixel.0;
+                        *pixel = Rgba([255 - r, 255 - g, 255 - b, a]);
+                    }
+                }
+                DrawCommand::Cover { colour } => {
+                    for pixel in frame.pixels_mut() {
+                        blend(pixel, colour);
+                    }
+                }
+            }
+        }
+
+        frame
+    }
+}
+
+// Plays back a saved run a frame at a time so callers can render whichever frames they need
+pub struct Replay {
+    pub game: Game,
+    pub renderer: SoftwareRenderer,
+    pub drawn_text: HashMap<String, DrawnText>,
+    pub mouse: Mouse,
+    rng: SeededRng,
+    inputs: std::vec::IntoIter<Mouse>,
+    ended_early: bool,
+}
+
+impl Replay {
+    // The saved run's path is relative to root, or can be absolute. Text is translated the same
+    // way as when rendering a single frame
+    pub fn load(
+        saved_run: &SavedRun,
+        root: impl AsRef<Path>,
+        intro_font: impl AsRef<Path>,
+        language: &str,
+    ) -> WeeResult<Replay> {
+        let path = root.as_ref().join(saved_run.path.replace('\\', "/"));
+        let data: GameData = serde_json::from_str(&fs::read_to_string(&path)?)?;
+        let base_path = path.parent().ok_or("Could not get game directory")?;
+        Replay::from_data(data, base_path, saved_run, intro_font, language)
+    }
+
+    // For a game that's already loaded, like one open in the editor. The saved run's path is
+    // ignored and assets are loaded from the base path
+    pub fn from_data(
+        data: GameData,
+        base_path: impl AsRef<Path>,
+        saved_run: &SavedRun,
+        intro_font: impl AsRef<Path>,
+        language: &str,
+    ) -> WeeResult<Replay> {
+        let base_path = base_path.as_ref();
+        let mut data = data;
+        data.localise(&Translations::load(base_path, language)?);
+        let masks = CollisionMasks::load(&data, base_path)?;
+        let mut renderer = SoftwareRenderer::load(&data, base_path)?;
+        renderer.load_intro_font(intro_font)?;
+
+        let mut rng = SeededRng::new(saved_run.seed);
+        let mut game = Game::from_data(data, &mut rng)?;
+        game.difficulty = saved_run.difficulty;
+        game.set_collision_masks(masks);
+
+        Ok(Replay {
+            game,
+            renderer,
+            drawn_text: HashMap::new(),
+            mouse: Mouse::default(),
+            rng,
+            inputs: saved_run.inputs.clone().into_iter(),
+            ended_early: false,
+        })
+    }
+
+    // Returns false without updating once the game has finished
+    pub fn next_frame(&mut self) -> WeeResult<bool> {
+        if self.ended_early || self.game.frames.remaining() == FrameCount::Frames(0) {
+            return Ok(false);
+        }
+        self.mouse = self.inputs.next().unwrap_or_default();
+        let actions = self.game.update_frame(self.mouse, &mut self.rng)?;
+        self.ended_early = actions
+            .iter()
+            .any(|action| matches!(action, WorldAction::EndEarly));
+        add_drawn_text(actions, &mut self.drawn_text);
+        Ok(true)
+    }
+
+    pub fn render_scaled(&self, scale: f32) -> RgbaImage {
+        self.renderer
+            .render_scaled(&self.game, &self.drawn_text, scale)
+    }
+}
+
+pub fn load_saved_run(path: impl AsRef<Path>) -> WeeResult<SavedRun> {
+    Ok(bincode::deserialize(&fs::read(path)?)?)
+}
+
+// Frames are always opaque so the alpha channel is dropped to keep the files small
+pub fn save_png(frame: &RgbaImage, path: impl AsRef<Path>) -> WeeResult<()> {
+    let rgb = DynamicImage::ImageRgba8(frame.clone()).into_rgb8();
+    let encoder = PngEncoder::new_with_quality(
+        BufWriter::new(File::create(path)?),
+        CompressionType::Best,
+        FilterType::Paeth,
+    );
+    encoder.encode(&rgb, rgb.width(), rgb.height(), ColorType::Rgb8)?;
+    Ok(())
+}
+
+// Keeps the text objects have been told to draw, the same as the frontends do
+pub fn add_drawn_text(actions: Vec<WorldAction>, drawn_text: &mut HashMap<String, DrawnText>) {
+    for action in actions {
+        if let WorldAction::DrawText { name, text } = action {
+            drawn_text.insert(name, text);
+        }
+    }
+}
+
+fn draw_intro_text(frame: &mut RgbaImage, font: &LoadedFont, text: &str, centre: Vec2, view: View) {
+    let size = font.measure(text);
+    let top_left = centre - Vec2::new(size.width, size.height) / 2.0;
+    let area = AABB::new(
+        top_left.x,
+        top_left.y,
+        top_left.x + size.width,
+        top_left.y + size.height,
+    );
+    if let Some(outline) = font.rasterise(text, Colour::black(), 1.0) {
+        let outline_offsets = [
+            Vec2::new(-INTRO_TEXT_OUTLINE, 0.0),
+            Vec2::new(0.0, -INTRO_TEXT_OUTLINE),
+            Vec2::new(INTRO_TEXT_OUTLINE, 0.0),
+            Vec2::new(0.0, INTRO_TEXT_OUTLINE),
+        ];
+        for outline_offset in outline_offsets.iter() {
+            let transform = SpriteTransform::new(area.move_position(*outline_offset));
+            draw_image(frame, &outline, transform, view);
+        }
+    }
+    if let Some(main) = font.rasterise(text, Colour::white(), 1.0) {
+        draw_image(frame, &main, SpriteTransform::new(area), view);
+    }
+}
+
+fn draw_image(frame: &mut RgbaImage, image: &RgbaImage, transform: SpriteTransform, view: View) {
+    let whole = ImageRect::new(0, 0, image.width(), image.height());
+    draw_image_region(frame, image, whole, transform, view);
+}
+
+fn draw_image_region(
+    frame: &mut RgbaImage,
+    image: &RgbaImage,
+    region: ImageRect,
+    transform: SpriteTransform,
+    view: View,
+) {
+    if region.width == 0 || region.height == 0 {
+        return;
+    }
+    fill(frame, transform, view, |uv| {
+        let x = region.x + ((uv.x * region.width as f32) as u32).min(region.width - 1);
+        let y = region.y + ((uv.y * region.height as f32) as u32).min(region.height - 1);
+        let [r, g, b, a] = image.get_pixel(x, y).0;
+        Colour::rgba(
+            r as f32 / 255.0,
+            g as f32 / 255.0,
+            b as f32 / 255.0,
+            a as f32 / 255.0,
+        )
+    });
+}
+
+// Blends in the colour for every pixel whose centre is inside the transformed sprite
+fn fill(
+    frame: &mut RgbaImage,
+    transform: SpriteTransform,
+    view: View,
+    colour_at: impl Fn(Vec2) -> Colour,
+) {
+    let mapping = match transform.texture_mapping() {
+        Some(mapping) => mapping,
+        None => return,
+    };
+    let mut min = Vec2::new(f32::INFINITY, f32::INFINITY);
+    let mut max = Vec2::new(f32::NEG_INFINITY, f32::NEG_INFINITY);
+    for corner in transform.corners().iter() {
+        let corner = (*corner + view.offset) * view.scale;
+        min = Vec2::new(min.x.min(corner.x), min.y.min(corner.y));
+        max = Vec2::new(max.x.max(corner.x), max.y.max(corner.y));
+    }
+    let start_x = min.x.floor().max(0.0) as u32;
+    let start_y = min.y.floor().max(0.0) as u32;
+    let end_x = max.x.ceil().min(frame.width() as f32).max(0.0) as u32;
+    let end_y = max.y.ceil().min(frame.height() as f32).max(0.0) as u32;
+
+    for y in start_y..end_y {
+        for x in start_x..end_x {
+            let point = Vec2::new(x as f32 + 0.5, y as f32 + 0.5) / view.scale - view.offset;
+            if let Some(uv) = mapping.texture_coordinates(point) {
+                blend(frame.get_pixel_mut(x, y), colour_at(uv));
+            }
+        }
+    }
+}
+
+// Colours are mixed between the corners for every pixel whose centre is inside
+fn fill_triangle(frame: &mut RgbaImage, triangle: &[ShapeVertex], view: View) {
+    let [a, b, c] = [0, 1, 2].map(|i| (triangle[i].position + view.offset) * view.scale);
+    let area = (b - a).x * (c - a).y - (b - a).y * (c - a).x;
+    if area == 0.0 {
+        return;
+    }
+    let min_x = a.x.min(b.x).min(c.x).floor().max(0.0) as u32;
+    let min_y = a.y.min(b.y).min(c.y).floor().max(0.0) as u32;
+    let max_x =
+        a.x.max(b.x)
+            .max(c.x)
+            .ceil()
+            .min(frame.width() as f32)
+            .max(0.0) as u32;
+    let max_y =
+        a.y.max(b.y)
+            .max(c.y)
+            .ceil()
+            .min(frame.height() as f32)
+            .max(0.0) as u32;
+    let edge = |from: Vec2, to: Vec2, point: Vec2| {
+        ((to - from).x * (point - from).y - (to - from).y * (point - from).x) / area
+    };
+
+    for y in min_y..max_y {
+        for x in min_x..max_x {
+            let point = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+            let weights = [edge(b, c, point), edge(c, a, point), edge(a, b, point)];
+            if weights.iter().any(|weight| *weight < 0.0) {
+                continue;
+            }
+            let mix = |channel: fn(&Colour) -> f32| {
+                (0..3)
+                    .map(|i| channel(&triangle[i].colour) * weights[i])
+                    .sum()
+            };
+            let colour = Colour::rgba(
+                mix(|colour| colour.r),
+                mix(|colour| colour.g),
+                mix(|colour| colour.b),
+                mix(|colour| colour.a),
+            );
+            blend(frame.get_pixel_mut(x, y), colour);
+        }
+    }
+}
+
+fn blend(pixel: &mut Rgba<u8>, colour: Colour) {
+    let alpha = colour.a.clamp(0.0, 1.0);
+    if alpha == 0.0 {
+        return;
+    }
+    let below = pixel.0;
+    let mix = |above: f32, below: u8| {
+        ((above * alpha + below as f32 / 255.0 * (1.0 - alpha)) * 255.0).round() as u8
+    };
+    let below_alpha = below[3] as f32 / 255.0;
+    *pixel = Rgba([
+        mix(colour.r, below[0]),
+        mix(colour.g, below[1]),
+        mix(colour.b, below[2]),
+        ((alpha + below_alpha * (1.0 - alpha)) * 255.0).round() as u8,
+    ]);
+}
+
+fn to_rgba(colour: Colour) -> Rgba<u8> {
+    let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+    Rgba([
+        channel(colour.r),
+        channel(colour.g),
+        channel(colour.b),
+        channel(colour.a),
+    ])
+}
@@ -0,0 +1,192 @@
+// Saves a frame of a game as a PNG without opening a window.
+//
+// cargo run --release -- games/yeah/bird.json --frame 30 --output bird.png
+//
+// Export a saved run as a GIF, or as numbered PNGs if the output isn't a .gif:
+// cargo run --release -- --export ../wee/saved-runs/bird-won.savedrun --output bird.gif --cursor
+//
+// Rebless the golden images after an expected change with:
+// cargo run --release -- --bless-goldens
+
+use std::{collections::HashMap, env, fs, path::PathBuf, process};
+use wee::*;
+use wee_common::WeeResult;
+use wee_render::export::{self, ExportSettings};
+use wee_render::{add_drawn_text, golden, load_saved_run, save_png, SeededRng, SoftwareRenderer};
+
+struct Options {
+    root: PathBuf,
+    path: String,
+    frame: u32,
+    seed: u64,
+    difficulty: u32,
+    output: PathBuf,
+    intro_font: PathBuf,
+    language: String,
+    bless_goldens: bool,
+    export: Option<PathBuf>,
+    scale: Option<f32>,
+    export_settings: ExportSettings,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            root: PathBuf::from("../main-game"),
+            path: String::new(),
+            frame: 0,
+            seed: 0,
+            difficulty: 1,
+            output: PathBuf::from("frame.png"),
+            intro_font: PathBuf::from("fonts/Roboto-Medium.ttf"),
+            language: DEFAULT_LANGUAGE.to_string(),
+            bless_goldens: false,
+            export: None,
+            scale: None,
+            export_settings: ExportSettings::default(),
+        }
+    }
+}
+
+const USAGE: &str = "Usage: wee-render [OPTIONS] <GAME>
+       wee-render --export <SAVED_RUN> [OPTIONS]
+       wee-render --bless-goldens
+
+The game is a file relative to --root
+
+Options:
+    --root <DIR>          Directory the game path is relative to (default: ../main-game)
+    --frame <FRAME>       Number of frames to run before rendering (default: 0)
+    --seed <SEED>         Seed for the game's random numbers (default: 0)
+    --difficulty <LEVEL>  Difficulty between 1 and 3 (default: 1)
+    --output <FILE>       PNG file to save the frame to (default: frame.png)
+    --scale <SCALE>       Size of the output compared to 1600x900 (default: 1, or 0.5 when exporting)
+    --intro-font <FILE>   Font for the intro text relative to --root (default: fonts/Roboto-Medium.ttf)
+    --language <LANGUAGE> Language to translate the game's text into (default: en)
+    --export <SAVED_RUN>  Replay a saved run and save it to --output as a .gif or a directory of PNGs
+    --frame-skip <FRAMES> Frames left out after each exported frame (default: 2)
+    --cursor              Draw the mouse cursor over exported frames
+    --bless-goldens       Replace the golden images in goldens with frames from ../wee/saved-runs";
+
+const SAVED_RUNS: &str = "../wee/saved-runs";
+const GOLDENS: &str = "goldens";
+
+impl Options {
+    fn from_args() -> WeeResult<Options> {
+        let mut options = Options::default();
+        let mut args = env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("Missing value for {}", arg))
+            };
+            match arg.as_str() {
+                "--root" => options.root = PathBuf::from(value()?),
+                "--frame" => options.frame = value()?.parse()?,
+                "--seed" => options.seed = value()?.parse()?,
+                "--difficulty" => {
+                    let level = value()?.parse()?;
+                    if !(1..=3).contains(&level) {
+                        return Err(format!("Difficulty {} is not between 1 and 3", level).into());
+                    }
+                    options.difficulty = level;
+                }
+                "--output" => options.output = PathBuf::from(value()?),
+                "--intro-font" => options.intro_font = PathBuf::from(value()?),
+                "--language" => options.language = value()?,
+                "--bless-goldens" => options.bless_goldens = true,
+                "--export" => options.export = Some(PathBuf::from(value()?)),
+                "--scale" => {
+                    let scale: f32 = value()?.parse()?;
+                    if scale <= 0.0 {
+                        return Err(format!("Scale {} must be above 0", scale).into());
+                    }
+                    options.scale = Some(scale);
+                }
+                "--frame-skip" => options.export_settings.frame_skip = value()?.parse()?,
+                "--cursor" => options.export_settings.show_cursor = true,
+                "--help" | "-h" => {
+                    println!("{}", USAGE);
+                    process::exit(0);
+                }
+                _ if arg.starts_with("--") => {
+                    return Err(format!("Unknown option {}\n\n{}", arg, USAGE).into())
+                }
+                _ => options.path = arg,
+            }
+        }
+        if options.path.is_empty() && !options.bless_goldens && options.export.is_none() {
+            return Err(format!("Missing the game to render\n\n{}", USAGE).into());
+        }
+        Ok(options)
+    }
+}
+
+fn render(options: &Options) -> WeeResult<()> {
+    let path = options.root.join(&options.path);
+    let mut data: GameData = serde_json::from_str(&fs::read_to_string(&path)?)?;
+    let base_path = path.parent().ok_or("Could not get game directory")?;
+    data.localise(&Translations::load(base_path, &options.language)?);
+
+    let masks = CollisionMasks::load(&data, base_path)?;
+    let mut renderer = SoftwareRenderer::load(&data, base_path)?;
+    renderer.load_intro_font(options.root.join(&options.intro_font))?;
+
+    let mut rng = SeededRng::new(options.seed);
+    let mut game = Game::from_data(data, &mut rng)?;
+    game.difficulty = options.difficulty;
+    game.set_collision_masks(masks);
+
+    let mut drawn_text = HashMap::new();
+    for _ in 0..options.frame {
+        let actions = game.update_frame(Mouse::default(), &mut rng)?;
+        add_drawn_text(actions, &mut drawn_text);
+    }
+
+    let frame = renderer.render_scaled(&game, &drawn_text, options.scale.unwrap_or(1.0));
+    save_png(&frame, &options.output)?;
+    println!("Saved frame {} to {:?}", game.frames.ran, options.output);
+    Ok(())
+}
+
+fn export_saved_run(options: &Options, saved_run: &PathBuf) -> WeeResult<()> {
+    let mut settings = options.export_settings;
+    if let Some(scale) = options.scale {
+        settings.scale = scale;
+    }
+    let saved_run = load_saved_run(saved_run)?;
+    let intro_font = options.root.join(&options.intro_font);
+    let frames = export::export(
+        &saved_run,
+        &options.root,
+        intro_font,
+        &options.language,
+        &options.output,
+        settings,
+    )?;
+    println!("Exported {} frames to {:?}", frames, options.output);
+    Ok(())
+}
+
+fn bless_goldens(options: &Options) -> WeeResult<()> {
+    let intro_font = options.root.join(&options.intro_font);
+    let written = golden::bless(SAVED_RUNS, GOLDENS, &options.root, intro_font)?;
+    println!("Saved {} golden images to {}", written, GOLDENS);
+    Ok(())
+}
+
+fn main() {
+    let result = Options::from_args().and_then(|options| {
+        if options.bless_goldens {
+            bless_goldens(&options)
+        } else if let Some(saved_run) = &options.export {
+            export_saved_run(&options, saved_run)
+        } else {
+            render(&options)
+        }
+    });
+    if let Err(error) = result {
+        eprintln!("{}", error);
+        process::exit(1);
+    }
+}
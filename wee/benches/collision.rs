@@ -0,0 +1,95 @@
+use bracket_random::prelude::*;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::collections::HashSet;
+use wee::*;
+use wee_common::{Size, Vec2, AABB};
+
+struct BenchRng(RandomNumberGenerator);
+
+impl WeeRng for BenchRng {
+    fn random_in_range(&mut self, min: f32, max: f32) -> f32 {
+        self.0.range(min, max)
+    }
+
+    fn random_in_range_u32(&mut self, min: u32, max: u32) -> u32 {
+        self.0.range(min, max)
+    }
+
+    fn random_in_slice<'a, T>(&mut self, slice: &'a [T]) -> Option<&'a T> {
+        self.0.random_slice_entry(slice)
+    }
+
+    fn coin_flip(&mut self) -> bool {
+        self.0.roll_dice(1, 2) == 1
+    }
+}
+
+fn object_name(index: usize) -> String {
+    format!("Ball {}", index)
+}
+
+fn colliding_game(count: usize) -> GameData {
+    let area = AABB::new(0.0, 0.0, 1600.0, 900.0);
+    let objects = (0..count)
+        .map(|i| {
+            let collisions = (1..=4)
+                .map(|offset| Instruction {
+                    triggers: vec![Trigger::Collision(
+                        CollisionWith::Object {
+                            name: object_name((i + offset * 7) % count),
+                        },
+                        CollisionPhase::Stay,
+                    )],
+                    actions: vec![Action::SetProperty(PropertySetter::Switch(Switch::On))],
+                })
+                .collect::<Vec<_>>();
+            let mut instructions = vec![Instruction {
+                triggers: vec![Trigger::Time(When::Start)],
+                actions: vec![Action::Motion(Motion::Roam {
+                    movement_type: MovementType::Reflect {
+                        initial_direction: MovementDirection::Direction {
+                            possible_directions: HashSet::new(),
+                        },
+                        movement_handling: if i % 4 == 0 {
+                            MovementHandling::TryNotToOverlap
+                        } else {
+                            MovementHandling::Anywhere
+                        },
+                    },
+                    area,
+                    speed: Speed::Normal,
+                })],
+            }];
+            instructions.extend(collisions);
+            SerialiseObject {
+                name: object_name(i),
+                position: Vec2::new((i * 37 % 1600) as f32, (i * 53 % 900) as f32),
+                size: Size::new(50.0, 50.0),
+                angle: (i * 15 % 360) as f32,
+                instructions,
+                ..Default::default()
+            }
+        })
+        .collect();
+
+    GameData {
+        objects,
+        length: Length::Infinite,
+        ..Default::default()
+    }
+}
+
+fn collision(c: &mut Criterion) {
+    let mut group = c.benchmark_group("collision");
+    for count in [100, 300].iter() {
+        let mut rng = BenchRng(RandomNumberGenerator::seeded(1));
+        let mut game = Game::from_data(colliding_game(*count), &mut rng).unwrap();
+        group.bench_with_input(BenchmarkId::from_parameter(count), count, |b, _| {
+            b.iter(|| game.update_frame(Mouse::default(), &mut rng).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, collision);
+criterion_main!(benches);
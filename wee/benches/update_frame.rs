@@ -0,0 +1,121 @@
+use bracket_random::prelude::*;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::collections::HashSet;
+use wee::*;
+use wee_common::{Vec2, AABB};
+
+struct BenchRng(RandomNumberGenerator);
+
+impl WeeRng for BenchRng {
+    fn random_in_range(&mut self, min: f32, max: f32) -> f32 {
+        self.0.range(min, max)
+    }
+
+    fn random_in_range_u32(&mut self, min: u32, max: u32) -> u32 {
+        self.0.range(min, max)
+    }
+
+    fn random_in_slice<'a, T>(&mut self, slice: &'a [T]) -> Option<&'a T> {
+        self.0.random_slice_entry(slice)
+    }
+
+    fn coin_flip(&mut self) -> bool {
+        self.0.roll_dice(1, 2) == 1
+    }
+}
+
+fn object_name(index: usize) -> String {
+    format!("Object {}", index)
+}
+
+fn crowd_game(count: usize) -> GameData {
+    let area = AABB::new(0.0, 0.0, 1600.0, 900.0);
+    let objects = (0..count)
+        .map(|i| {
+            let next = object_name((i + 1) % count);
+            let previous = object_name((i + count - 1) % count);
+            let motion = if i % 2 == 0 {
+                Motion::Roam {
+                    movement_type: MovementType::Insect,
+                    area,
+                    speed: Speed::Normal,
+                }
+            } else {
+                Motion::Target {
+                    target: Target::Object { name: next.clone() },
+                    target_type: TargetType::Follow,
+                    offset: Vec2::zero(),
+                    speed: Speed::Slow,
+                }
+            };
+            SerialiseObject {
+                name: object_name(i),
+                position: Vec2::new((i * 37 % 1600) as f32, (i * 53 % 900) as f32),
+                size: wee_common::Size::new(40.0, 40.0),
+                instructions: vec![
+                    Instruction {
+                        triggers: vec![Trigger::Time(When::Start)],
+                        actions: vec![Action::Motion(motion)],
+                    },
+                    Instruction {
+                        triggers: vec![Trigger::Collision(
+                            CollisionWith::Object { name: next.clone() },
+                            CollisionPhase::Stay,
+                        )],
+                        actions: vec![Action::SetProperty(PropertySetter::Switch(Switch::On))],
+                    },
+                    Instruction {
+                        triggers: vec![
+                            Trigger::CheckProperty {
+                                name: previous.clone(),
+                                check: PropertyCheck::Switch(SwitchState::On),
+                            },
+                            Trigger::Input(Input::Mouse {
+                                over: MouseOver::Object { name: next },
+                                interaction: MouseInteraction::Hover,
+                            }),
+                        ],
+                        actions: vec![Action::SetProperty(PropertySetter::Switch(Switch::Off))],
+                    },
+                    Instruction {
+                        triggers: Vec::new(),
+                        actions: vec![Action::SetProperty(PropertySetter::Angle(
+                            AngleSetter::RotateToObject { name: previous },
+                        ))],
+                    },
+                    Instruction {
+                        triggers: vec![Trigger::Random { chance: 0.01 }],
+                        actions: vec![Action::Motion(Motion::GoStraight {
+                            direction: MovementDirection::Direction {
+                                possible_directions: HashSet::new(),
+                            },
+                            speed: Speed::Fast,
+                        })],
+                    },
+                ],
+                ..Default::default()
+            }
+        })
+        .collect();
+
+    GameData {
+        objects,
+        length: Length::Infinite,
+        ..Default::default()
+    }
+}
+
+fn update_frame(c: &mut Criterion) {
+    let mut group = c.benchmark_group("update_frame");
+    for count in [50, 200, 500].iter() {
+        let mut rng = BenchRng(RandomNumberGenerator::seeded(1));
+        let mut game = Game::from_data(crowd_game(*count), &mut rng).unwrap();
+        group.bench_with_input(BenchmarkId::from_parameter(count), count, |b, _| {
+            b.iter(|| game.update_frame(Mouse::default(), &mut rng).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, update_frame);
+criterion_main!(benches);
@@ -0,0 +1,102 @@
+use crate::ImageRect;
+use std::collections::HashMap;
+
+// Space left around each image so neighbours don't bleed in when sampling at the edges
+pub const ATLAS_PADDING: u32 = 1;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AtlasPlacement {
+    pub page: usize,
+    pub rect: ImageRect,
+}
+
+// Where images go when they're packed into as few textures as possible
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AtlasLayout {
+    // The width and height each page needs to be
+    pub pages: Vec<(u32, u32)>,
+    pub placements: HashMap<String, AtlasPlacement>,
+}
+
+struct Shelf {
+    page: usize,
+    y: u32,
+    height: u32,
+    width_used: u32,
+}
+
+impl AtlasLayout {
+    // Fills rows of images from the tallest down, starting a new page when one is full.
+    // Images too big for a page are left out so the frontend can load them on their own
+    pub fn pack<'a>(
+        sizes: impl IntoIterator<Item = (&'a str, u32, u32)>,
+        page_size: u32,
+    ) -> AtlasLayout {
+        let mut sizes: Vec<_> = sizes
+            .into_iter()
+            .filter(|(_, width, height)| {
+                *width > 0
+                    && *height > 0
+                    && width + ATLAS_PADDING * 2 <= page_size
+                    && height + ATLAS_PADDING * 2 <= page_size
+            })
+            .collect();
+        sizes.sort_by(|a, b| b.2.cmp(&a.2).then(b.1.cmp(&a.1)).then(a.0.cmp(b.0)));
+
+        let mut layout = AtlasLayout::default();
+        let mut shelves: Vec<Shelf> = Vec::new();
+        for (name, width, height) in sizes {
+            let padded_width = width + ATLAS_PADDING * 2;
+            let padded_height = height + ATLAS_PADDING * 2;
+
+            let shelf_index = shelves.iter().position(|shelf| {
+                shelf.height >= padded_height && shelf.width_used + padded_width <= page_size
+            });
+            let shelf_index = match shelf_index {
+                Some(index) => index,
+                None => {
+                    let next_y = shelves
+                        .last()
+                        .filter(|shelf| shelf.page + 1 == layout.pages.len())
+                        .map(|shelf| shelf.y + shelf.height);
+                    let (page, y) = match next_y {
+                        Some(y) if y + padded_height <= page_size => (layout.pages.len() - 1, y),
+                        _ => {
+                            layout.pages.push((0, 0));
+                            (layout.pages.len() - 1, 0)
+                        }
+                    };
+                    shelves.push(Shelf {
+                        page,
+                        y,
+                        height: padded_height,
+                        width_used: 0,
+                    });
+                    shelves.len() - 1
+                }
+            };
+
+            let shelf = &mut shelves[shelf_index];
+            let rect = ImageRect::new(
+                shelf.width_used + ATLAS_PADDING,
+                shelf.y + ATLAS_PADDING,
+                width,
+                height,
+            );
+            shelf.width_used += padded_width;
+
+            let page_extent = &mut layout.pages[shelf.page];
+            page_extent.0 = page_extent.0.max(shelf.width_used);
+            page_extent.1 = page_extent.1.max(shelf.y + padded_height);
+
+            layout.placements.insert(
+                name.to_string(),
+                AtlasPlacement {
+                    page: shelf.page,
+                    rect,
+                },
+            );
+        }
+        layout
+    }
+}
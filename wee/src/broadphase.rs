@@ -0,0 +1,210 @@
+use crate::{Objects, WorldShape};
+use std::collections::HashMap;
+use wee_common::{Vec2, AABB};
+
+const CELL_SIZE: f32 = 128.0;
+const MAX_CELLS_PER_OBJECT: i32 = 256;
+const MAX_CELLS: usize = 4096;
+// c2 can report shapes a tiny distance apart as touching so bounds are padded before comparing
+const BOUNDS_MARGIN: f32 = 1.0;
+
+#[derive(Debug, Copy, Clone)]
+struct CachedShape {
+    shape: WorldShape,
+    bounds: AABB,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct CellSpan {
+    min: (i32, i32),
+    max: (i32, i32),
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct CollisionCache {
+    shapes: Vec<CachedShape>,
+    grid_built: bool,
+    cells: HashMap<(i32, i32), Vec<usize>>,
+    spans: Vec<Option<CellSpan>>,
+    oversized: Vec<usize>,
+    candidates: Vec<usize>,
+}
+
+impl CollisionCache {
+    pub(crate) fn rebuild(&mut self, objects: &Objects) {
+        self.shapes.clear();
+        self.shapes.extend(
+            objects
+                .values()
+                .map(|object| CachedShape::new(object.shape())),
+        );
+        self.grid_built = false;
+    }
+
+    pub(crate) fn update(&mut self, objects: &Objects, index: usize) {
+        if index >= self.shapes.len() {
+            return;
+        }
+        self.shapes[index] = CachedShape::new(objects[index].shape());
+        if self.grid_built {
+            self.remove_from_grid(index);
+            self.insert_into_grid(index);
+        }
+    }
+
+    pub(crate) fn shape(&self, objects: &Objects, index: usize) -> WorldShape {
+        match self.shapes.get(index) {
+            Some(shape) => shape.shape,
+            None => objects[index].shape(),
+        }
+    }
+
+    pub(crate) fn might_collide(&self, a: usize, b: usize) -> bool {
+        match (self.shapes.get(a), self.shapes.get(b)) {
+            (Some(a), Some(b)) => bounds_overlap(a.bounds, b.bounds),
+            _ => true,
+        }
+    }
+
+    pub(crate) fn might_collide_with_area(&self, index: usize, area: AABB) -> bool {
+        match self.shapes.get(index) {
+            Some(shape) => bounds_overlap(shape.bounds, area),
+            None => true,
+        }
+    }
+
+    pub(crate) fn might_contain(&self, index: usize, point: Vec2) -> bool {
+        self.might_collide_with_area(
+            index,
+            AABB {
+                min: point,
+                max: point,
+            },
+        )
+    }
+
+    pub(crate) fn find_nearby(&mut self, bounds: AABB) {
+        if !self.grid_built {
+            self.build_grid();
+        }
+
+        self.candidates.clear();
+        match CellSpan::new(bounds) {
+            Some(span) => {
+                for x in span.min.0..=span.max.0 {
+                    for y in span.min.1..=span.max.1 {
+                        if let Some(cell) = self.cells.get(&(x, y)) {
+                            self.candidates.extend(cell);
+                        }
+                    }
+                }
+                self.candidates.extend(&self.oversized);
+                self.candidates.sort_unstable();
+                self.candidates.dedup();
+            }
+            None => self.candidates.extend(0..self.shapes.len()),
+        }
+    }
+
+    pub(crate) fn nearby(&self) -> &[usize] {
+        &self.candidates
+    }
+
+    fn build_grid(&mut self) {
+        if self.cells.len() > MAX_CELLS {
+            self.cells.clear();
+        }
+        for cell in self.cells.values_mut() {
+            cell.clear();
+        }
+        self.oversized.clear();
+        self.spans.clear();
+        self.spans.resize(self.shapes.len(), None);
+        for index in 0..self.shapes.len() {
+            self.insert_into_grid(index);
+        }
+        self.grid_built = true;
+    }
+
+    fn insert_into_grid(&mut self, index: usize) {
+        let span = CellSpan::new(self.shapes[index].bounds);
+        match span {
+            Some(span) => {
+                for x in span.min.0..=span.max.0 {
+                    for y in span.min.1..=span.max.1 {
+                        self.cells.entry((x, y)).or_default().push(index);
+                    }
+                }
+            }
+            None => self.oversized.push(index),
+        }
+        self.spans[index] = span;
+    }
+
+    fn remove_from_grid(&mut self, index: usize) {
+        match self.spans[index] {
+            Some(span) => {
+                for x in span.min.0..=span.max.0 {
+                    for y in span.min.1..=span.max.1 {
+                        if let Some(cell) = self.cells.get_mut(&(x, y)) {
+                            cell.retain(|&other| other != index);
+                        }
+                    }
+                }
+            }
+            None => self.oversized.retain(|&other| other != index),
+        }
+    }
+}
+
+impl CachedShape {
+    fn new(shape: WorldShape) -> CachedShape {
+        CachedShape {
+            shape,
+            bounds: shape_bounds(&shape),
+        }
+    }
+}
+
+impl CellSpan {
+    fn new(bounds: AABB) -> Option<CellSpan> {
+        if !is_finite(bounds) {
+            return None;
+        }
+        let cell = |v: f32| (v / CELL_SIZE).floor() as i32;
+        let span = CellSpan {
+            min: (cell(bounds.min.x), cell(bounds.min.y)),
+            max: (cell(bounds.max.x), cell(bounds.max.y)),
+        };
+        let columns = span.max.0.saturating_sub(span.min.0).saturating_add(1);
+        let rows = span.max.1.saturating_sub(span.min.1).saturating_add(1);
+        if columns.saturating_mul(rows) > MAX_CELLS_PER_OBJECT {
+            None
+        } else {
+            Some(span)
+        }
+    }
+}
+
+pub(crate) fn shape_bounds(shape: &WorldShape) -> AABB {
+    let mut bounds = shape.bounds();
+    bounds.min.x -= BOUNDS_MARGIN;
+    bounds.min.y -= BOUNDS_MARGIN;
+    bounds.max.x += BOUNDS_MARGIN;
+    bounds.max.y += BOUNDS_MARGIN;
+    bounds
+}
+
+fn is_finite(bounds: AABB) -> bool {
+    bounds.min.x.is_finite()
+        && bounds.min.y.is_finite()
+        && bounds.max.x.is_finite()
+        && bounds.max.y.is_finite()
+}
+
+fn bounds_overlap(a: AABB, b: AABB) -> bool {
+    if !is_finite(a) || !is_finite(b) {
+        return true;
+    }
+    a.min.x <= b.max.x && a.max.x >= b.min.x && a.min.y <= b.max.y && a.max.y >= b.min.y
+}
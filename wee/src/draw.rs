@@ -0,0 +1,302 @@
+use crate::{DrawnText, FontMetrics, Game, ImageRect, Object, ShapeVertex, SliceInsets, Sprite};
+use std::collections::HashMap;
+use wee_common::{Colour, Flip, Size, Vec2, AABB, PROJECTION_HEIGHT, PROJECTION_WIDTH};
+
+pub const INTRO_TEXT_TIME: u32 = 60;
+
+// Where a sprite ends up on screen, rotated by angle degrees around the pivot
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SpriteTransform {
+    pub area: AABB,
+    pub angle: f32,
+    pub pivot: Vec2,
+    pub flip: Flip,
+}
+
+impl SpriteTransform {
+    pub fn new(area: AABB) -> SpriteTransform {
+        SpriteTransform {
+            area,
+            angle: 0.0,
+            pivot: area.min + Vec2::new(area.width(), area.height()) / 2.0,
+            flip: Flip::default(),
+        }
+    }
+
+    fn from_object(object: &Object, size: Size, top_left: Vec2) -> SpriteTransform {
+        SpriteTransform {
+            area: AABB::new(
+                top_left.x,
+                top_left.y,
+                top_left.x + size.width,
+                top_left.y + size.height,
+            ),
+            angle: object.angle,
+            pivot: object.origin_in_world(),
+            flip: object.flip,
+        }
+    }
+
+    // The top left, top right, bottom right and bottom left corners of the image on screen
+    pub fn corners(&self) -> [Vec2; 4] {
+        let AABB { min, max } = self.area;
+        let mut corners = [min, Vec2::new(max.x, min.y), max, Vec2::new(min.x, max.y)];
+        if self.flip.horizontal {
+            corners.swap(0, 1);
+            corners.swap(2, 3);
+        }
+        if self.flip.vertical {
+            corners.swap(0, 3);
+            corners.swap(1, 2);
+        }
+        let (sin, cos) = self.angle.to_radians().sin_cos();
+        for corner in corners.iter_mut() {
+            let offset = *corner - self.pivot;
+            *corner = self.pivot
+                + Vec2::new(
+                    offset.x * cos - offset.y * sin,
+                    offset.x * sin + offset.y * cos,
+                );
+        }
+        corners
+    }
+
+    // Each piece of a nine-slice sprite with the part of the image drawn there.
+    // Flipping mirrors where the pieces go as well as each piece's image
+    pub fn nine_slice(
+        &self,
+        rect: ImageRect,
+        insets: SliceInsets,
+    ) -> impl Iterator<Item = (ImageRect, SpriteTransform)> {
+        let transform = *self;
+        let AABB { min, max } = self.area;
+        IntoIterator::into_iter(insets.pieces(rect, self.area))
+            .filter(|(region, area)| {
+                region.width > 0 && region.height > 0 && area.width() > 0.0 && area.height() > 0.0
+            })
+            .map(move |(region, mut area)| {
+                if transform.flip.horizontal {
+                    area = AABB::new(
+                        min.x + max.x - area.max.x,
+                        area.min.y,
+                        min.x + max.x - area.min.x,
+                        area.max.y,
+                    );
+                }
+                if transform.flip.vertical {
+                    area = AABB::new(
+                        area.min.x,
+                        min.y + max.y - area.max.y,
+                        area.max.x,
+                        min.y + max.y - area.min.y,
+                    );
+                }
+                (region, SpriteTransform { area, ..transform })
+            })
+    }
+
+    // Where a point measured in pixels from the unflipped top left of the area ends up on screen
+    pub fn to_screen(&self, point: Vec2) -> Vec2 {
+        let [top_left, top_right, _, bottom_left] = self.corners();
+        let fraction = |length: f32, size: f32| if size == 0.0 { 0.0 } else { length / size };
+        top_left
+            + (top_right - top_left) * fraction(point.x, self.area.width())
+            + (bottom_left - top_left) * fraction(point.y, self.area.height())
+    }
+
+    // Where a point on screen falls in the image, from (0, 0) at the top left to (1, 1)
+    pub fn texture_coordinates(&self, point: Vec2) -> Option<Vec2> {
+        self.texture_mapping()
+            .and_then(|mapping| mapping.texture_coordinates(point))
+    }
+
+    // Lets many points be mapped without working out the corners each time
+    pub fn texture_mapping(&self) -> Option<TextureMapping> {
+        let [top_left, top_right, _, bottom_left] = self.corners();
+        let across = top_right - top_left;
+        let down = bottom_left - top_left;
+        let determinant = across.x * down.y - across.y * down.x;
+        if determinant == 0.0 {
+            None
+        } else {
+            Some(TextureMapping {
+                top_left,
+                across: across / determinant,
+                down: down / determinant,
+            })
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TextureMapping {
+    top_left: Vec2,
+    across: Vec2,
+    down: Vec2,
+}
+
+impl TextureMapping {
+    pub fn texture_coordinates(&self, point: Vec2) -> Option<Vec2> {
+        let offset = point - self.top_left;
+        let u = offset.x * self.down.y - offset.y * self.down.x;
+        let v = self.across.x * offset.y - self.across.y * offset.x;
+        if (0.0..=1.0).contains(&u) && (0.0..=1.0).contains(&v) {
+            Some(Vec2::new(u, v))
+        } else {
+            None
+        }
+    }
+}
+
+// Everything needed to draw a frame, from back to front
+#[derive(Debug, Clone, PartialEq)]
+pub enum DrawCommand {
+    // The region is the part of the image to draw, or the whole image if it's None
+    Image {
+        name: String,
+        region: Option<ImageRect>,
+        transform: SpriteTransform,
+    },
+    Rectangle {
+        colour: Colour,
+        transform: SpriteTransform,
+    },
+    // Already placed on screen, every three vertices making a triangle
+    Triangles {
+        vertices: Vec<ShapeVertex>,
+    },
+    // One line of an object's text, with the font scaled by the given amount
+    Text {
+        object: String,
+        text: String,
+        font: String,
+        colour: Colour,
+        scale: f32,
+        transform: SpriteTransform,
+    },
+    IntroText {
+        text: String,
+        centre: Vec2,
+    },
+    Invert,
+    Cover {
+        colour: Colour,
+    },
+}
+
+fn push_sprite_commands(
+    commands: &mut Vec<DrawCommand>,
+    sprite: &Sprite,
+    transform: SpriteTransform,
+) {
+    let command = match sprite {
+        Sprite::Image { name } => DrawCommand::Image {
+            name: name.clone(),
+            region: None,
+            transform,
+        },
+        Sprite::Region { image, rect } => DrawCommand::Image {
+            name: image.clone(),
+            region: Some(*rect),
+            transform,
+        },
+        Sprite::Colour(colour) => DrawCommand::Rectangle {
+            colour: *colour,
+            transform,
+        },
+        Sprite::Shape(sprite) => DrawCommand::Triangles {
+            vertices: sprite.triangles(&transform),
+        },
+        Sprite::NineSlice {
+            image,
+            rect,
+            insets,
+        } => {
+            commands.extend(
+                transform
+                    .nine_slice(*rect, *insets)
+                    .map(|(region, transform)| DrawCommand::Image {
+                        name: image.clone(),
+                        region: Some(region),
+                        transform,
+                    }),
+            );
+            return;
+        }
+    };
+    commands.push(command);
+}
+
+impl Game {
+    // Fonts are measured by the frontend since only it knows about them
+    pub fn draw_commands<M: FontMetrics>(
+        &self,
+        drawn_text: &HashMap<String, DrawnText>,
+        font_metrics: impl Fn(&str) -> Option<M>,
+    ) -> Vec<DrawCommand> {
+        let mut commands = Vec::new();
+        for part in &self.background {
+            push_sprite_commands(&mut commands, &part.sprite, SpriteTransform::new(part.area));
+        }
+
+        for index in self.draw_order() {
+            let (name, object) = match self.objects.get_index(index) {
+                Some(entry) => entry,
+                None => continue,
+            };
+            let transform = SpriteTransform::from_object(object, object.size, object.top_left());
+            push_sprite_commands(&mut commands, &object.sprite, transform);
+
+            let text = match drawn_text.get(name) {
+                Some(text) => text,
+                None => continue,
+            };
+            let metrics = match font_metrics(&text.font) {
+                Some(metrics) => metrics,
+                None => continue,
+            };
+            let top_left = object.top_left();
+            let area = AABB::new(
+                top_left.x,
+                top_left.y,
+                top_left.x + object.size.width,
+                top_left.y + object.size.height,
+            );
+            let laid_out = text.lay_out(area, &metrics);
+            for (offset, colour) in text.layout.passes(text.colour, laid_out.scale) {
+                for line in &laid_out.lines {
+                    let size = Size::new(line.area.width(), line.area.height());
+                    commands.push(DrawCommand::Text {
+                        object: name.clone(),
+                        text: line.text.clone(),
+                        font: text.font.clone(),
+                        colour,
+                        scale: laid_out.scale,
+                        transform: SpriteTransform::from_object(
+                            object,
+                            size,
+                            line.area.min + offset,
+                        ),
+                    });
+                }
+            }
+        }
+
+        if self.frames.ran < INTRO_TEXT_TIME && !self.intro_text.is_empty() {
+            commands.push(DrawCommand::IntroText {
+                text: self.intro_text.clone(),
+                centre: Vec2::new(PROJECTION_WIDTH / 2.0, PROJECTION_HEIGHT / 2.0),
+            });
+        }
+
+        let effects = self.screen_effects();
+        if effects.invert {
+            commands.push(DrawCommand::Invert);
+        }
+        if let Some(colour) = effects.overlay {
+            commands.push(DrawCommand::Cover { colour });
+        }
+
+        commands
+    }
+}
@@ -0,0 +1,135 @@
+use crate::{Effect, Fade};
+use serde::{Deserialize, Serialize};
+use wee_common::{Colour, Vec2};
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq)]
+pub(crate) struct TimedEffect {
+    effect: Effect,
+    elapsed: u32,
+}
+
+// What the renderer should do to the screen this frame
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ScreenEffects {
+    pub offset: Vec2,
+    pub overlay: Option<Colour>,
+    pub invert: bool,
+}
+
+impl Default for ScreenEffects {
+    fn default() -> ScreenEffects {
+        ScreenEffects {
+            offset: Vec2::zero(),
+            overlay: None,
+            invert: false,
+        }
+    }
+}
+
+impl TimedEffect {
+    fn frames(&self) -> u32 {
+        match self.effect {
+            Effect::SlowMotion { frames, .. }
+            | Effect::Shake { frames, .. }
+            | Effect::Flash { frames, .. }
+            | Effect::Fade { frames, .. }
+            | Effect::Invert { frames } => frames,
+            Effect::Freeze | Effect::None => 0,
+        }
+    }
+
+    fn progress(&self) -> f32 {
+        (self.elapsed as f32 / self.frames().max(1) as f32).min(1.0)
+    }
+
+    fn is_finished(&self) -> bool {
+        match self.effect {
+            // The screen stays faded out until something else replaces it
+            Effect::Fade {
+                fade: Fade::Out, ..
+            } => false,
+            _ => self.elapsed >= self.frames(),
+        }
+    }
+}
+
+pub(crate) fn start_effect(effects: &mut Vec<TimedEffect>, effect: Effect) {
+    effects
+        .retain(|timed| std::mem::discriminant(&timed.effect) != std::mem::discriminant(&effect));
+    effects.push(TimedEffect { effect, elapsed: 0 });
+}
+
+pub(crate) fn advance_effects(effects: &mut Vec<TimedEffect>) {
+    for timed in effects.iter_mut() {
+        timed.elapsed = timed.elapsed.saturating_add(1);
+    }
+    effects.retain(|timed| !timed.is_finished());
+}
+
+// Slow motion only lets the game run on every nth frame
+pub(crate) fn is_slowed(effects: &[TimedEffect]) -> bool {
+    effects.iter().any(|timed| match timed.effect {
+        Effect::SlowMotion { every, .. } => every > 1 && timed.elapsed % every != 0,
+        _ => false,
+    })
+}
+
+pub(crate) fn screen_effects(effects: &[TimedEffect]) -> ScreenEffects {
+    let mut screen = ScreenEffects::default();
+    let mut fade = None;
+    let mut flash = None;
+    for timed in effects {
+        let progress = timed.progress();
+        match timed.effect {
+            Effect::Shake { amplitude, .. } => {
+                // Fixed frequencies so replays shake the same way
+                let elapsed = timed.elapsed as f32;
+                let strength = amplitude * (1.0 - progress);
+                screen.offset = Vec2::new((elapsed * 2.9).sin(), (elapsed * 3.7).cos()) * strength;
+            }
+            Effect::Flash { colour, .. } => {
+                flash = Some(Colour {
+                    a: colour.a * (1.0 - progress),
+                    ..colour
+                });
+            }
+            Effect::Fade {
+                fade: direction,
+                colour,
+                ..
+            } => {
+                let strength = match direction {
+                    Fade::In => 1.0 - progress,
+                    Fade::Out => progress,
+                };
+                fade = Some(Colour {
+                    a: colour.a * strength,
+                    ..colour
+                });
+            }
+            Effect::Invert { .. } => screen.invert = true,
+            Effect::SlowMotion { .. } | Effect::Freeze | Effect::None => {}
+        }
+    }
+    screen.overlay = match (fade, flash) {
+        (Some(below), Some(above)) => Some(blend(below, above)),
+        (below, above) => above.or(below),
+    };
+    screen
+}
+
+fn blend(below: Colour, above: Colour) -> Colour {
+    let a = above.a + below.a * (1.0 - above.a);
+    if a == 0.0 {
+        return Colour::rgba(0.0, 0.0, 0.0, 0.0);
+    }
+    let mix = |above_channel: f32, below_channel: f32| {
+        (above_channel * above.a + below_channel * below.a * (1.0 - above.a)) / a
+    };
+    Colour::rgba(
+        mix(above.r, below.r),
+        mix(above.g, below.g),
+        mix(above.b, below.b),
+        a,
+    )
+}
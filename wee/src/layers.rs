@@ -0,0 +1,76 @@
+use crate::{GameData, Object, Objects};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+pub type LayerSorting = BTreeMap<u8, LayerSort>;
+
+// How objects on the same layer are ordered when drawn
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub enum LayerSort {
+    #[default]
+    InsertionOrder,
+    // Objects lower down the screen are drawn in front
+    YPosition,
+    // Objects with a higher depth are drawn behind, the same as layers
+    Depth,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct DrawKey {
+    layer: u8,
+    bottom: f32,
+    depth: f32,
+}
+
+// Indices of the objects from back to front
+fn draw_order(keys: &[DrawKey], sorting: &LayerSorting) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..keys.len()).collect();
+    sort_by_draw_key(&mut order, |index| keys[index], sorting);
+    order
+}
+
+fn sort_by_draw_key(order: &mut [usize], key: impl Fn(usize) -> DrawKey, sorting: &LayerSorting) {
+    order.sort_by(|&a, &b| {
+        let (a, b) = (key(a), key(b));
+        b.layer.cmp(&a.layer).then_with(|| {
+            match sorting.get(&a.layer).copied().unwrap_or_default() {
+                LayerSort::InsertionOrder => std::cmp::Ordering::Equal,
+                LayerSort::YPosition => a.bottom.total_cmp(&b.bottom),
+                LayerSort::Depth => b.depth.total_cmp(&a.depth),
+            }
+        })
+    });
+}
+
+fn object_draw_key(object: &Object) -> DrawKey {
+    DrawKey {
+        layer: object.layer,
+        bottom: object.position.y + object.size.height / 2.0,
+        depth: object.depth,
+    }
+}
+
+pub fn objects_draw_order(objects: &Objects, sorting: &LayerSorting) -> Vec<usize> {
+    let keys: Vec<DrawKey> = objects.values().map(object_draw_key).collect();
+    draw_order(&keys, sorting)
+}
+
+// Sorts some of the objects, given in index order, the same way as the full draw order
+pub fn sort_objects(order: &mut [usize], objects: &Objects, sorting: &LayerSorting) {
+    sort_by_draw_key(order, |index| object_draw_key(&objects[index]), sorting);
+}
+
+impl GameData {
+    pub fn draw_order(&self) -> Vec<usize> {
+        let keys: Vec<DrawKey> = self
+            .objects
+            .iter()
+            .map(|object| DrawKey {
+                layer: object.layer,
+                bottom: object.position.y + object.size.height / 2.0,
+                depth: object.depth,
+            })
+            .collect();
+        draw_order(&keys, &self.layer_sorting)
+    }
+}
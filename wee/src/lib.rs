@@ -2,6 +2,18 @@
 
 //use macroquad::logging as log;
 
+mod atlas;
+mod broadphase;
+mod draw;
+mod effects;
+mod layers;
+mod localise;
+mod mask;
+mod shape;
+mod sheet;
+mod text;
+mod vector;
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -9,6 +21,7 @@ mod tests {
     //use macroquad::logging as log;
     use std::{fs, iter::FromIterator};
 
+    #[derive(Clone, Serialize, Deserialize)]
     struct TestRng(RandomNumberGenerator);
 
     impl Default for TestRng {
@@ -35,6 +48,27 @@ mod tests {
         }
     }
 
+    // Every letter, including spaces, has the same width
+    struct FixedWidthFont {
+        letter_width: f32,
+        line_height: f32,
+    }
+
+    impl FontMetrics for FixedWidthFont {
+        fn text_width(&self, text: &str) -> f32 {
+            text.chars().count() as f32 * self.letter_width
+        }
+
+        fn line_height(&self) -> f32 {
+            self.line_height
+        }
+    }
+
+    const TEST_FONT: FixedWidthFont = FixedWidthFont {
+        letter_width: 10.0,
+        line_height: 20.0,
+    };
+
     fn load_game_data(filename: impl AsRef<Path>) -> WeeResult<GameData> {
         let json_string = fs::read_to_string(&filename)?;
 
@@ -48,92 +82,1755 @@ mod tests {
         json_from_str(&json_string)
     }
 
-    fn load_test_game(filename: &str, rng: &mut impl WeeRng) -> WeeResult<Game> {
-        let game_data = load_game_data(filename)?;
-        Ok(Game::from_data(game_data, rng)?)
+    fn load_test_game(filename: &str, rng: &mut impl WeeRng) -> WeeResult<Game> {
+        let game_data = load_game_data(filename)?;
+        Game::from_data(game_data, rng)
+    }
+
+    fn play_test_game(
+        game: &mut Game,
+        inputs: &mut Vec<Mouse>,
+        rng: &mut impl WeeRng,
+    ) -> WeeResult<()> {
+        while game.frames.remaining() != FrameCount::Frames(0) {
+            let mouse = if !inputs.is_empty() {
+                inputs.remove(0)
+            } else {
+                Mouse::default()
+            };
+
+            let world_actions = game.update_frame(mouse, rng)?;
+
+            for action in &world_actions {
+                if let WorldAction::EndEarly = action {
+                    return Ok(());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Raises the object's layer each time the trigger fires, so a test can count how often it did
+    fn count_in_layer(trigger: Trigger) -> Instruction {
+        Instruction {
+            triggers: vec![trigger],
+            actions: vec![Action::SetProperty(PropertySetter::Layer(
+                LayerSetter::Increase,
+            ))],
+        }
+    }
+
+    #[test]
+    fn triggers_time_start_on_first_frame() {
+        let mut game = Game::default();
+        game.objects.insert("Simple".to_string(), Object::default());
+        assert!(Trigger::Time(When::Start)
+            .is_triggered(
+                &game,
+                0,
+                TriggerTargets::default(),
+                Mouse::default(),
+                &mut TestRng::default()
+            )
+            .unwrap());
+    }
+
+    #[test]
+    fn triggers_time_end_on_last_frame() {
+        let mut game = Game::default();
+        game.objects.insert("Simple".to_string(), Object::default());
+        game.frames.ran += DEFAULT_GAME_LENGTH_IN_FRAMES - 1;
+        assert!(Trigger::Time(When::End)
+            .is_triggered(
+                &game,
+                0,
+                TriggerTargets::default(),
+                Mouse::default(),
+                &mut TestRng::default()
+            )
+            .unwrap());
+    }
+
+    #[test]
+    fn only_triggers_time_end_on_last_frame() {
+        let mut game = Game::default();
+        game.objects.insert("Simple".to_string(), Object::default());
+        for _ in 0..DEFAULT_GAME_LENGTH_IN_FRAMES - 1 {
+            assert!(!Trigger::Time(When::End)
+                .is_triggered(
+                    &game,
+                    0,
+                    TriggerTargets::default(),
+                    Mouse::default(),
+                    &mut TestRng::default()
+                )
+                .unwrap());
+            game.frames.ran += 1;
+        }
+        assert!(Trigger::Time(When::End)
+            .is_triggered(
+                &game,
+                0,
+                TriggerTargets::default(),
+                Mouse::default(),
+                &mut TestRng::default()
+            )
+            .unwrap());
+    }
+
+    #[test]
+    fn triggers_time_end_on_first_frame_in_game_with_one_frame() {
+        let mut game = Game::default();
+        game.objects.insert("Simple".to_string(), Object::default());
+        game.frames.total = FrameCount::Frames(1);
+        assert!(Trigger::Time(When::End)
+            .is_triggered(
+                &game,
+                0,
+                TriggerTargets::default(),
+                Mouse::default(),
+                &mut TestRng::default()
+            )
+            .unwrap());
+    }
+
+    #[test]
+    fn broadphase_keeps_touching_objects_colliding() {
+        let mut game = Game::default();
+        let mut touching = Object::default();
+        touching.position.x += 100.0;
+        let mut far_away = Object::default();
+        far_away.position.x += 400.0;
+        game.objects.insert("Simple".to_string(), Object::default());
+        game.objects.insert("Touching".to_string(), touching);
+        game.objects.insert("Far Away".to_string(), far_away);
+        game.collisions.rebuild(&game.objects);
+
+        let collides_with = |other: usize| {
+            Trigger::Collision(
+                CollisionWith::Object {
+                    name: game.object_name(other).to_string(),
+                },
+                CollisionPhase::Stay,
+            )
+            .is_triggered(
+                &game,
+                0,
+                TriggerTargets {
+                    other: Some(other),
+                    ..TriggerTargets::default()
+                },
+                Mouse::default(),
+                &mut TestRng::default(),
+            )
+            .unwrap()
+        };
+        assert!(collides_with(1));
+        assert!(!collides_with(2));
+    }
+
+    #[test]
+    fn pixel_collision_uses_flipped_and_rotated_mask() {
+        let mut game = Game::default();
+        let half = Object {
+            sprite: Sprite::Image {
+                name: "Half".to_string(),
+            },
+            collision_mode: CollisionMode::Pixels,
+            ..Object::default()
+        };
+        let mut other = Object::default();
+        other.position.x += 60.0;
+        game.objects.insert("Half".to_string(), half);
+        game.objects.insert("Other".to_string(), other);
+        let mut masks = HashMap::new();
+        masks.insert(
+            "Half".to_string(),
+            AlphaMask::from_rgba(2, 1, &[0, 0, 0, 255, 0, 0, 0, 0]),
+        );
+        game.set_collision_masks(CollisionMasks::new(masks));
+
+        let collides = |game: &Game| {
+            Trigger::Collision(
+                CollisionWith::Object {
+                    name: "Other".to_string(),
+                },
+                CollisionPhase::Stay,
+            )
+            .is_triggered(
+                game,
+                0,
+                TriggerTargets {
+                    other: Some(1),
+                    ..TriggerTargets::default()
+                },
+                Mouse::default(),
+                &mut TestRng::default(),
+            )
+            .unwrap()
+        };
+        assert!(!collides(&game));
+
+        game.objects[0].flip.horizontal = true;
+        assert!(collides(&game));
+
+        game.objects[0].flip.horizontal = false;
+        game.objects[0].angle = 180.0;
+        assert!(collides(&game));
+
+        game.objects[0].collision_mode = CollisionMode::Area;
+        game.objects[0].angle = 0.0;
+        assert!(collides(&game));
+    }
+
+    #[test]
+    fn mouse_over_pixel_object_ignores_transparent_pixels() {
+        let mut game = Game::default();
+        let half = Object {
+            sprite: Sprite::Image {
+                name: "Half".to_string(),
+            },
+            collision_mode: CollisionMode::Pixels,
+            ..Object::default()
+        };
+        game.objects.insert("Half".to_string(), half);
+        let mut masks = HashMap::new();
+        masks.insert(
+            "Half".to_string(),
+            AlphaMask::from_rgba(2, 1, &[0, 0, 0, 255, 0, 0, 0, 0]),
+        );
+        game.set_collision_masks(CollisionMasks::new(masks));
+
+        let is_over = |x: f32| {
+            let mouse = Mouse {
+                position: Vec2::new(x, 450.0),
+                ..Mouse::default()
+            };
+            Trigger::Input(Input::Mouse {
+                over: MouseOver::Object {
+                    name: "Half".to_string(),
+                },
+                interaction: MouseInteraction::Hover,
+            })
+            .is_triggered(
+                &game,
+                0,
+                TriggerTargets {
+                    other: Some(0),
+                    ..TriggerTargets::default()
+                },
+                mouse,
+                &mut TestRng::default(),
+            )
+            .unwrap()
+        };
+        assert!(is_over(770.0));
+        assert!(!is_over(830.0));
+    }
+
+    #[test]
+    fn mouse_over_region_uses_its_part_of_the_mask() {
+        let mut game = Game::default();
+        let frame = Object {
+            sprite: Sprite::Region {
+                image: "Sheet".to_string(),
+                rect: ImageRect::new(2, 0, 2, 1),
+            },
+            collision_mode: CollisionMode::Pixels,
+            ..Object::default()
+        };
+        game.objects.insert("Frame".to_string(), frame);
+        let mut masks = HashMap::new();
+        masks.insert(
+            "Sheet".to_string(),
+            AlphaMask::from_rgba(
+                4,
+                1,
+                &[0, 0, 0, 255, 0, 0, 0, 255, 0, 0, 0, 0, 0, 0, 0, 255],
+            ),
+        );
+        game.set_collision_masks(CollisionMasks::new(masks));
+
+        let is_over = |x: f32| {
+            let mouse = Mouse {
+                position: Vec2::new(x, 450.0),
+                ..Mouse::default()
+            };
+            Trigger::Input(Input::Mouse {
+                over: MouseOver::Object {
+                    name: "Frame".to_string(),
+                },
+                interaction: MouseInteraction::Hover,
+            })
+            .is_triggered(
+                &game,
+                0,
+                TriggerTargets {
+                    other: Some(0),
+                    ..TriggerTargets::default()
+                },
+                mouse,
+                &mut TestRng::default(),
+            )
+            .unwrap()
+        };
+        assert!(!is_over(770.0));
+        assert!(is_over(830.0));
+    }
+
+    #[test]
+    fn atlas_packs_images_onto_pages_without_overlapping() {
+        let sizes = [
+            ("a", 60, 40),
+            ("b", 60, 30),
+            ("c", 30, 30),
+            ("d", 90, 90),
+            ("too big", 200, 10),
+        ];
+        let layout = AtlasLayout::pack(sizes.iter().copied(), 128);
+
+        assert!(!layout.placements.contains_key("too big"));
+        assert_eq!(layout.placements.len(), 4);
+        assert_eq!(layout.pages.len(), 2);
+        for (name, width, height) in sizes.iter().take(4) {
+            let placement = layout.placements[*name];
+            assert_eq!(
+                (placement.rect.width, placement.rect.height),
+                (*width, *height)
+            );
+            let (page_width, page_height) = layout.pages[placement.page];
+            assert!(placement.rect.x + placement.rect.width + ATLAS_PADDING <= page_width);
+            assert!(placement.rect.y + placement.rect.height + ATLAS_PADDING <= page_height);
+        }
+        let placements: Vec<_> = layout.placements.values().collect();
+        for (i, a) in placements.iter().enumerate() {
+            for b in placements.iter().skip(i + 1) {
+                let a_area = a.rect.to_aabb();
+                let b_area = b.rect.to_aabb();
+                let overlaps = a_area.min.x < b_area.max.x
+                    && b_area.min.x < a_area.max.x
+                    && a_area.min.y < b_area.max.y
+                    && b_area.min.y < a_area.max.y;
+                assert!(a.page != b.page || !overlaps);
+            }
+        }
+    }
+
+    #[test]
+    fn sprite_sheets_import_tags_with_frame_durations() {
+        let aseprite = r#"{
+            "frames": {
+                "bird 0.aseprite": { "frame": { "x": 0, "y": 0, "w": 16, "h": 16 }, "rotated": false, "trimmed": false, "duration": 100 },
+                "bird 1.aseprite": { "frame": { "x": 16, "y": 0, "w": 16, "h": 16 }, "rotated": false, "trimmed": false, "duration": 50 },
+                "bird 2.aseprite": { "frame": { "x": 32, "y": 0, "w": 16, "h": 16 }, "rotated": false, "trimmed": false, "duration": 100 }
+            },
+            "meta": {
+                "image": "bird.png",
+                "size": { "w": 48, "h": 16 },
+                "frameTags": [
+                    { "name": "flap", "from": 0, "to": 2, "direction": "pingpong" },
+                    { "name": "fall", "from": 1, "to": 2, "direction": "reverse" }
+                ]
+            }
+        }"#;
+        let sheet = SpriteSheet::from_json(aseprite).unwrap();
+        assert_eq!(sheet.image, "bird.png");
+        let animations = sheet.animations("bird");
+        assert_eq!(animations[0].0, "bird flap");
+        let x_positions = |animation: &FrameAnimation| -> Vec<u32> {
+            animation
+                .sprites
+                .iter()
+                .map(|sprite| match sprite {
+                    Sprite::Region { rect, .. } => rect.x,
+                    _ => panic!("Sheet frames should be regions"),
+                })
+                .collect()
+        };
+        assert_eq!(x_positions(&animations[0].1), vec![0, 16, 32]);
+        assert_eq!(animations[0].1.frame_durations, vec![6, 3, 6]);
+        assert_eq!(animations[0].1.playback, Playback::PingPong);
+        assert_eq!(animations[1].0, "bird fall");
+        assert_eq!(x_positions(&animations[1].1), vec![16, 32]);
+        assert_eq!(animations[1].1.playback, Playback::Reverse);
+
+        let texture_packer = r#"{
+            "frames": [
+                { "filename": "walk_01.png", "frame": { "x": 0, "y": 0, "w": 8, "h": 10 }, "rotated": false },
+                { "filename": "walk_02.png", "frame": { "x": 8, "y": 0, "w": 8, "h": 10 }, "rotated": false }
+            ],
+            "meta": { "image": "walk.png" }
+        }"#;
+        let animations = SpriteSheet::from_json(texture_packer)
+            .unwrap()
+            .animations("walk");
+        assert_eq!(animations.len(), 1);
+        assert_eq!(animations[0].0, "walk");
+        assert_eq!(x_positions(&animations[0].1), vec![0, 8]);
+        assert!(animations[0].1.frame_durations.is_empty());
+    }
+
+    #[test]
+    fn sprite_sheets_with_trimmed_frames_are_rejected() {
+        let sheet = |frame: &str| {
+            SpriteSheet::from_json(&format!(
+                r#"{{ "frames": {{ "walk_01.png": {} }}, "meta": {{ "image": "walk.png" }} }}"#,
+                frame
+            ))
+        };
+        let whole = r#"{ "frame": { "x": 0, "y": 0, "w": 8, "h": 10 }, "rotated": false, "trimmed": false,
+            "spriteSourceSize": { "x": 0, "y": 0, "w": 8, "h": 10 }, "sourceSize": { "w": 8, "h": 10 } }"#;
+        assert!(sheet(whole).is_ok());
+
+        let trimmed = r#"{ "frame": { "x": 0, "y": 0, "w": 6, "h": 8 }, "rotated": false, "trimmed": true,
+            "spriteSourceSize": { "x": 1, "y": 2, "w": 6, "h": 8 }, "sourceSize": { "w": 8, "h": 10 } }"#;
+        let error = sheet(trimmed).unwrap_err().to_string();
+        assert!(error.contains("walk_01.png"));
+        assert!(error.contains("trimming"));
+
+        let unflagged = r#"{ "frame": { "x": 0, "y": 0, "w": 6, "h": 8 },
+            "spriteSourceSize": { "x": 1, "y": 2, "w": 6, "h": 8 }, "sourceSize": { "w": 8, "h": 10 } }"#;
+        assert!(sheet(unflagged).is_err());
+    }
+
+    #[test]
+    fn animations_show_each_sprite_for_its_frame_duration() {
+        let sprites: Vec<Sprite> = (0..2)
+            .map(|x| Sprite::Region {
+                image: "sheet".to_string(),
+                rect: ImageRect::new(x, 0, 1, 1),
+            })
+            .collect();
+        let mut animation = AnimationStatus::start(
+            AnimationType::PlayOnce,
+            &sprites,
+            &[3, 2],
+            Speed::Normal,
+            Playback::Forward,
+            0,
+        );
+        let changes: Vec<Option<Sprite>> = (0..5).map(|_| animation.update()).collect();
+        assert_eq!(
+            changes,
+            vec![None, None, Some(sprites[1].clone()), None, None]
+        );
+        assert!(matches!(animation, AnimationStatus::Finished));
+    }
+
+    #[test]
+    fn animations_play_in_each_direction_from_the_start_frame() {
+        let sprites: Vec<Sprite> = (0..3)
+            .map(|x| Sprite::Region {
+                image: "sheet".to_string(),
+                rect: ImageRect::new(x, 0, 1, 1),
+            })
+            .collect();
+        let frames_shown = |animation_type, playback, start_frame| {
+            let mut animation = AnimationStatus::start(
+                animation_type,
+                &sprites,
+                &[1, 1, 1],
+                Speed::Normal,
+                playback,
+                start_frame,
+            );
+            let mut frames = Vec::new();
+            while let Some(frame) = animation.frame() {
+                frames.push(frame);
+                animation.update();
+                if frames.len() > 20 {
+                    break;
+                }
+            }
+            frames
+        };
+
+        assert_eq!(
+            frames_shown(AnimationType::PlayOnce, Playback::Reverse, 2),
+            vec![2, 1, 0]
+        );
+        assert_eq!(
+            frames_shown(AnimationType::PlayOnce, Playback::Forward, 1),
+            vec![1, 2]
+        );
+        assert_eq!(
+            frames_shown(AnimationType::LoopTimes(2), Playback::PingPong, 0),
+            vec![0, 1, 2, 1, 0, 1, 2, 1, 0]
+        );
+        assert_eq!(
+            frames_shown(AnimationType::LoopTimes(2), Playback::Forward, 2),
+            vec![2, 0, 1, 2]
+        );
+        assert_eq!(
+            frames_shown(AnimationType::Loop, Playback::Forward, 0).len(),
+            21
+        );
+    }
+
+    #[test]
+    fn animation_frame_checks_the_sprite_being_shown() {
+        let mut rng = TestRng::default();
+        let sprites: Vec<Sprite> = (0..3)
+            .map(|x| Sprite::Region {
+                image: "sheet".to_string(),
+                rect: ImageRect::new(x, 0, 1, 1),
+            })
+            .collect();
+        let mut game_data = GameData::default();
+        game_data.objects.push(SerialiseObject {
+            name: "Bird".to_string(),
+            sprite: Sprite::Colour(Colour::black()),
+            instructions: vec![
+                Instruction {
+                    triggers: vec![Trigger::Time(When::Start)],
+                    actions: vec![Action::Animate {
+                        animation_type: AnimationType::PlayOnce,
+                        sprites: sprites.clone(),
+                        speed: Speed::Normal,
+                        frame_durations: vec![2, 2, 2],
+                        playback: Playback::Forward,
+                        start_frame: 0,
+                    }],
+                },
+                Instruction {
+                    triggers: vec![Trigger::CheckProperty {
+                        name: "Bird".to_string(),
+                        check: PropertyCheck::AnimationFrame { index: 1 },
+                    }],
+                    actions: vec![Action::Win],
+                },
+            ],
+            ..Default::default()
+        });
+        let mut game = Game::from_data(game_data, &mut rng).unwrap();
+
+        let mut won_on = None;
+        for frame in 0..10 {
+            game.update_frame(Mouse::default(), &mut rng).unwrap();
+            if game.status.current == WinStatus::JustWon {
+                won_on = Some(frame);
+                break;
+            }
+        }
+        assert_eq!(game.objects[0].sprite, sprites[1]);
+        assert!(won_on.is_some());
+    }
+
+    #[test]
+    fn loads_collision_masks_from_image_files() {
+        let mut game_data = GameData::default();
+        game_data
+            .asset_files
+            .images
+            .insert("Any".to_string(), "any.png".to_string());
+        let masks = CollisionMasks::load(&game_data, "nonexistent").unwrap();
+        assert!(masks.is_empty());
+
+        game_data.objects.push(SerialiseObject {
+            collision_mode: CollisionMode::Pixels,
+            ..SerialiseObject::default()
+        });
+        assert!(CollisionMasks::load(&game_data, "nonexistent").is_err());
+    }
+
+    #[test]
+    fn wins_game_with_every_frame_trigger() {
+        let mut game = Game::default();
+        let mut object = Object::default();
+        object.instructions.push(Instruction {
+            triggers: vec![],
+            actions: vec![Action::Win],
+        });
+        game.objects.insert("Simple".to_string(), object);
+        let table = InstructionTable::new(&game.objects, 0);
+        let mut triggered = Vec::new();
+        check_triggers(
+            &game,
+            0,
+            &table.objects[0],
+            &mut triggered,
+            Mouse::default(),
+            &mut TestRng::default(),
+        )
+        .unwrap();
+        assert_eq!(triggered.len(), 1);
+        assert_eq!(
+            game.objects[0].instructions[triggered[0]].actions,
+            vec![Action::Win]
+        );
+    }
+
+    #[test]
+    fn rebuilds_instructions_when_objects_move_or_change() {
+        let mut game = Game::default();
+        let watcher = Object {
+            instructions: vec![Instruction {
+                triggers: vec![Trigger::CheckProperty {
+                    name: "Flag".to_string(),
+                    check: PropertyCheck::Switch(SwitchState::On),
+                }],
+                actions: vec![Action::Win],
+            }],
+            ..Object::default()
+        };
+        let other = Object {
+            switch: SwitchState::On,
+            ..Object::default()
+        };
+        game.objects.insert("Watcher".to_string(), watcher);
+        game.objects.insert("Flag".to_string(), Object::default());
+        game.objects.insert("Other".to_string(), other);
+        let mut rng = TestRng::default();
+
+        game.update_frame(Mouse::default(), &mut rng).unwrap();
+        game.objects.swap_indices(1, 2);
+        game.objects_changed();
+        game.update_frame(Mouse::default(), &mut rng).unwrap();
+        assert_eq!(game.status.current, WinStatus::NotYetWon);
+
+        game.objects[0].instructions[0].actions = vec![Action::Lose];
+        game.objects["Flag"].switch = SwitchState::On;
+        game.objects_changed();
+        game.update_frame(Mouse::default(), &mut rng).unwrap();
+        assert_eq!(game.status.current, WinStatus::JustLost);
+    }
+
+    #[test]
+    fn loads_old_collision_area_as_aabb_shape() {
+        let mut json = serde_json::to_value(SerialiseObject::default()).unwrap();
+        let fields = json.as_object_mut().unwrap();
+        fields.remove("collision_shape");
+        fields.insert(
+            "collision_area".to_string(),
+            serde_json::to_value(AABB::new(0.0, 0.0, 10.0, 20.0)).unwrap(),
+        );
+        let object: SerialiseObject = serde_json::from_value(json).unwrap();
+        assert_eq!(
+            object.collision_shape,
+            Some(CollisionShape::AABB(AABB::new(0.0, 0.0, 10.0, 20.0)))
+        );
+    }
+
+    #[test]
+    fn resizing_scales_collision_shapes_along_each_axis() {
+        let mut area = CollisionShape::AABB(AABB::new(10.0, 10.0, 90.0, 40.0));
+        area.scale(Size::new(100.0, 50.0), Size::new(300.0, 50.0));
+        assert_eq!(
+            area,
+            CollisionShape::AABB(AABB::new(30.0, 10.0, 270.0, 40.0))
+        );
+
+        let mut circle = CollisionShape::Circle {
+            centre: Vec2::new(50.0, 25.0),
+            radius: 20.0,
+        };
+        circle.scale(Size::new(100.0, 50.0), Size::new(100.0, 100.0));
+        assert_eq!(
+            circle,
+            CollisionShape::Circle {
+                centre: Vec2::new(50.0, 50.0),
+                radius: 20.0,
+            }
+        );
+    }
+
+    #[test]
+    fn circle_collision_shape_misses_corners() {
+        let mut game = Game::default();
+        let ball = Object {
+            collision_shape: Some(CollisionShape::Circle {
+                centre: Vec2::new(50.0, 50.0),
+                radius: 50.0,
+            }),
+            ..Object::default()
+        };
+        let corner = Object {
+            position: Vec2::new(752.0, 402.0),
+            size: Size::new(10.0, 10.0),
+            ..Object::default()
+        };
+        game.objects.insert("Ball".to_string(), ball);
+        game.objects.insert("Corner".to_string(), corner);
+
+        let collides = |game: &Game| {
+            Trigger::Collision(
+                CollisionWith::Object {
+                    name: "Corner".to_string(),
+                },
+                CollisionPhase::Stay,
+            )
+            .is_triggered(
+                game,
+                0,
+                TriggerTargets {
+                    other: Some(1),
+                    ..TriggerTargets::default()
+                },
+                Mouse::default(),
+                &mut TestRng::default(),
+            )
+            .unwrap()
+        };
+        assert!(!collides(&game));
+
+        game.objects[1].position = Vec2::new(800.0, 402.0);
+        assert!(collides(&game));
+    }
+
+    #[test]
+    fn shape_sprites_collide_with_their_shape_by_default() {
+        let circle = Sprite::Shape(VectorSprite::new(VectorShape::Ellipse, Colour::black()));
+        let ball = Object {
+            sprite: circle.clone(),
+            ..Object::default()
+        };
+        assert!(!ball.shape().contains_point(Vec2::new(752.0, 402.0)));
+        assert!(ball.shape().contains_point(Vec2::new(800.0, 402.0)));
+
+        let line = Sprite::Shape(VectorSprite {
+            shape: VectorShape::Line {
+                start: Vec2::new(0.0, 0.5),
+                end: Vec2::new(1.0, 0.5),
+            },
+            fill: Fill::None,
+            stroke: Some(Stroke {
+                colour: Colour::black(),
+                width: 10.0,
+            }),
+        });
+        let wire = Object {
+            sprite: line,
+            ..Object::default()
+        };
+        assert!(wire.shape().contains_point(Vec2::new(760.0, 454.0)));
+        assert!(!wire.shape().contains_point(Vec2::new(760.0, 470.0)));
+
+        let boxed = Object {
+            sprite: circle,
+            collision_shape: Some(CollisionShape::AABB(AABB::new(0.0, 0.0, 100.0, 100.0))),
+            ..Object::default()
+        };
+        assert!(boxed.shape().contains_point(Vec2::new(752.0, 402.0)));
+    }
+
+    #[test]
+    fn concave_shape_sprites_collide_with_their_bounds() {
+        let polygon = |points: &[(f32, f32)]| {
+            let points = points.iter().map(|&(x, y)| Vec2::new(x, y)).collect();
+            VectorSprite::new(VectorShape::Polygon { points }, Colour::black())
+        };
+        let size = Size::new(100.0, 100.0);
+        let corner = polygon(&[
+            (0.0, 0.0),
+            (1.0, 0.0),
+            (1.0, 1.0),
+            (0.5, 1.0),
+            (0.5, 0.5),
+            (0.0, 0.5),
+        ]);
+        assert_eq!(
+            corner.collision_shape(size),
+            CollisionShape::AABB(AABB::new(0.0, 0.0, 100.0, 100.0))
+        );
+        let triangle = polygon(&[(0.0, 0.0), (1.0, 0.0), (0.5, 1.0)]);
+        assert!(matches!(
+            triangle.collision_shape(size),
+            CollisionShape::Polygon { .. }
+        ));
+
+        let mut game = Game::default();
+        game.objects.insert(
+            "Triangle".to_string(),
+            Object {
+                sprite: Sprite::Shape(triangle.clone()),
+                ..Object::default()
+            },
+        );
+        game.update_frame(Mouse::default(), &mut TestRng::default())
+            .unwrap();
+        assert!(matches!(
+            game.objects[0].active_collision_shape(),
+            Some(Cow::Borrowed(_))
+        ));
+        game.objects[0].size = Size::new(200.0, 100.0);
+        assert_eq!(
+            game.objects[0].active_collision_shape().as_deref(),
+            Some(&triangle.collision_shape(Size::new(200.0, 100.0)))
+        );
+    }
+
+    #[test]
+    fn shape_sprite_triangles_cover_the_shape() {
+        let triangle_area = |vertices: &[ShapeVertex]| -> f32 {
+            vertices
+                .chunks(3)
+                .map(|triangle| {
+                    let (a, b, c) = (
+                        triangle[0].position,
+                        triangle[1].position,
+                        triangle[2].position,
+                    );
+                    ((b - a).x * (c - a).y - (b - a).y * (c - a).x).abs() / 2.0
+                })
+                .sum()
+        };
+        let transform = SpriteTransform::new(AABB::new(100.0, 100.0, 300.0, 200.0));
+
+        let rounded = VectorSprite::new(
+            VectorShape::RoundedRectangle { radius: 20.0 },
+            Colour::black(),
+        );
+        let expected = 200.0 * 100.0 - (4.0 - std::f32::consts::PI) * 20.0 * 20.0;
+        assert!((triangle_area(&rounded.triangles(&transform)) - expected).abs() < 10.0);
+
+        // An L shape isn't convex so it can't be filled as a fan from one corner
+        let l_shape = VectorSprite {
+            shape: VectorShape::Polygon {
+                points: vec![
+                    Vec2::new(0.0, 0.0),
+                    Vec2::new(0.5, 0.0),
+                    Vec2::new(0.5, 0.5),
+                    Vec2::new(1.0, 0.5),
+                    Vec2::new(1.0, 1.0),
+                    Vec2::new(0.0, 1.0),
+                ],
+            },
+            fill: Fill::Gradient {
+                start: Colour::black(),
+                end: Colour::white(),
+                direction: GradientDirection::Horizontal,
+            },
+            stroke: None,
+        };
+        let vertices = l_shape.triangles(&transform);
+        assert!((triangle_area(&vertices) - 200.0 * 100.0 * 0.75).abs() < 0.01);
+        for vertex in vertices {
+            let expected = (vertex.position.x - 100.0) / 200.0;
+            assert!((vertex.colour.r - expected).abs() < 0.001);
+        }
+    }
+
+    #[test]
+    fn size_setter_scales_collision_shape() {
+        let mut game = Game::default();
+        let ball = Object {
+            collision_shape: Some(CollisionShape::Circle {
+                centre: Vec2::new(50.0, 50.0),
+                radius: 40.0,
+            }),
+            ..Object::default()
+        };
+        game.objects.insert("Ball".to_string(), ball);
+
+        Action::SetProperty(PropertySetter::Size(SizeSetter::Value(Size::new(
+            200.0, 400.0,
+        ))))
+        .apply(
+            &mut game,
+            0,
+            None,
+            Mouse::default(),
+            &mut TestRng::default(),
+            &mut Vec::new(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            game.objects[0].collision_shape,
+            Some(CollisionShape::Circle {
+                centre: Vec2::new(100.0, 200.0),
+                radius: 80.0,
+            })
+        );
+    }
+
+    #[test]
+    fn editor_shape_points_round_trip_through_world() {
+        let object = SerialiseObject {
+            position: Vec2::new(300.0, 200.0),
+            size: Size::new(100.0, 50.0),
+            angle: 30.0,
+            flip: Flip {
+                horizontal: true,
+                vertical: false,
+            },
+            ..SerialiseObject::default()
+        };
+        let point = Vec2::new(20.0, 10.0);
+        let world = object.world_to_shape_point(object.shape_point_to_world(point));
+
+        assert!((world - point).magnitude() < 0.001);
+    }
+
+    #[test]
+    fn collision_enter_and_exit_fire_once_per_touch() {
+        let count_collision = |with: CollisionWith, phase: CollisionPhase| {
+            count_in_layer(Trigger::Collision(with, phase))
+        };
+        let mut game = Game::default();
+        let player = Object {
+            instructions: vec![count_collision(
+                CollisionWith::Object {
+                    name: "Wall".to_string(),
+                },
+                CollisionPhase::Enter,
+            )],
+            ..Object::default()
+        };
+        let wall = Object {
+            position: Vec2::new(1200.0, 450.0),
+            instructions: vec![
+                count_collision(
+                    CollisionWith::Object {
+                        name: "Player".to_string(),
+                    },
+                    CollisionPhase::Exit,
+                ),
+                count_collision(
+                    CollisionWith::Area(AABB::new(0.0, 0.0, 100.0, 100.0)),
+                    CollisionPhase::Enter,
+                ),
+            ],
+            ..Object::default()
+        };
+        game.objects.insert("Player".to_string(), player);
+        game.objects.insert("Wall".to_string(), wall);
+
+        let mut rng = TestRng::default();
+        let mut run_frame = |game: &mut Game, x: f32| {
+            game.objects[0].position.x = x;
+            game.update_frame(Mouse::default(), &mut rng).unwrap();
+        };
+        run_frame(&mut game, 800.0);
+        run_frame(&mut game, 1150.0);
+        run_frame(&mut game, 1160.0);
+        assert_eq!(game.objects[0].layer, 1);
+        assert_eq!(game.objects[1].layer, 0);
+
+        let snapshot = Snapshot::take(&game, &TestRng::default());
+        let mut game = Game::default();
+        Snapshot::<TestRng>::from_bytes(&snapshot.to_bytes().unwrap())
+            .unwrap()
+            .restore(&mut game, &mut TestRng::default());
+
+        run_frame(&mut game, 800.0);
+        run_frame(&mut game, 800.0);
+        assert_eq!(game.objects[0].layer, 1);
+        assert_eq!(game.objects[1].layer, 1);
+
+        run_frame(&mut game, 1150.0);
+        assert_eq!(game.objects[0].layer, 2);
+        assert_eq!(game.objects[1].layer, 1);
+    }
+
+    #[test]
+    fn loads_collision_trigger_without_phase() {
+        let trigger: Trigger =
+            serde_json::from_str(r#"{"Collision":{"Object":{"name":"Wall"}}}"#).unwrap();
+
+        assert_eq!(
+            trigger,
+            Trigger::Collision(
+                CollisionWith::Object {
+                    name: "Wall".to_string()
+                },
+                CollisionPhase::Stay
+            )
+        );
+        let saved = serde_json::to_string(&trigger).unwrap();
+        assert_eq!(serde_json::from_str::<Trigger>(&saved).unwrap(), trigger);
+    }
+
+    #[test]
+    fn bounds_trigger_checks_object_against_screen() {
+        let mut game = Game::default();
+        game.objects.insert("Ball".to_string(), Object::default());
+
+        let mut check = |x: f32, condition: BoundsCondition| {
+            game.objects[0].position.x = x;
+            game.collisions.rebuild(&game.objects);
+            let trigger: Trigger = serde_json::from_value(serde_json::json!({
+                "Bounds": { "condition": condition }
+            }))
+            .unwrap();
+            trigger
+                .is_triggered(
+                    &game,
+                    0,
+                    TriggerTargets::default(),
+                    Mouse::default(),
+                    &mut TestRng::default(),
+                )
+                .unwrap()
+        };
+        assert!(check(800.0, BoundsCondition::FullyInside));
+        assert!(!check(800.0, BoundsCondition::TouchingEdge));
+        assert!(check(1600.0, BoundsCondition::TouchingEdge));
+        assert!(check(1600.0, BoundsCondition::CrossedEdge(Side::Right)));
+        assert!(!check(1600.0, BoundsCondition::CrossedEdge(Side::Left)));
+        assert!(!check(1600.0, BoundsCondition::FullyOutside));
+        assert!(check(1700.0, BoundsCondition::FullyOutside));
+        assert!(!check(1700.0, BoundsCondition::FullyInside));
+    }
+
+    #[test]
+    fn compares_properties_with_values_and_other_objects() {
+        let check = |name: &str, property, comparison, to| {
+            count_in_layer(Trigger::CheckProperty {
+                name: name.to_string(),
+                check: PropertyCheck::Compare {
+                    property,
+                    comparison,
+                    to,
+                },
+            })
+        };
+        let mut game = Game::default();
+        let balloon = Object {
+            instructions: vec![check(
+                "Balloon",
+                NumericProperty::Height,
+                Comparison::GreaterThan,
+                CompareTo::Value(400.0),
+            )],
+            ..Object::default()
+        };
+        let car = Object {
+            position: Vec2::new(100.0, 450.0),
+            active_motion: ActiveMotion::GoStraight {
+                velocity: Vec2::new(300.0, 0.0),
+            },
+            instructions: vec![check(
+                "Car",
+                NumericProperty::X,
+                Comparison::LessThan,
+                CompareTo::Object {
+                    name: "Finish".to_string(),
+                    property: NumericProperty::X,
+                },
+            )],
+            ..Object::default()
+        };
+        let finish = Object {
+            position: Vec2::new(1000.0, 450.0),
+            ..Object::default()
+        };
+        game.objects.insert("Balloon".to_string(), balloon);
+        game.objects.insert("Car".to_string(), car);
+        game.objects.insert("Finish".to_string(), finish);
+
+        let mut rng = TestRng::default();
+        for _ in 0..4 {
+            game.update_frame(Mouse::default(), &mut rng).unwrap();
+        }
+        game.objects[0].size.height = 500.0;
+        game.update_frame(Mouse::default(), &mut rng).unwrap();
+
+        assert_eq!(game.objects[0].layer, 1);
+        assert_eq!(game.objects[1].layer, 3);
+        assert_eq!(
+            game.objects[1].property_value(NumericProperty::Speed),
+            300.0
+        );
+    }
+
+    #[test]
+    fn distance_trigger_measures_centres_and_edges() {
+        let mut game = Game::default();
+        let dog = |x: f32| Object {
+            position: Vec2::new(x, 450.0),
+            tags: vec!["Dog".to_string()],
+            ..Object::default()
+        };
+        game.objects.insert("Nose".to_string(), Object::default());
+        game.objects.insert("Far Dog".to_string(), dog(1500.0));
+        game.objects.insert("Near Dog".to_string(), dog(950.0));
+        game.collisions.rebuild(&game.objects);
+
+        let is_near = |to: DistanceEnd, measure, value| {
+            Trigger::Distance {
+                from: DistanceEnd::Object {
+                    name: "Nose".to_string(),
+                },
+                to,
+                measure,
+                comparison: Comparison::LessThanOrEqual,
+                value,
+            }
+            .is_triggered(
+                &game,
+                0,
+                TriggerTargets {
+                    other: Some(0),
+                    ..TriggerTargets::default()
+                },
+                Mouse::default(),
+                &mut TestRng::default(),
+            )
+            .unwrap()
+        };
+        let dogs = || DistanceEnd::Tag {
+            tag: "Dog".to_string(),
+        };
+        assert!(is_near(dogs(), DistanceMeasure::Centre, 150.0));
+        assert!(!is_near(dogs(), DistanceMeasure::Centre, 149.0));
+        assert!(is_near(dogs(), DistanceMeasure::Edge, 50.0));
+        assert!(!is_near(dogs(), DistanceMeasure::Edge, 49.0));
+        assert!(is_near(
+            DistanceEnd::Point(Vec2::new(800.0, 500.0)),
+            DistanceMeasure::Edge,
+            0.0
+        ));
+        assert!(!is_near(
+            DistanceEnd::Tag {
+                tag: "Cat".to_string()
+            },
+            DistanceMeasure::Centre,
+            10000.0
+        ));
+    }
+
+    #[test]
+    fn distance_to_a_tag_skips_the_object_measuring() {
+        let mut game = Game::default();
+        let dog = |x: f32| Object {
+            position: Vec2::new(x, 450.0),
+            tags: vec!["Dog".to_string()],
+            ..Object::default()
+        };
+        game.objects.insert("Nose".to_string(), dog(800.0));
+        game.objects.insert("Near Dog".to_string(), dog(950.0));
+        game.collisions.rebuild(&game.objects);
+
+        let is_near = |from: DistanceEnd, value| {
+            Trigger::Distance {
+                from,
+                to: DistanceEnd::Tag {
+                    tag: "Dog".to_string(),
+                },
+                measure: DistanceMeasure::Centre,
+                comparison: Comparison::LessThanOrEqual,
+                value,
+            }
+            .is_triggered(
+                &game,
+                0,
+                TriggerTargets {
+                    other: Some(0),
+                    ..TriggerTargets::default()
+                },
+                Mouse::default(),
+                &mut TestRng::default(),
+            )
+            .unwrap()
+        };
+        let nose = || DistanceEnd::Object {
+            name: "Nose".to_string(),
+        };
+        let dogs = || DistanceEnd::Tag {
+            tag: "Dog".to_string(),
+        };
+        assert!(is_near(nose(), 150.0));
+        assert!(!is_near(nose(), 149.0));
+        assert!(is_near(dogs(), 150.0));
+        assert!(!is_near(dogs(), 149.0));
+    }
+
+    #[test]
+    fn mouse_over_picks_topmost_object_in_draw_order() {
+        let hovered_objects = |layer_sorting: LayerSorting| {
+            let mut game = Game {
+                layer_sorting,
+                ..Game::default()
+            };
+            let button = |name: &str, y: f32, depth: f32| Object {
+                position: Vec2::new(800.0, y),
+                depth,
+                instructions: vec![Instruction {
+                    triggers: vec![Trigger::Input(Input::Mouse {
+                        over: MouseOver::Object {
+                            name: name.to_string(),
+                        },
+                        interaction: MouseInteraction::Hover,
+                    })],
+                    actions: vec![Action::SetProperty(PropertySetter::Switch(Switch::On))],
+                }],
+                ..Object::default()
+            };
+            game.objects
+                .insert("Low".to_string(), button("Low", 480.0, 1.0));
+            game.objects
+                .insert("High".to_string(), button("High", 450.0, 2.0));
+            let mouse = Mouse {
+                position: Vec2::new(800.0, 460.0),
+                ..Mouse::default()
+            };
+            game.update_frame(mouse, &mut TestRng::default()).unwrap();
+            game.objects
+                .iter()
+                .filter(|(_, object)| object.switch == SwitchState::SwitchedOn)
+                .map(|(name, _)| name.clone())
+                .collect::<Vec<String>>()
+        };
+
+        let sorted_by = |sort| {
+            let mut sorting = LayerSorting::new();
+            sorting.insert(0, sort);
+            sorting
+        };
+        assert_eq!(hovered_objects(LayerSorting::new()), vec!["High"]);
+        assert_eq!(
+            hovered_objects(sorted_by(LayerSort::YPosition)),
+            vec!["Low"]
+        );
+        assert_eq!(hovered_objects(sorted_by(LayerSort::Depth)), vec!["Low"]);
+    }
+
+    #[test]
+    fn slow_motion_holds_back_frames_and_effects_wear_off() {
+        let mut game = Game::default();
+        let instructions = vec![Instruction {
+            triggers: vec![Trigger::Time(When::Start)],
+            actions: vec![
+                Action::Effect(Effect::SlowMotion {
+                    every: 3,
+                    frames: 6,
+                }),
+                Action::Effect(Effect::Flash {
+                    colour: Colour::white(),
+                    frames: 4,
+                }),
+            ],
+        }];
+        game.objects.insert(
+            "Effects".to_string(),
+            Object {
+                instructions,
+                ..Object::default()
+            },
+        );
+        let mut rng = TestRng::default();
+
+        game.update_frame(Mouse::default(), &mut rng).unwrap();
+        assert_eq!(game.screen_effects().overlay, Some(Colour::white()));
+        game.update_frame(Mouse::default(), &mut rng).unwrap();
+        assert_eq!(
+            game.screen_effects().overlay,
+            Some(Colour::rgba(1.0, 1.0, 1.0, 0.75))
+        );
+
+        for _ in 0..5 {
+            game.update_frame(Mouse::default(), &mut rng).unwrap();
+        }
+        assert_eq!(game.frames.ran, 3);
+        assert_eq!(game.frames.slowed, 4);
+        assert_eq!(game.screen_effects(), ScreenEffects::default());
+    }
+
+    #[test]
+    fn clicks_during_slow_motion_reach_the_next_frame() {
+        let mut game = Game::default();
+        let instructions = vec![
+            Instruction {
+                triggers: vec![Trigger::Time(When::Start)],
+                actions: vec![Action::Effect(Effect::SlowMotion {
+                    every: 3,
+                    frames: 30,
+                })],
+            },
+            Instruction {
+                triggers: vec![Trigger::Input(Input::Mouse {
+                    over: MouseOver::Anywhere,
+                    interaction: MouseInteraction::Button {
+                        state: ButtonState::Release,
+                    },
+                })],
+                actions: vec![Action::Win],
+            },
+        ];
+        game.objects.insert(
+            "Button".to_string(),
+            Object {
+                instructions,
+                ..Object::default()
+            },
+        );
+        let mut rng = TestRng::default();
+        let click = |state| Mouse {
+            state,
+            ..Mouse::default()
+        };
+
+        game.update_frame(Mouse::default(), &mut rng).unwrap();
+        game.update_frame(click(ButtonState::Press), &mut rng)
+            .unwrap();
+        game.update_frame(click(ButtonState::Release), &mut rng)
+            .unwrap();
+        assert_eq!(game.frames.slowed, 2);
+        assert_eq!(game.status.next_frame, WinStatus::NotYetWon);
+
+        // The press runs on one frame and the release on the next
+        while game.frames.ran < 3 {
+            game.update_frame(Mouse::default(), &mut rng).unwrap();
+        }
+        assert!(matches!(
+            game.status.next_frame,
+            WinStatus::JustWon | WinStatus::Won
+        ));
+    }
+
+    #[test]
+    fn sprite_transform_flips_then_rotates_around_pivot() {
+        let transform = SpriteTransform {
+            area: AABB::new(0.0, 0.0, 100.0, 50.0),
+            angle: 90.0,
+            pivot: Vec2::zero(),
+            flip: Flip {
+                horizontal: true,
+                vertical: false,
+            },
+        };
+        let is_near = |a: Vec2, b: Vec2| (a - b).magnitude() < 0.001;
+
+        let expected = [
+            Vec2::new(0.0, 100.0),
+            Vec2::zero(),
+            Vec2::new(-50.0, 0.0),
+            Vec2::new(-50.0, 100.0),
+        ];
+        for (corner, expected) in transform.corners().iter().zip(expected.iter()) {
+            assert!(is_near(*corner, *expected), "{:?}", corner);
+        }
+
+        let uv = transform
+            .texture_coordinates(Vec2::new(-25.0, 75.0))
+            .unwrap();
+        assert!(is_near(uv, Vec2::new(0.25, 0.5)), "{:?}", uv);
+        assert_eq!(transform.texture_coordinates(Vec2::new(10.0, 10.0)), None);
+
+        let unrotated = SpriteTransform::new(AABB::new(10.0, 20.0, 30.0, 60.0));
+        assert_eq!(unrotated.pivot, Vec2::new(20.0, 40.0));
+        assert_eq!(
+            unrotated.corners(),
+            [
+                Vec2::new(10.0, 20.0),
+                Vec2::new(30.0, 20.0),
+                Vec2::new(30.0, 60.0),
+                Vec2::new(10.0, 60.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn draw_commands_resolve_sprites_and_text() {
+        let mut game = Game {
+            background: vec![BackgroundPart {
+                sprite: Sprite::Colour(Colour::black()),
+                area: AABB::new(0.0, 0.0, PROJECTION_WIDTH, PROJECTION_HEIGHT),
+            }],
+            intro_text: "Go!".to_string(),
+            ..Game::default()
+        };
+        game.objects.insert(
+            "Sign".to_string(),
+            Object {
+                sprite: Sprite::Image {
+                    name: "sign".to_string(),
+                },
+                position: Vec2::new(800.0, 450.0),
+                size: Size::new(200.0, 100.0),
+                angle: 30.0,
+                origin: Some(Vec2::zero()),
+                ..Object::default()
+            },
+        );
+        let mut drawn_text = HashMap::new();
+        drawn_text.insert(
+            "Sign".to_string(),
+            DrawnText {
+                text: "Hello".to_string(),
+                font: "font".to_string(),
+                colour: Colour::white(),
+                resize: TextResize::MatchText,
+                justify: JustifyText::Left,
+                layout: TextLayout::default(),
+            },
+        );
+
+        let commands = game.draw_commands(&drawn_text, |_| Some(&TEST_FONT));
+
+        let sign_transform = SpriteTransform {
+            area: AABB::new(700.0, 400.0, 900.0, 500.0),
+            angle: 30.0,
+            pivot: Vec2::new(700.0, 400.0),
+            flip: Flip::default(),
+        };
+        assert_eq!(
+            commands,
+            vec![
+                DrawCommand::Rectangle {
+                    colour: Colour::black(),
+                    transform: SpriteTransform::new(AABB::new(
+                        0.0,
+                        0.0,
+                        PROJECTION_WIDTH,
+                        PROJECTION_HEIGHT
+                    )),
+                },
+                DrawCommand::Image {
+                    name: "sign".to_string(),
+                    region: None,
+                    transform: sign_transform,
+                },
+                DrawCommand::Text {
+                    object: "Sign".to_string(),
+                    text: "Hello".to_string(),
+                    font: "font".to_string(),
+                    colour: Colour::white(),
+                    scale: 1.0,
+                    transform: SpriteTransform {
+                        area: AABB::new(700.0, 440.0, 750.0, 460.0),
+                        ..sign_transform
+                    },
+                },
+                DrawCommand::IntroText {
+                    text: "Go!".to_string(),
+                    centre: Vec2::new(800.0, 450.0),
+                },
+            ]
+        );
+
+        game.frames.ran = INTRO_TEXT_TIME;
+        let commands = game.draw_commands(&HashMap::new(), |_| None::<FixedWidthFont>);
+        assert_eq!(commands.len(), 2);
+    }
+
+    #[test]
+    fn text_is_wrapped_aligned_and_shrunk_inside_its_object() {
+        let area = AABB::new(0.0, 0.0, 100.0, 100.0);
+        let lines = |laid_out: &LaidOutText| -> Vec<(String, AABB)> {
+            laid_out
+                .lines
+                .iter()
+                .map(|line| (line.text.clone(), line.area))
+                .collect()
+        };
+
+        let wrapped = TextLayout {
+            wrap: true,
+            vertical_align: VerticalAlign::Top,
+            line_spacing: 1.5,
+            ..TextLayout::default()
+        };
+        let laid_out = wrapped.lay_out(
+            "one two three\nfourteen letters",
+            area,
+            JustifyText::Right,
+            &TEST_FONT,
+        );
+        assert_eq!(
+            lines(&laid_out),
+            vec![
+                ("one two".to_string(), AABB::new(30.0, 0.0, 100.0, 20.0)),
+                ("three".to_string(), AABB::new(50.0, 30.0, 100.0, 50.0)),
+                ("fourteen".to_string(), AABB::new(20.0, 60.0, 100.0, 80.0)),
+                ("letters".to_string(), AABB::new(30.0, 90.0, 100.0, 110.0)),
+            ]
+        );
+        assert_eq!(laid_out.size(), Size::new(80.0, 110.0));
+
+        // Words longer than the object are split between letters
+        let laid_out = wrapped.lay_out("abcdefghijkl", area, JustifyText::Left, &TEST_FONT);
+        assert_eq!(
+            lines(&laid_out)
+                .into_iter()
+                .map(|(text, _)| text)
+                .collect::<Vec<String>>(),
+            vec!["abcdefghij".to_string(), "kl".to_string()]
+        );
+
+        let shrunk = TextLayout {
+            shrink_to_fit: true,
+            vertical_align: VerticalAlign::Bottom,
+            ..TextLayout::default()
+        };
+        let laid_out = shrunk.lay_out(
+            "twenty letters long!",
+            area,
+            JustifyText::Centre,
+            &TEST_FONT,
+        );
+        assert_eq!(laid_out.scale, 0.5);
+        assert_eq!(
+            lines(&laid_out),
+            vec![(
+                "twenty letters long!".to_string(),
+                AABB::new(0.0, 90.0, 100.0, 100.0)
+            )]
+        );
+
+        // Text the object is resized to match is never wrapped or shrunk
+        let drawn_text = DrawnText {
+            text: "twenty letters long!".to_string(),
+            font: "font".to_string(),
+            colour: Colour::black(),
+            resize: TextResize::MatchText,
+            justify: JustifyText::Left,
+            layout: TextLayout {
+                wrap: true,
+                ..shrunk
+            },
+        };
+        let laid_out = drawn_text.lay_out(area, &TEST_FONT);
+        assert_eq!(laid_out.scale, 1.0);
+        assert_eq!(laid_out.size(), Size::new(200.0, 20.0));
+    }
+
+    #[test]
+    fn text_outlines_and_shadows_are_drawn_underneath() {
+        let mut game = Game::default();
+        game.objects.insert("Title".to_string(), Object::default());
+        let mut drawn_text = HashMap::new();
+        drawn_text.insert(
+            "Title".to_string(),
+            DrawnText {
+                text: "Hi".to_string(),
+                font: "font".to_string(),
+                colour: Colour::white(),
+                resize: TextResize::MatchObject,
+                justify: JustifyText::Centre,
+                layout: TextLayout {
+                    outline: Some(TextOutline {
+                        colour: Colour::black(),
+                        width: 2.0,
+                    }),
+                    shadow: Some(TextShadow {
+                        colour: Colour::rgba(0.0, 0.0, 0.0, 0.5),
+                        offset: Vec2::new(3.0, 3.0),
+                    }),
+                    ..TextLayout::default()
+                },
+            },
+        );
+
+        let text: Vec<(Colour, AABB)> = game
+            .draw_commands(&drawn_text, |_| Some(&TEST_FONT))
+            .into_iter()
+            .filter_map(|command| match command {
+                DrawCommand::Text {
+                    colour, transform, ..
+                } => Some((colour, transform.area)),
+                _ => None,
+            })
+            .collect();
+        // The shadow, eight copies around the text for the outline, then the text
+        assert_eq!(text.len(), 10);
+        let centred = AABB::new(790.0, 440.0, 810.0, 460.0);
+        assert_eq!(
+            text[0],
+            (
+                Colour::rgba(0.0, 0.0, 0.0, 0.5),
+                AABB::new(793.0, 443.0, 813.0, 463.0)
+            )
+        );
+        assert_eq!(
+            text[1],
+            (Colour::black(), AABB::new(792.0, 440.0, 812.0, 460.0))
+        );
+        assert_eq!(text[9], (Colour::white(), centred));
+    }
+
+    fn draw_text(text: &str, key: Option<&str>) -> Action {
+        Action::DrawText {
+            text: text.to_string(),
+            font: "font".to_string(),
+            colour: Colour::black(),
+            resize: TextResize::MatchObject,
+            justify: JustifyText::Centre,
+            layout: TextLayout::default(),
+            key: key.map(str::to_string),
+        }
     }
 
-    fn play_test_game(
-        game: &mut Game,
-        inputs: &mut Vec<Mouse>,
-        rng: &mut impl WeeRng,
-    ) -> WeeResult<()> {
-        while game.frames.remaining() != FrameCount::Frames(0) {
-            let mouse = if inputs.len() > 0 {
-                inputs.remove(0)
-            } else {
-                Mouse::default()
-            };
-
-            let world_actions = game.update_frame(mouse, rng)?;
-
-            for action in &world_actions {
-                if let WorldAction::EndEarly = action {
-                    return Ok(());
+    fn drawn_texts(game_data: &GameData) -> Vec<(String, Option<String>)> {
+        let mut texts = Vec::new();
+        for instruction in &game_data.objects[0].instructions {
+            for action in &instruction.actions {
+                let actions = match action {
+                    Action::Random { random_actions } => random_actions.iter().collect(),
+                    action => vec![action],
+                };
+                for action in actions {
+                    if let Action::DrawText { text, key, .. } = action {
+                        texts.push((text.clone(), key.clone()));
+                    }
                 }
             }
         }
-
-        Ok(())
+        texts
     }
 
     #[test]
-    fn triggers_time_start_on_first_frame() {
-        let mut game = Game::default();
-        game.objects.insert("Simple".to_string(), Object::default());
-        assert!(Trigger::Time(When::Start)
-            .is_triggered(&game, "Simple", Mouse::default(), &mut TestRng::default())
-            .unwrap());
-    }
+    fn text_with_a_key_uses_the_chosen_language_then_the_default() {
+        let mut game_data = GameData {
+            intro_text: Some("Go!".to_string()),
+            intro_text_key: Some("intro".to_string()),
+            objects: vec![SerialiseObject {
+                instructions: vec![Instruction {
+                    triggers: vec![Trigger::Time(When::Start)],
+                    actions: vec![
+                        draw_text("Score", Some("score")),
+                        draw_text("Lives", Some("lives")),
+                        draw_text("Unchanged", None),
+                        Action::Random {
+                            random_actions: vec![draw_text("Missing", Some("missing"))],
+                        },
+                    ],
+                }],
+                ..SerialiseObject::default()
+            }],
+            ..GameData::default()
+        };
+        let table = |entries: &[(&str, &str)]| {
+            entries
+                .iter()
+                .map(|(key, text)| (key.to_string(), text.to_string()))
+                .collect::<StringTable>()
+        };
+        let french = table(&[("intro", "Allez !"), ("score", "Points")]);
+        let english = table(&[("score", "Score:"), ("lives", "Lives:")]);
 
-    #[test]
-    fn triggers_time_end_on_last_frame() {
-        let mut game = Game::default();
-        game.objects.insert("Simple".to_string(), Object::default());
-        game.frames.ran += DEFAULT_GAME_LENGTH_IN_FRAMES - 1;
-        assert!(Trigger::Time(When::End)
-            .is_triggered(&game, "Simple", Mouse::default(), &mut TestRng::default())
-            .unwrap());
-    }
+        game_data.localise(&Translations::new(vec![french, english]));
 
-    #[test]
-    fn only_triggers_time_end_on_last_frame() {
-        let mut game = Game::default();
-        game.objects.insert("Simple".to_string(), Object::default());
-        for _ in 0..DEFAULT_GAME_LENGTH_IN_FRAMES - 1 {
-            assert!(!Trigger::Time(When::End)
-                .is_triggered(&game, "Simple", Mouse::default(), &mut TestRng::default())
-                .unwrap());
-            game.frames.ran += 1;
-        }
-        assert!(Trigger::Time(When::End)
-            .is_triggered(&game, "Simple", Mouse::default(), &mut TestRng::default())
-            .unwrap());
+        assert_eq!(game_data.intro_text.as_deref(), Some("Allez !"));
+        let texts: Vec<String> = drawn_texts(&game_data)
+            .into_iter()
+            .map(|(text, _)| text)
+            .collect();
+        assert_eq!(texts, vec!["Points", "Lives:", "Unchanged", "Missing"]);
+        assert_eq!(fallback_languages("fr"), vec!["fr", DEFAULT_LANGUAGE]);
+        assert_eq!(fallback_languages(DEFAULT_LANGUAGE), vec![DEFAULT_LANGUAGE]);
     }
 
     #[test]
-    fn triggers_time_end_on_first_frame_in_game_with_one_frame() {
-        let mut game = Game::default();
-        game.objects.insert("Simple".to_string(), Object::default());
-        game.frames.total = FrameCount::Frames(1);
-        assert!(Trigger::Time(When::End)
-            .is_triggered(&game, "Simple", Mouse::default(), &mut TestRng::default())
-            .unwrap());
+    fn extracting_text_gives_every_text_a_key() {
+        let mut game_data = GameData {
+            intro_text: Some("Pop!".to_string()),
+            objects: vec![SerialiseObject {
+                name: "Sign".to_string(),
+                instructions: vec![Instruction {
+                    triggers: vec![Trigger::Time(When::Start)],
+                    actions: vec![
+                        draw_text("Hello", None),
+                        draw_text("Kept", Some("greeting")),
+                        draw_text("", None),
+                        Action::Random {
+                            random_actions: vec![draw_text("Bye", None)],
+                        },
+                        // Chosen by hand, so it can't be given to any other text
+                        draw_text("Later", Some("balloon.Sign 2")),
+                    ],
+                }],
+                ..SerialiseObject::default()
+            }],
+            ..GameData::default()
+        };
+
+        let template = game_data.extract_text("balloon");
+
+        let expected: StringTable = [
+            ("balloon.intro", "Pop!"),
+            ("balloon.Sign", "Hello"),
+            ("greeting", "Kept"),
+            ("balloon.Sign 3", "Bye"),
+            ("balloon.Sign 2", "Later"),
+        ]
+        .iter()
+        .map(|(key, text)| (key.to_string(), text.to_string()))
+        .collect();
+        assert_eq!(template, expected);
+        assert_eq!(game_data.intro_text_key.as_deref(), Some("balloon.intro"));
+        let keys: Vec<Option<String>> = drawn_texts(&game_data)
+            .into_iter()
+            .map(|(_, key)| key)
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                Some("balloon.Sign".to_string()),
+                Some("greeting".to_string()),
+                None,
+                Some("balloon.Sign 3".to_string()),
+                Some("balloon.Sign 2".to_string()),
+            ]
+        );
     }
 
     #[test]
-    fn wins_game_with_every_frame_trigger() {
+    fn nine_slice_sprites_keep_their_corners_when_stretched() {
         let mut game = Game::default();
-        let mut object = Object::default();
-        object.instructions.push(Instruction {
-            triggers: vec![],
-            actions: vec![Action::Win],
-        });
-        game.objects.insert("Simple".to_string(), object);
-        let actions =
-            check_triggers(&game, "Simple", Mouse::default(), &mut TestRng::default()).unwrap();
-        assert_eq!(actions.len(), 1);
-        assert_eq!(actions[0], Action::Win);
+        game.objects.insert(
+            "Button".to_string(),
+            Object {
+                sprite: Sprite::NineSlice {
+                    image: "button".to_string(),
+                    rect: ImageRect::new(10, 20, 30, 30),
+                    insets: SliceInsets::new(5, 10, 5, 10),
+                },
+                position: Vec2::new(200.0, 100.0),
+                size: Size::new(300.0, 100.0),
+                flip: Flip {
+                    horizontal: true,
+                    vertical: false,
+                },
+                ..Object::default()
+            },
+        );
+
+        let pieces: Vec<(ImageRect, AABB)> = game
+            .draw_commands(&HashMap::new(), |_| None::<FixedWidthFont>)
+            .into_iter()
+            .filter_map(|command| match command {
+                DrawCommand::Image {
+                    region: Some(region),
+                    transform,
+                    ..
+                } => Some((region, transform.area)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(pieces.len(), 9);
+        // Flipped, so the image's top left corner is drawn at the top right
+        assert_eq!(
+            pieces[0],
+            (
+                ImageRect::new(10, 20, 5, 10),
+                AABB::new(345.0, 50.0, 350.0, 60.0)
+            )
+        );
+        assert_eq!(
+            pieces[4],
+            (
+                ImageRect::new(15, 30, 20, 10),
+                AABB::new(55.0, 60.0, 345.0, 140.0)
+            )
+        );
+
+        // Borders shrink to fit an object smaller than them and the empty middle isn't drawn
+        let insets = SliceInsets::new(20, 0, 20, 0);
+        let transform = SpriteTransform::new(AABB::new(0.0, 0.0, 20.0, 10.0));
+        let areas: Vec<AABB> = transform
+            .nine_slice(ImageRect::new(0, 0, 50, 10), insets)
+            .map(|(_, transform)| transform.area)
+            .collect();
+        assert_eq!(
+            areas,
+            vec![
+                AABB::new(0.0, 0.0, 10.0, 10.0),
+                AABB::new(10.0, 0.0, 20.0, 10.0)
+            ]
+        );
     }
 
     #[test]
@@ -144,9 +1841,11 @@ mod tests {
         Action::Win
             .apply(
                 &mut game,
-                "Simple",
+                0,
+                None,
                 Mouse::default(),
                 &mut TestRng::default(),
+                &mut Vec::new(),
             )
             .unwrap();
 
@@ -169,7 +1868,8 @@ mod tests {
 
         move_object(
             &mut game,
-            "Simple",
+            &mut InstructionTable::default(),
+            0,
             Mouse::default(),
             &mut TestRng::default(),
         )
@@ -199,6 +1899,42 @@ mod tests {
         assert_eq!(game.objects["Simple"].switch, SwitchState::SwitchedOn);
     }
 
+    #[test]
+    fn restored_snapshot_plays_the_same_as_original() {
+        let saved_run: SavedRun =
+            bincode::deserialize(&fs::read("saved-runs/monkey-won.savedrun").unwrap()).unwrap();
+        let game_path = Path::new("../main-game").join(saved_run.path.replace('\\', "/"));
+
+        let mut rng = TestRng(RandomNumberGenerator::seeded(saved_run.seed));
+        let mut game = load_test_game(game_path.to_str().unwrap(), &mut rng).unwrap();
+        game.difficulty = saved_run.difficulty;
+        let mut inputs = saved_run.inputs;
+
+        for _ in 0..60 {
+            let mouse = if !inputs.is_empty() {
+                inputs.remove(0)
+            } else {
+                Mouse::default()
+            };
+            game.update_frame(mouse, &mut rng).unwrap();
+        }
+
+        let snapshot = Snapshot::take(&game, &rng);
+        let from_bytes = Snapshot::<TestRng>::from_bytes(&snapshot.to_bytes().unwrap()).unwrap();
+        let from_json = Snapshot::<TestRng>::from_json(&snapshot.to_json().unwrap()).unwrap();
+
+        play_test_game(&mut game, &mut inputs.clone(), &mut rng).unwrap();
+        let expected = serde_json::to_string(&game).unwrap();
+
+        for snapshot in [from_bytes, from_json] {
+            let mut restored_game = Game::default();
+            let mut restored_rng = TestRng::default();
+            snapshot.restore(&mut restored_game, &mut restored_rng);
+            play_test_game(&mut restored_game, &mut inputs.clone(), &mut restored_rng).unwrap();
+            assert_eq!(serde_json::to_string(&restored_game).unwrap(), expected);
+        }
+    }
+
     #[test]
     fn check_all_saved_runs() {
         let mut wrong_result_games = Vec::new();
@@ -208,7 +1944,7 @@ mod tests {
             let path = entry.path();
             if path.is_file() {
                 let saved_run: SavedRun = bincode::deserialize(&fs::read(&path).unwrap()).unwrap();
-                let game_path = Path::new("../main-game").join(saved_run.path);
+                let game_path = Path::new("../main-game").join(saved_run.path.replace('\\', "/"));
 
                 let mut rng = TestRng(RandomNumberGenerator::seeded(saved_run.seed));
                 let mut inputs = saved_run.inputs;
@@ -218,7 +1954,8 @@ mod tests {
                     game_path, saved_run.difficulty, saved_run.seed, saved_run.has_been_won
                 );
 
-                let mut game = load_test_game(&game_path.to_str().unwrap(), &mut rng).unwrap();
+                let mut game = load_test_game(game_path.to_str().unwrap(), &mut rng).unwrap();
+                game.difficulty = saved_run.difficulty;
 
                 play_test_game(&mut game, &mut inputs, &mut rng).unwrap();
                 if (game.status.current == WinStatus::Won
@@ -261,8 +1998,14 @@ const DEFAULT_GAME_LENGTH_IN_FRAMES: u32 = 60 * 4;
 
 impl Game {
     pub fn from_data(game_data: GameData, rng: &mut impl WeeRng) -> WeeResult<Game> {
+        let objects = Objects::from_serialised(game_data.objects, rng);
+        let table = InstructionTable::new(&objects, 0);
         Ok(Game {
-            objects: Objects::from_serialised(game_data.objects, rng),
+            objects,
+            table,
+            collisions: CollisionCache::default(),
+            masks: CollisionMasks::default(),
+            contacts: Vec::new(),
             background: game_data.background,
             frames: FrameInfo::from_length(game_data.length),
             status: GameStatus {
@@ -271,10 +2014,41 @@ impl Game {
             },
             intro_text: game_data.intro_text.as_deref().unwrap_or("").to_string(),
             effect: Effect::None,
+            timed_effects: Vec::new(),
             difficulty: 1,
+            layer_sorting: game_data.layer_sorting,
+            topmost_mouse_over: game_data.topmost_mouse_over,
+            hover_order: Vec::new(),
+            objects_generation: 0,
+            held_clicks: Vec::new(),
         })
     }
 
+    pub fn screen_effects(&self) -> ScreenEffects {
+        effects::screen_effects(&self.timed_effects)
+    }
+
+    pub fn draw_order(&self) -> Vec<usize> {
+        layers::objects_draw_order(&self.objects, &self.layer_sorting)
+    }
+
+    fn contains_point(&self, index: usize, point: Vec2) -> bool {
+        self.collisions.might_contain(index, point) && {
+            let shape = self.collisions.shape(&self.objects, index);
+            shape.contains_point(point)
+                && mask::contains_point(&self.objects[index], shape, point, &self.masks)
+        }
+    }
+
+    // Only the topmost object with a mouse trigger counts as being under the mouse
+    fn is_covered_at(&self, index: usize, point: Vec2) -> bool {
+        self.hover_order
+            .iter()
+            .skip_while(|&&other| other != index)
+            .skip(1)
+            .any(|&above| self.contains_point(above, point))
+    }
+
     fn update_win_status(&mut self) {
         self.status.current = self.status.next_frame;
         self.status.next_frame = match self.status.next_frame {
@@ -284,47 +2058,187 @@ impl Game {
         };
     }
 
+    // Call after renaming or reordering objects, or changing their instructions, from outside the
+    // game so the next frame compiles them again. Adding or removing objects is noticed anyway
+    pub fn objects_changed(&mut self) {
+        self.objects_generation = self.objects_generation.wrapping_add(1);
+    }
+
+    // Only the latest press and release are kept, so a click is never half lost
+    fn hold_click(&mut self, state: ButtonState) {
+        if let ButtonState::Press | ButtonState::Release = state {
+            if self.held_clicks.len() == 2 {
+                self.held_clicks.remove(0);
+            }
+            self.held_clicks.push(state);
+        }
+    }
+
     pub fn update_frame(
         &mut self,
-        mouse: Mouse,
+        mut mouse: Mouse,
         rng: &mut impl WeeRng,
     ) -> WeeResult<Vec<WorldAction>> {
-        // TODO: Optimise this line by doing it only at the start of the game
-        let keys: Vec<String> = self.objects.keys().cloned().collect();
+        effects::advance_effects(&mut self.timed_effects);
+        if effects::is_slowed(&self.timed_effects) {
+            self.frames.slowed += 1;
+            self.hold_click(mouse.state);
+            return Ok(Vec::new());
+        }
+        // Held back clicks reach the frames that run next, one each
+        if !self.held_clicks.is_empty() {
+            self.hold_click(mouse.state);
+            mouse.state = self.held_clicks.remove(0);
+        }
+
+        if !self.table.is_for(&self.objects, self.objects_generation) {
+            self.table = InstructionTable::new(&self.objects, self.objects_generation);
+        }
+        if !self.table.has_contacts_for(&self.contacts) {
+            self.contacts = self.table.new_contacts();
+        }
+        self.hover_order.clear();
+        if self.topmost_mouse_over && self.table.mouse_targets.len() > 1 {
+            self.hover_order
+                .extend_from_slice(&self.table.mouse_targets);
+            layers::sort_objects(&mut self.hover_order, &self.objects, &self.layer_sorting);
+        }
+
+        for object in self.objects.values_mut() {
+            object.cache_sprite_shape();
+        }
+        self.collisions.rebuild(&self.objects);
+
+        let mut table = std::mem::take(&mut self.table);
+        let world_actions = self.run_instructions(&mut table, mouse, rng);
+        self.table = table;
+        let world_actions = world_actions?;
+
+        self.update_win_status();
 
+        self.frames.ran += 1;
+
+        Ok(world_actions)
+    }
+
+    fn run_instructions(
+        &mut self,
+        table: &mut InstructionTable,
+        mouse: Mouse,
+        rng: &mut impl WeeRng,
+    ) -> WeeResult<Vec<WorldAction>> {
         let mut world_actions = Vec::new();
         if self.effect == Effect::Freeze {
-            for name in keys.iter() {
-                self.objects[name].update_timer();
-                let actions = check_triggers(self, name, mouse, rng)?;
-                for action in actions {
-                    if action == Action::EndEarly {
-                        world_actions.push(WorldAction::EndEarly);
+            for index in 0..self.objects.len() {
+                self.objects[index].update_timer();
+                check_triggers(
+                    self,
+                    index,
+                    &table.objects[index],
+                    &mut table.triggered,
+                    mouse,
+                    rng,
+                )?;
+                self.update_contacts(table, index)?;
+                for &instruction in table.triggered.iter() {
+                    for action in table.objects[index][instruction].actions.iter() {
+                        if let CompiledAction::Action {
+                            action: Action::EndEarly,
+                            ..
+                        } = action
+                        {
+                            world_actions.push(WorldAction::EndEarly);
+                        }
                     }
                 }
             }
         } else {
-            for name in keys.iter() {
-                let old_switch = self.objects[name].switch;
-                self.objects[name].update_timer();
-                let actions = check_triggers(self, name, mouse, rng)?;
-                for action in actions {
-                    let mut new_world_actions = action.apply(self, name, mouse, rng)?;
-                    world_actions.append(&mut new_world_actions);
+            for index in 0..self.objects.len() {
+                let old_switch = self.objects[index].switch;
+                self.objects[index].update_timer();
+                check_triggers(
+                    self,
+                    index,
+                    &table.objects[index],
+                    &mut table.triggered,
+                    mouse,
+                    rng,
+                )?;
+                self.update_contacts(table, index)?;
+                table.queued_motions.clear();
+                for &instruction in table.triggered.iter() {
+                    for action in table.objects[index][instruction].actions.iter() {
+                        action.apply(
+                            self,
+                            index,
+                            mouse,
+                            rng,
+                            &mut table.queued_motions,
+                            &mut world_actions,
+                        )?;
+                    }
                 }
-                if let Some(sprite) = self.objects[name].animation.update() {
-                    self.objects[name].sprite = sprite;
+                if let Some(sprite) = self.objects[index].animation.update() {
+                    self.objects[index].sprite = sprite;
                 }
-                move_object(self, name, mouse, rng)?;
-                self.objects[name].update_switch(old_switch);
+                move_object(self, table, index, mouse, rng)?;
+                self.objects[index].update_switch(old_switch);
+                self.collisions.update(&self.objects, index);
             }
         }
 
-        self.update_win_status();
+        Ok(world_actions)
+    }
 
-        self.frames.ran += 1;
+    fn update_contacts(&mut self, table: &InstructionTable, index: usize) -> WeeResult<()> {
+        for (i, contact) in table.contacts[index].iter().enumerate() {
+            let touching = is_touching(self, index, &contact.with, contact.other)?;
+            self.contacts[index][i] = touching;
+        }
+        Ok(())
+    }
 
-        Ok(world_actions)
+    pub fn set_collision_masks(&mut self, masks: CollisionMasks) {
+        self.masks = masks;
+    }
+
+    fn object_name(&self, index: usize) -> &str {
+        self.objects
+            .get_index(index)
+            .map(|(name, _)| name.as_str())
+            .unwrap_or("")
+    }
+}
+
+impl<R: Clone + Serialize + DeserializeOwned> Snapshot<R> {
+    pub fn take(game: &Game, rng: &R) -> Snapshot<R> {
+        Snapshot {
+            game: game.clone(),
+            rng: rng.clone(),
+        }
+    }
+
+    pub fn restore(self, game: &mut Game, rng: &mut R) {
+        let masks = std::mem::take(&mut game.masks);
+        *game = self.game;
+        game.masks = masks;
+        *rng = self.rng;
+    }
+
+    pub fn to_bytes(&self) -> WeeResult<Vec<u8>> {
+        Ok(bincode::serialize(self)?)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> WeeResult<Snapshot<R>> {
+        Ok(bincode::deserialize(bytes)?)
+    }
+
+    pub fn to_json(&self) -> WeeResult<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    pub fn from_json(text: &str) -> WeeResult<Snapshot<R>> {
+        Ok(serde_json::from_str(text)?)
     }
 }
 
@@ -332,12 +2246,22 @@ impl Default for Game {
     fn default() -> Game {
         Game {
             objects: Objects::new(),
+            table: InstructionTable::default(),
+            collisions: CollisionCache::default(),
+            masks: CollisionMasks::default(),
+            contacts: Vec::new(),
             background: Vec::new(),
             frames: FrameInfo::default(),
             status: GameStatus::default(),
             intro_text: "".to_string(),
             effect: Effect::None,
+            timed_effects: Vec::new(),
             difficulty: 1,
+            layer_sorting: LayerSorting::new(),
+            topmost_mouse_over: true,
+            hover_order: Vec::new(),
+            objects_generation: 0,
+            held_clicks: Vec::new(),
         }
     }
 }
@@ -350,15 +2274,19 @@ impl Default for Object {
             size: Size::new(100.0, 100.0),
             angle: 0.0,
             origin: None,
-            collision_area: None,
+            collision_shape: None,
+            collision_mode: CollisionMode::Area,
             flip: Flip::default(),
             layer: 0,
+            depth: 0.0,
             switch: SwitchState::Off,
+            tags: Vec::new(),
             instructions: Vec::new(),
             queued_motion: Vec::new(),
             active_motion: ActiveMotion::Stop,
             timer: None,
             animation: AnimationStatus::None,
+            sprite_shape: None,
         }
     }
 }
@@ -386,11 +2314,195 @@ impl ObjectList for Objects {
     }
 }
 
+impl InstructionTable {
+    fn new(objects: &Objects, generation: u32) -> InstructionTable {
+        let mut table = InstructionTable {
+            generation,
+            ..InstructionTable::default()
+        };
+        for object in objects.values() {
+            let mut contacts = Vec::new();
+            let instructions = object
+                .instructions
+                .iter()
+                .map(|instruction| CompiledInstruction {
+                    triggers: instruction
+                        .triggers
+                        .iter()
+                        .map(|trigger| {
+                            let other = find_index(objects, trigger.object_name());
+                            CompiledTrigger {
+                                trigger: trigger.clone(),
+                                targets: TriggerTargets {
+                                    other,
+                                    compared: find_index(objects, trigger.compared_object_name()),
+                                    contact: compile_contact(trigger, other, &mut contacts),
+                                },
+                            }
+                        })
+                        .collect(),
+                    actions: instruction
+                        .actions
+                        .iter()
+                        .map(|action| table.compile_action(action, objects))
+                        .collect(),
+                })
+                .collect();
+            table.objects.push(instructions);
+            table.contacts.push(contacts);
+        }
+        table.mouse_targets = table
+            .objects
+            .iter()
+            .flatten()
+            .flat_map(|instruction| instruction.triggers.iter())
+            .filter_map(|compiled| match compiled.trigger {
+                Trigger::Input(Input::Mouse {
+                    over: MouseOver::Object { .. },
+                    ..
+                }) => compiled.targets.other,
+                _ => None,
+            })
+            .collect();
+        table.mouse_targets.sort_unstable();
+        table.mouse_targets.dedup();
+        table
+    }
+
+    fn is_for(&self, objects: &Objects, generation: u32) -> bool {
+        self.generation == generation && self.objects.len() == objects.len()
+    }
+
+    fn has_contacts_for(&self, contacts: &[Vec<bool>]) -> bool {
+        self.contacts.len() == contacts.len()
+            && self
+                .contacts
+                .iter()
+                .zip(contacts)
+                .all(|(table, game)| table.len() == game.len())
+    }
+
+    fn new_contacts(&self) -> Vec<Vec<bool>> {
+        self.contacts
+            .iter()
+            .map(|contacts| vec![false; contacts.len()])
+            .collect()
+    }
+
+    fn compile_action(&mut self, action: &Action, objects: &Objects) -> CompiledAction {
+        match action {
+            Action::Motion(motion) => {
+                self.motions
+                    .push(CompiledMotion::new(motion.clone(), objects));
+                CompiledAction::Motion(self.motions.len() - 1)
+            }
+            Action::Random { random_actions } => CompiledAction::Random(
+                random_actions
+                    .iter()
+                    .map(|action| self.compile_action(action, objects))
+                    .collect(),
+            ),
+            _ => CompiledAction::Action {
+                action: action.clone(),
+                other: find_index(objects, action.object_name()),
+            },
+        }
+    }
+}
+
+impl CompiledMotion {
+    fn new(motion: Motion, objects: &Objects) -> CompiledMotion {
+        let directions = match &motion {
+            Motion::GoStraight { direction, .. }
+            | Motion::Accelerate(Acceleration::Continuous { direction, .. })
+            | Motion::Roam {
+                movement_type:
+                    MovementType::Reflect {
+                        initial_direction: direction,
+                        ..
+                    },
+                ..
+            } => direction.sorted_directions(),
+            _ => Vec::new(),
+        };
+        let other = find_index(objects, motion.object_name());
+        CompiledMotion {
+            motion,
+            other,
+            directions,
+        }
+    }
+}
+
+fn compile_contact(
+    trigger: &Trigger,
+    other: Option<usize>,
+    contacts: &mut Vec<Contact>,
+) -> Option<usize> {
+    match trigger {
+        Trigger::Collision(_, CollisionPhase::Stay) => None,
+        Trigger::Collision(with, _) => {
+            let existing = contacts.iter().position(|contact| contact.with == *with);
+            Some(existing.unwrap_or_else(|| {
+                contacts.push(Contact {
+                    with: with.clone(),
+                    other,
+                });
+                contacts.len() - 1
+            }))
+        }
+        _ => None,
+    }
+}
+
+fn find_index(objects: &Objects, name: Option<&str>) -> Option<usize> {
+    name.and_then(|name| objects.get_index_of(name))
+}
+
+fn found_object(other: Option<usize>, name: &str) -> WeeResult<usize> {
+    other.ok_or_else(|| format!("Couldn't find object with name {}", name).into())
+}
+
 impl Trigger {
+    fn object_name(&self) -> Option<&str> {
+        match self {
+            Trigger::Collision(CollisionWith::Object { name }, _) => Some(name),
+            Trigger::Input(Input::Mouse {
+                over: MouseOver::Object { name },
+                ..
+            }) => Some(name),
+            Trigger::CheckProperty { name, .. } => Some(name),
+            Trigger::Distance {
+                from: DistanceEnd::Object { name },
+                ..
+            } => Some(name),
+            _ => None,
+        }
+    }
+
+    fn compared_object_name(&self) -> Option<&str> {
+        match self {
+            Trigger::Distance {
+                to: DistanceEnd::Object { name },
+                ..
+            } => Some(name),
+            Trigger::CheckProperty {
+                check:
+                    PropertyCheck::Compare {
+                        to: CompareTo::Object { name, .. },
+                        ..
+                    },
+                ..
+            } => Some(name),
+            _ => None,
+        }
+    }
+
     fn is_triggered(
         &self,
         game: &Game,
-        name: &str,
+        index: usize,
+        targets: TriggerTargets,
         mouse: Mouse,
         rng: &mut impl WeeRng,
     ) -> WeeResult<bool> {
@@ -398,23 +2510,69 @@ impl Trigger {
             pos.x >= area.min.x && pos.y >= area.min.y && pos.x < area.max.x && pos.y < area.max.y
         };
         let is_mouse_in_area = |mouse: Mouse, area| is_point_in_area(mouse.position, area);
-        let c2v = |v: Vec2| c2::Vec2::new(v.x, v.y);
-        let object = game.objects.get(name).ok_or("Couldn't find object")?;
+        let other = targets.other;
 
         let triggered = match self {
             Trigger::Time(When::Start) => game.frames.ran == 0,
             Trigger::Time(When::End) => game.frames.is_final(),
             Trigger::Time(When::Exact { time }) => game.frames.ran == *time,
             Trigger::Time(When::Random { .. }) => false,
-            Trigger::Collision(CollisionWith::Object { name: other_name }) => {
-                let other_obj = game.objects.get_obj(other_name)?;
-
-                object.poly().collides_with(&other_obj.poly())
+            Trigger::Collision(with, phase) => {
+                let touching = is_touching(game, index, with, other)?;
+                let was_touching = targets
+                    .contact
+                    .map(|contact| game.contacts[index][contact])
+                    .unwrap_or(false);
+                match phase {
+                    CollisionPhase::Enter => touching && !was_touching,
+                    CollisionPhase::Stay => touching,
+                    CollisionPhase::Exit => !touching && was_touching,
+                }
             }
-            Trigger::Collision(CollisionWith::Area(area)) => {
-                let area = c2::AABB::new(c2v(area.min), c2v(area.max));
-
-                object.poly().collides_with(&area)
+            Trigger::Bounds { area, condition } => {
+                let shape = game.collisions.shape(&game.objects, index);
+                let bounds = shape.bounds();
+                let inside = bounds.min.x >= area.min.x
+                    && bounds.min.y >= area.min.y
+                    && bounds.max.x <= area.max.x
+                    && bounds.max.y <= area.max.y;
+                let outside = !shape.collides_with_area(*area);
+                match condition {
+                    BoundsCondition::FullyInside => inside,
+                    BoundsCondition::FullyOutside => outside,
+                    BoundsCondition::TouchingEdge => !inside && !outside,
+                    BoundsCondition::CrossedEdge(Side::Top) => bounds.min.y < area.min.y,
+                    BoundsCondition::CrossedEdge(Side::Bottom) => bounds.max.y > area.max.y,
+                    BoundsCondition::CrossedEdge(Side::Left) => bounds.min.x < area.min.x,
+                    BoundsCondition::CrossedEdge(Side::Right) => bounds.max.x > area.max.x,
+                }
+            }
+            Trigger::Distance {
+                from,
+                to,
+                measure,
+                comparison,
+                value,
+            } => {
+                let mut nearest: Option<f32> = None;
+                for_each_distance_end(game, from, targets.other, mouse, |from| {
+                    for_each_distance_end(game, to, targets.compared, mouse, |to| {
+                        // An object is never measured against itself
+                        if from.index.is_some() && from.index == to.index {
+                            return Ok(());
+                        }
+                        let distance = match measure {
+                            DistanceMeasure::Centre => (from.centre - to.centre).magnitude(),
+                            DistanceMeasure::Edge => from.shape.distance_to(&to.shape),
+                        };
+                        nearest = Some(nearest.map_or(distance, |nearest| nearest.min(distance)));
+                        Ok(())
+                    })
+                })?;
+                match nearest {
+                    Some(nearest) => comparison.compare(nearest, *value),
+                    None => false,
+                }
             }
             Trigger::WinStatus(win_status) => match win_status {
                 WinStatus::Won => {
@@ -442,14 +2600,9 @@ impl Trigger {
             Trigger::Input(Input::Mouse { over, interaction }) => {
                 let is_over = match over {
                     MouseOver::Object { name: other_name } => {
-                        let other_obj = game.objects.get_obj(other_name)?;
-                        other_obj
-                            .poly()
-                            .gjk(&c2::Circle::new(c2v(mouse.position), 1.0))
-                            .use_radius(false)
-                            .run()
-                            .distance()
-                            == 0.0
+                        let other = found_object(other, other_name)?;
+                        game.contains_point(other, mouse.position)
+                            && !game.is_covered_at(other, mouse.position)
                     }
                     MouseOver::Area(area) => is_mouse_in_area(mouse, *area),
                     MouseOver::Anywhere => true,
@@ -464,7 +2617,7 @@ impl Trigger {
                 name: object_name,
                 check,
             } => {
-                let obj = game.objects.get_obj(object_name)?;
+                let obj = &game.objects[found_object(other, object_name)?];
                 match check {
                     PropertyCheck::Switch(switch_state) => {
                         if *switch_state == SwitchState::Off {
@@ -479,10 +2632,26 @@ impl Trigger {
                     PropertyCheck::FinishedAnimation => {
                         matches!(obj.animation, AnimationStatus::Finished)
                     }
+                    PropertyCheck::AnimationFrame { index } => {
+                        obj.animation.frame() == Some(*index)
+                    }
                     PropertyCheck::Timer => match obj.timer {
                         Some(alarm) => alarm == 0,
                         None => false,
                     },
+                    PropertyCheck::Compare {
+                        property,
+                        comparison,
+                        to,
+                    } => {
+                        let value = match to {
+                            CompareTo::Value(value) => *value,
+                            CompareTo::Object { name, property } => game.objects
+                                [found_object(targets.compared, name)?]
+                            .property_value(*property),
+                        };
+                        comparison.compare(obj.property_value(*property), value)
+                    }
                 }
             }
             Trigger::Random { chance } => {
@@ -494,39 +2663,133 @@ impl Trigger {
         Ok(triggered)
     }
 }
-trait AllOk: Iterator {
-    fn all_ok<F>(&mut self, f: F) -> WeeResult<bool>
-    where
-        Self: Sized,
-        F: FnMut(Self::Item) -> WeeResult<bool>,
-    {
-        Ok(self
-            .map(f)
-            .collect::<WeeResult<Vec<bool>>>()?
-            .into_iter()
-            .all(|t| t))
+
+fn is_touching(
+    game: &Game,
+    index: usize,
+    with: &CollisionWith,
+    other: Option<usize>,
+) -> WeeResult<bool> {
+    let touching = match with {
+        CollisionWith::Object { name: other_name } => {
+            let other = found_object(other, other_name)?;
+
+            game.collisions.might_collide(index, other) && {
+                let shape = game.collisions.shape(&game.objects, index);
+                let other_shape = game.collisions.shape(&game.objects, other);
+                shape.collides_with(&other_shape)
+                    && mask::objects_overlap(
+                        &game.objects[index],
+                        shape,
+                        &game.objects[other],
+                        other_shape,
+                        &game.masks,
+                    )
+            }
+        }
+        CollisionWith::Area(area) => {
+            game.collisions.might_collide_with_area(index, *area) && {
+                let shape = game.collisions.shape(&game.objects, index);
+                shape.collides_with_area(*area)
+                    && mask::overlaps_area(&game.objects[index], shape, *area, &game.masks)
+            }
+        }
+    };
+    Ok(touching)
+}
+
+#[derive(Copy, Clone)]
+struct DistanceEndShape {
+    index: Option<usize>,
+    centre: Vec2,
+    shape: WorldShape,
+}
+
+// Calls f with the centre and shape of everything at one end of a distance trigger
+fn for_each_distance_end(
+    game: &Game,
+    end: &DistanceEnd,
+    other: Option<usize>,
+    mouse: Mouse,
+    mut f: impl FnMut(DistanceEndShape) -> WeeResult<()>,
+) -> WeeResult<()> {
+    let point = |position: Vec2| DistanceEndShape {
+        index: None,
+        centre: position,
+        shape: WorldShape::Circle {
+            centre: position,
+            radius: 0.0,
+        },
+    };
+    let object = |index: usize| DistanceEndShape {
+        index: Some(index),
+        centre: game.objects[index].position,
+        shape: game.collisions.shape(&game.objects, index),
+    };
+    match end {
+        DistanceEnd::Object { name } => f(object(found_object(other, name)?)),
+        DistanceEnd::Tag { tag } => {
+            for (index, tagged) in game.objects.values().enumerate() {
+                if tagged.tags.contains(tag) {
+                    f(object(index))?;
+                }
+            }
+            Ok(())
+        }
+        DistanceEnd::Mouse => f(point(mouse.position)),
+        DistanceEnd::Point(position) => f(point(*position)),
     }
 }
 
-impl<T: ?Sized> AllOk for T where T: Iterator {}
+pub fn screen_area() -> AABB {
+    AABB::new(0.0, 0.0, PROJECTION_WIDTH, PROJECTION_HEIGHT)
+}
+
+// Games saved before collision phases only store what the object collides with
+fn deserialize_collision<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<(CollisionWith, CollisionPhase), D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum SavedCollision {
+        Phase(CollisionWith, CollisionPhase),
+        Level(CollisionWith),
+    }
+
+    if deserializer.is_human_readable() {
+        Ok(match SavedCollision::deserialize(deserializer)? {
+            SavedCollision::Phase(with, phase) => (with, phase),
+            SavedCollision::Level(with) => (with, CollisionPhase::Stay),
+        })
+    } else {
+        <(CollisionWith, CollisionPhase)>::deserialize(deserializer)
+    }
+}
 
 fn check_triggers(
     game: &Game,
-    name: &str,
+    index: usize,
+    instructions: &[CompiledInstruction],
+    triggered: &mut Vec<usize>,
     mouse: Mouse,
     rng: &mut impl WeeRng,
-) -> WeeResult<Vec<Action>> {
-    let mut actions = Vec::new();
-    for instruction in game.objects[name].instructions.iter() {
-        if instruction
-            .triggers
-            .iter()
-            .all_ok(|trigger| trigger.is_triggered(game, name, mouse, rng))?
-        {
-            actions.extend(instruction.actions.clone())
+) -> WeeResult<()> {
+    triggered.clear();
+    for (i, instruction) in instructions.iter().enumerate() {
+        let mut all_triggered = true;
+        for trigger in instruction.triggers.iter() {
+            if !trigger
+                .trigger
+                .is_triggered(game, index, trigger.targets, mouse, rng)?
+            {
+                all_triggered = false;
+            }
+        }
+        if all_triggered {
+            triggered.push(i);
         }
     }
-    Ok(actions)
+    Ok(())
 }
 
 impl SerialiseObject {
@@ -534,7 +2797,7 @@ impl SerialiseObject {
         fn replace_text_in_action(action: &mut Action, text_replacements: &[(&str, String)]) {
             if let Action::DrawText { text, .. } = action {
                 for (before, after) in text_replacements {
-                    *text = text.replace(before, &after);
+                    *text = text.replace(before, after);
                 }
             } else if let Action::Random { random_actions } = action {
                 for action in random_actions {
@@ -545,7 +2808,7 @@ impl SerialiseObject {
 
         for instruction in self.instructions.iter_mut() {
             for action in instruction.actions.iter_mut() {
-                replace_text_in_action(action, &text_replacements);
+                replace_text_in_action(action, text_replacements);
             }
         }
     }
@@ -559,10 +2822,13 @@ impl SerialiseObject {
             size: self.size,
             angle: self.angle,
             origin: self.origin,
-            collision_area: self.collision_area,
+            collision_shape: self.collision_shape,
+            collision_mode: self.collision_mode,
             flip: self.flip,
             layer: self.layer,
+            depth: self.depth,
             switch,
+            tags: self.tags,
             instructions: self.instructions,
             ..Default::default()
         };
@@ -590,6 +2856,7 @@ impl Default for FrameInfo {
             to_run: 0,
             total_time_elapsed: 0.0,
             previous_frame_time: 0.0,
+            slowed: 0,
         }
     }
 }
@@ -608,6 +2875,7 @@ impl FrameInfo {
             to_run: 0,
             total_time_elapsed: 0.0,
             previous_frame_time: 0.0,
+            slowed: 0,
         }
     }
 
@@ -647,6 +2915,19 @@ impl Switch {
     }
 }
 
+// Shape sprites collide with their shape unless they've been given their own collision shape
+fn active_collision_shape<'a>(
+    collision_shape: &'a Option<CollisionShape>,
+    sprite: &Sprite,
+    size: Size,
+) -> Option<Cow<'a, CollisionShape>> {
+    match (collision_shape, sprite) {
+        (Some(shape), _) => Some(Cow::Borrowed(shape)),
+        (None, Sprite::Shape(sprite)) => Some(Cow::Owned(sprite.collision_shape(size))),
+        (None, _) => None,
+    }
+}
+
 impl Object {
     pub fn poly(&self) -> c2::Poly {
         let collision_aabb = self.collision_aabb();
@@ -672,23 +2953,75 @@ impl Object {
         c2::Poly::from_slice(&points)
     }
 
-    pub fn collision_aabb(&self) -> AABB {
-        match &self.collision_area {
-            Some(mut area) => {
-                if self.flip.horizontal {
-                    let difference_from_left = area.min.x;
-                    let difference_from_right = self.size.width - area.max.x;
-                    area.min.x = difference_from_right;
-                    area.max.x = self.size.width - difference_from_left;
-                }
-                if self.flip.vertical {
-                    let difference_from_top = area.min.y;
-                    let difference_from_bottom = self.size.height - area.max.y;
-                    area.min.y = difference_from_bottom;
-                    area.max.y = self.size.height - difference_from_top;
-                }
-                area.move_position(self.top_left())
+    pub fn active_collision_shape(&self) -> Option<Cow<'_, CollisionShape>> {
+        match (&self.collision_shape, &self.sprite, &self.sprite_shape) {
+            (None, Sprite::Shape(sprite), Some(cached)) if cached.is_for(sprite, self.size) => {
+                Some(Cow::Borrowed(&cached.shape))
+            }
+            _ => active_collision_shape(&self.collision_shape, &self.sprite, self.size),
+        }
+    }
+
+    fn cache_sprite_shape(&mut self) {
+        let sprite = match (&self.collision_shape, &self.sprite) {
+            (None, Sprite::Shape(sprite)) => sprite,
+            _ => {
+                self.sprite_shape = None;
+                return;
+            }
+        };
+        let is_current =
+            matches!(&self.sprite_shape, Some(cached) if cached.is_for(sprite, self.size));
+        if !is_current {
+            self.sprite_shape = Some(SpriteCollisionShape {
+                sprite: sprite.clone(),
+                size: self.size,
+                shape: sprite.collision_shape(self.size),
+            });
+        }
+    }
+
+    pub fn shape(&self) -> WorldShape {
+        match self.active_collision_shape().as_deref() {
+            None | Some(CollisionShape::AABB(_)) => WorldShape::Poly(self.poly()),
+            Some(CollisionShape::Polygon { points }) if points.len() < 3 => {
+                WorldShape::Poly(self.poly())
+            }
+            Some(shape) => {
+                let top_left = self.top_left();
+                let origin = self.origin_in_world();
+                let angle = self.angle.to_radians();
+                let c = angle.cos();
+                let s = angle.sin();
+                shape::world_shape(&shape.flipped(self.flip, self.size), |point| {
+                    let point = point + top_left - origin;
+                    Vec2::new(
+                        point.x * c - point.y * s + origin.x,
+                        point.x * s + point.y * c + origin.y,
+                    )
+                })
             }
+        }
+    }
+
+    pub fn property_value(&self, property: NumericProperty) -> f32 {
+        match property {
+            NumericProperty::X => self.position.x,
+            NumericProperty::Y => self.position.y,
+            NumericProperty::Width => self.size.width,
+            NumericProperty::Height => self.size.height,
+            NumericProperty::Angle => self.angle,
+            NumericProperty::Layer => self.layer as f32,
+            NumericProperty::Speed => self.active_motion.speed(),
+        }
+    }
+
+    pub fn collision_aabb(&self) -> AABB {
+        match self.active_collision_shape() {
+            Some(shape) => shape
+                .flipped(self.flip, self.size)
+                .bounds()
+                .move_position(self.top_left()),
             None => AABB {
                 min: self.top_left(),
                 max: self.bottom_right(),
@@ -769,8 +3102,25 @@ pub struct DrawnText {
     pub text: String,
     pub font: String,
     pub colour: Colour,
-    //resize: TextResize,
+    pub resize: TextResize,
     pub justify: JustifyText,
+    pub layout: TextLayout,
+}
+
+impl DrawnText {
+    // Text that the object is resized to match keeps its natural size, so only text fitted to
+    // the object is wrapped or shrunk
+    pub fn lay_out(&self, area: AABB, metrics: &impl FontMetrics) -> LaidOutText {
+        let layout = match self.resize {
+            TextResize::MatchText => TextLayout {
+                wrap: false,
+                shrink_to_fit: false,
+                ..self.layout
+            },
+            TextResize::MatchObject => self.layout,
+        };
+        layout.lay_out(&self.text, area, self.justify, metrics)
+    }
 }
 
 pub trait WeeRng {
@@ -780,14 +3130,56 @@ pub trait WeeRng {
     fn coin_flip(&mut self) -> bool;
 }
 
+impl CompiledAction {
+    fn apply(
+        &self,
+        game: &mut Game,
+        index: usize,
+        mouse: Mouse,
+        rng: &mut impl WeeRng,
+        queued_motions: &mut Vec<usize>,
+        world_actions: &mut Vec<WorldAction>,
+    ) -> WeeResult<()> {
+        match self {
+            CompiledAction::Action { action, other } => {
+                action.apply(game, index, *other, mouse, rng, world_actions)
+            }
+            CompiledAction::Motion(motion) => {
+                queued_motions.push(*motion);
+                Ok(())
+            }
+            CompiledAction::Random(random_actions) => {
+                if let Some(action) = rng.random_in_slice(random_actions) {
+                    action.apply(game, index, mouse, rng, queued_motions, world_actions)
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
+}
+
 impl Action {
+    fn object_name(&self) -> Option<&str> {
+        match self {
+            Action::SetProperty(PropertySetter::Angle(AngleSetter::Match { name }))
+            | Action::SetProperty(PropertySetter::Angle(AngleSetter::RotateToObject { name })) => {
+                Some(name)
+            }
+            Action::Motion(motion) => motion.object_name(),
+            _ => None,
+        }
+    }
+
     fn apply(
         &self,
         game: &mut Game,
-        name: &str,
+        index: usize,
+        other: Option<usize>,
         mouse: Mouse,
         rng: &mut impl WeeRng,
-    ) -> WeeResult<Vec<WorldAction>> {
+        world_actions: &mut Vec<WorldAction>,
+    ) -> WeeResult<()> {
         let try_to_set_status = |status: &mut GameStatus, opposite, next_frame| {
             *status = match status.current {
                 WinStatus::NotYetWon | WinStatus::NotYetLost => {
@@ -809,10 +3201,9 @@ impl Action {
         let try_to_lose = |status| {
             try_to_set_status(status, WinStatus::JustWon, WinStatus::JustLost);
         };
-        let mut world_actions = Vec::new();
         match self {
             Action::Motion(motion) => {
-                game.objects[name].queued_motion.push(motion.clone());
+                game.objects[index].queued_motion.push(motion.clone());
             }
             Action::Win => {
                 try_to_win(&mut game.status);
@@ -820,9 +3211,14 @@ impl Action {
             Action::Lose => {
                 try_to_lose(&mut game.status);
             }
-            Action::Effect(new_effect) => {
-                game.effect = *new_effect;
-            }
+            Action::Effect(new_effect) => match new_effect {
+                Effect::Freeze => game.effect = Effect::Freeze,
+                Effect::None => {
+                    game.effect = Effect::None;
+                    game.timed_effects.clear();
+                }
+                _ => effects::start_effect(&mut game.timed_effects, *new_effect),
+            },
             Action::PlaySound { name: sound_name } => {
                 world_actions.push(WorldAction::PlaySound {
                     name: sound_name.clone(),
@@ -835,41 +3231,54 @@ impl Action {
                 animation_type,
                 sprites,
                 speed,
+                frame_durations,
+                playback,
+                start_frame,
             } => {
-                game.objects[name].animation =
-                    AnimationStatus::start(*animation_type, sprites, *speed);
-
-                if let Some(sprite) = sprites.get(0).cloned() {
-                    game.objects[name].sprite = sprite;
+                let animation = AnimationStatus::start(
+                    *animation_type,
+                    sprites,
+                    frame_durations,
+                    *speed,
+                    *playback,
+                    *start_frame,
+                );
+                if let Some(sprite) = animation.sprite() {
+                    game.objects[index].sprite = sprite.clone();
                 }
+                game.objects[index].animation = animation;
             }
             Action::DrawText {
                 text,
                 font,
                 colour,
-                resize: _resize,
+                resize,
                 justify,
+                layout,
+                ..
             } => {
                 world_actions.push(WorldAction::DrawText {
-                    name: name.to_string(),
+                    name: game.object_name(index).to_string(),
                     text: DrawnText {
                         text: text.clone(),
                         font: font.clone(),
                         colour: *colour,
+                        resize: *resize,
                         justify: *justify,
+                        layout: *layout,
                     },
                 });
             }
             Action::SetProperty(PropertySetter::Angle(angle_setter)) => {
-                game.objects[name].angle = match angle_setter {
+                game.objects[index].angle = match angle_setter {
                     AngleSetter::Value(value) => *value,
-                    AngleSetter::Increase(value) => game.objects[name].angle + value,
-                    AngleSetter::Decrease(value) => game.objects[name].angle - value,
+                    AngleSetter::Increase(value) => game.objects[index].angle + value,
+                    AngleSetter::Decrease(value) => game.objects[index].angle - value,
                     AngleSetter::Match { name: other_name } => {
-                        game.objects.get_obj(other_name)?.angle
+                        game.objects[found_object(other, other_name)?].angle
                     }
                     AngleSetter::Clamp { min, max } => {
-                        let mut angle = game.objects[name].angle;
+                        let mut angle = game.objects[index].angle;
                         if angle < 0.0 {
                             angle += 360.0;
                         }
@@ -903,20 +3312,20 @@ impl Action {
                         clamp_degrees(angle, *min, *max)
                     }
                     AngleSetter::RotateToObject { name: other_name } => {
-                        let other_centre = game.objects.get_obj(other_name)?.position;
-                        let centre = game.objects[name].origin_in_world();
+                        let other_centre = game.objects[found_object(other, other_name)?].position;
+                        let centre = game.objects[index].origin_in_world();
                         (other_centre.y - centre.y)
                             .atan2(other_centre.x - centre.x)
                             .to_degrees()
                             + 90.0
                     }
                     AngleSetter::RotateToMouse => {
-                        let centre = game.objects[name].origin_in_world();
+                        let centre = game.objects[index].origin_in_world();
                         let error = 0.00001;
                         if (centre.x - mouse.position.x).abs() < error
                             && (centre.y - mouse.position.y).abs() < error
                         {
-                            game.objects[name].angle
+                            game.objects[index].angle
                         } else {
                             (mouse.position.y - centre.y)
                                 .atan2(mouse.position.x - centre.x)
@@ -927,123 +3336,95 @@ impl Action {
                 };
             }
             Action::SetProperty(PropertySetter::Sprite(sprite)) => {
-                game.objects[name].sprite = sprite.clone();
-                game.objects[name].animation = AnimationStatus::None;
+                game.objects[index].sprite = sprite.clone();
+                game.objects[index].animation = AnimationStatus::None;
             }
             Action::SetProperty(PropertySetter::Size(size_setter)) => {
-                let old_size = game.objects[name].size;
-                game.objects[name].size = match size_setter {
+                let old_size = game.objects[index].size;
+                game.objects[index].size = match size_setter {
                     SizeSetter::Value(value) => *value,
                     SizeSetter::Grow(SizeDifference::Value(value)) => Size {
-                        width: game.objects[name].size.width + value.width,
-                        height: game.objects[name].size.height + value.height,
+                        width: game.objects[index].size.width + value.width,
+                        height: game.objects[index].size.height + value.height,
                     },
                     SizeSetter::Shrink(SizeDifference::Value(value)) => Size {
-                        width: game.objects[name].size.width - value.width,
-                        height: game.objects[name].size.height - value.height,
+                        width: game.objects[index].size.width - value.width,
+                        height: game.objects[index].size.height - value.height,
                     },
                     SizeSetter::Grow(SizeDifference::Percent(percent)) => {
-                        let w = game.objects[name].size.width;
-                        let h = game.objects[name].size.height;
+                        let w = game.objects[index].size.width;
+                        let h = game.objects[index].size.height;
                         Size {
                             width: w + w * (percent.width / 100.0),
                             height: h + h * (percent.height / 100.0),
                         }
                     }
                     SizeSetter::Shrink(SizeDifference::Percent(percent)) => {
-                        let w = game.objects[name].size.width;
-                        let h = game.objects[name].size.height;
+                        let w = game.objects[index].size.width;
+                        let h = game.objects[index].size.height;
                         Size {
                             width: w - w * (percent.width / 100.0),
                             height: h - h * (percent.height / 100.0),
                         }
                     }
                     SizeSetter::Clamp { min, max } => Size {
-                        width: game.objects[name].size.width.min(max.width).max(min.width),
-                        height: game.objects[name]
+                        width: game.objects[index].size.width.min(max.width).max(min.width),
+                        height: game.objects[index]
                             .size
                             .height
                             .min(max.height)
                             .max(min.height),
                     },
                 };
-                let size = game.objects[name].size;
-                let difference =
-                    Size::new(size.width / old_size.width, size.height / old_size.height);
-                match &mut game.objects[name].collision_area {
-                    Some(area) => {
-                        *area = AABB {
-                            min: Vec2::new(
-                                if old_size.width == 0.0 {
-                                    size.width
-                                } else {
-                                    area.min.x * difference.width
-                                },
-                                if old_size.height == 0.0 {
-                                    size.height
-                                } else {
-                                    area.min.y * difference.height
-                                },
-                            ),
-                            max: Vec2::new(
-                                if old_size.width == 0.0 {
-                                    size.width
-                                } else {
-                                    area.max.x * difference.width
-                                },
-                                if old_size.height == 0.0 {
-                                    size.height
-                                } else {
-                                    area.max.y * difference.width
-                                },
-                            ),
-                        };
-                    }
-                    None => {}
+                let size = game.objects[index].size;
+                if let Some(shape) = &mut game.objects[index].collision_shape {
+                    shape.scale(old_size, size);
                 }
             }
             Action::SetProperty(PropertySetter::Switch(switch)) => {
-                if *switch == Switch::On && game.objects[name].switch != SwitchState::On {
-                    game.objects[name].switch = SwitchState::SwitchedOn;
-                } else if *switch == Switch::Off && game.objects[name].switch != SwitchState::Off {
-                    game.objects[name].switch = SwitchState::SwitchedOff;
+                if *switch == Switch::On && game.objects[index].switch != SwitchState::On {
+                    game.objects[index].switch = SwitchState::SwitchedOn;
+                } else if *switch == Switch::Off && game.objects[index].switch != SwitchState::Off {
+                    game.objects[index].switch = SwitchState::SwitchedOff;
                 }
             }
             Action::SetProperty(PropertySetter::Timer { time }) => {
-                game.objects[name].timer = Some(*time);
+                game.objects[index].timer = Some(*time);
             }
             Action::SetProperty(PropertySetter::FlipHorizontal(FlipSetter::Flip)) => {
-                game.objects[name].flip.horizontal = !game.objects[name].flip.horizontal;
+                game.objects[index].flip.horizontal = !game.objects[index].flip.horizontal;
             }
             Action::SetProperty(PropertySetter::FlipVertical(FlipSetter::Flip)) => {
-                game.objects[name].flip.vertical = !game.objects[name].flip.vertical;
+                game.objects[index].flip.vertical = !game.objects[index].flip.vertical;
             }
             Action::SetProperty(PropertySetter::FlipHorizontal(FlipSetter::SetFlip(flipped))) => {
-                game.objects[name].flip.horizontal = *flipped;
+                game.objects[index].flip.horizontal = *flipped;
             }
             Action::SetProperty(PropertySetter::FlipVertical(FlipSetter::SetFlip(flipped))) => {
-                game.objects[name].flip.vertical = *flipped;
+                game.objects[index].flip.vertical = *flipped;
             }
             Action::SetProperty(PropertySetter::Layer(layer_setter)) => {
-                game.objects[name].layer = match layer_setter {
+                game.objects[index].layer = match layer_setter {
                     LayerSetter::Value(value) => *value,
                     LayerSetter::Increase => {
-                        game.objects[name].layer
-                            + if game.objects[name].layer < std::u8::MAX - 1 {
+                        game.objects[index].layer
+                            + if game.objects[index].layer < u8::MAX - 1 {
                                 1
                             } else {
                                 0
                             }
                     }
                     LayerSetter::Decrease => {
-                        game.objects[name].layer - if game.objects[name].layer > 0 { 1 } else { 0 }
+                        game.objects[index].layer
+                            - if game.objects[index].layer > 0 { 1 } else { 0 }
                     }
                 };
             }
             Action::Random { random_actions } => {
                 let action = rng.random_in_slice(random_actions);
                 if let Some(action) = action {
-                    return action.apply(game, name, mouse, rng);
+                    let other = find_index(&game.objects, action.object_name());
+                    return action.apply(game, index, other, mouse, rng, world_actions);
                 }
             }
             Action::EndEarly => {
@@ -1051,7 +3432,7 @@ impl Action {
             }
         };
 
-        Ok(world_actions)
+        Ok(())
     }
 }
 
@@ -1064,6 +3445,19 @@ impl Default for Mouse {
     }
 }
 
+impl Comparison {
+    pub fn compare(self, a: f32, b: f32) -> bool {
+        match self {
+            Comparison::Equal => a == b,
+            Comparison::NotEqual => a != b,
+            Comparison::LessThan => a < b,
+            Comparison::LessThanOrEqual => a <= b,
+            Comparison::GreaterThan => a > b,
+            Comparison::GreaterThanOrEqual => a >= b,
+        }
+    }
+}
+
 impl Speed {
     fn as_value(self) -> f32 {
         match self {
@@ -1092,38 +3486,79 @@ impl AnimationStatus {
     pub fn start(
         animation_type: AnimationType,
         sprites: &[Sprite],
+        frame_durations: &[u32],
         speed: Speed,
+        playback: Playback,
+        start_frame: usize,
     ) -> AnimationStatus {
-        let should_loop = match animation_type {
-            AnimationType::Loop => true,
-            AnimationType::PlayOnce => false,
+        let plays_left = match animation_type {
+            AnimationType::Loop => None,
+            AnimationType::PlayOnce => Some(1),
+            AnimationType::LoopTimes(times) => Some(times.max(1)),
+        };
+        let forward = 0..sprites.len();
+        let order: Vec<usize> = match playback {
+            Playback::Forward => forward.collect(),
+            Playback::Reverse => forward.rev().collect(),
+            Playback::PingPong => forward.clone().chain(forward.rev().skip(1)).collect(),
         };
-        AnimationStatus::Animating(Animation {
-            should_loop,
+        let position = order
+            .iter()
+            .position(|index| *index == start_frame)
+            .unwrap_or(0);
+        let mut animation = Animation {
+            plays_left,
+            order,
+            position,
             sprites: sprites.to_vec(),
-            index: 0,
             speed,
-            time_to_next_change: speed.to_animation_time(),
-        })
+            frame_durations: frame_durations.to_vec(),
+            time_to_next_change: 0,
+        };
+        animation.time_to_next_change = animation.time_on_frame();
+        AnimationStatus::Animating(animation)
+    }
+
+    // The index of the sprite being shown
+    pub fn frame(&self) -> Option<usize> {
+        match self {
+            AnimationStatus::Animating(animation) => {
+                animation.order.get(animation.position).copied()
+            }
+            _ => None,
+        }
+    }
+
+    pub fn sprite(&self) -> Option<&Sprite> {
+        match self {
+            AnimationStatus::Animating(animation) => {
+                self.frame().map(|index| &animation.sprites[index])
+            }
+            _ => None,
+        }
     }
 
     pub fn update(&mut self) -> Option<Sprite> {
         match self {
             AnimationStatus::Animating(animation) => {
                 if animation.time_to_next_change == 0 {
-                    if animation.sprites.is_empty() {
-                    } else if animation.index == animation.sprites.len() - 1 {
-                        if animation.should_loop {
-                            animation.index = 0;
-                            animation.time_to_next_change = animation.speed.to_animation_time();
-                            return Some(animation.sprites[0].clone());
-                        } else {
-                            *self = AnimationStatus::Finished;
+                    if animation.order.is_empty() {
+                    } else if animation.position == animation.order.len() - 1 {
+                        match animation.plays_left {
+                            Some(plays) if plays <= 1 => {
+                                *self = AnimationStatus::Finished;
+                            }
+                            _ => {
+                                animation.plays_left = animation.plays_left.map(|plays| plays - 1);
+                                animation.position = animation.loop_start();
+                                animation.time_to_next_change = animation.time_on_frame();
+                                return self.sprite().cloned();
+                            }
                         }
                     } else {
-                        animation.index += 1;
-                        animation.time_to_next_change = animation.speed.to_animation_time();
-                        return Some(animation.sprites[animation.index].clone());
+                        animation.position += 1;
+                        animation.time_to_next_change = animation.time_on_frame();
+                        return self.sprite().cloned();
                     }
                 } else {
                     animation.time_to_next_change -= 1;
@@ -1139,230 +3574,280 @@ impl AnimationStatus {
     }
 }
 
-fn move_object(game: &mut Game, name: &str, mouse: Mouse, rng: &mut impl WeeRng) -> WeeResult<()> {
-    let mut clamps = Vec::new();
-    for mut motion in game.objects[name].queued_motion.clone().into_iter() {
-        if let Motion::JumpTo(JumpLocation::ClampPosition { .. }) = &motion {
+impl Animation {
+    // The countdown shows the sprite for one frame more than it starts at
+    fn time_on_frame(&self) -> u32 {
+        let index = self.order.get(self.position).copied().unwrap_or(0);
+        match self.frame_durations.get(index) {
+            Some(duration) => duration.saturating_sub(1),
+            None => self.speed.to_animation_time(),
+        }
+    }
+
+    // Ping-pong ends on its first frame so the next loop doesn't show it twice
+    fn loop_start(&self) -> usize {
+        let ends_on_first_frame = self.order.len() > 1 && self.order.first() == self.order.last();
+        if ends_on_first_frame {
+            1
         } else {
-            for area in clamps {
-                clamp_position(&mut game.objects[name].position, area);
-            }
-            clamps = Vec::new();
+            0
         }
-        game.objects[name].active_motion = match &mut motion {
-            Motion::GoStraight { direction, speed } => {
-                let velocity = direction.to_vector(&game.objects[name], *speed, rng);
-                ActiveMotion::GoStraight { velocity }
-            }
-            Motion::JumpTo(jump_location) => {
-                match jump_location {
-                    JumpLocation::Point(point) => {
-                        game.objects[name].position = *point;
-                    }
-                    JumpLocation::Relative { to, distance } => match to {
-                        RelativeTo::CurrentPosition => {
-                            game.objects[name].position += *distance;
-                        }
-                        RelativeTo::CurrentAngle => {
-                            let angle = game.objects[name].trig_angle();
-                            game.objects[name].position.x +=
-                                -distance.y * angle.cos() - distance.x * angle.sin();
-                            game.objects[name].position.y +=
-                                -distance.y * angle.sin() + distance.x * angle.cos();
-                        }
-                    },
-                    JumpLocation::Area(area) => {
-                        fn gen_in_area(area: AABB, rng: &mut impl WeeRng) -> Vec2 {
-                            Vec2::new(
-                                rng.gen_in_unordered_range(area.min.x, area.max.x),
-                                rng.gen_in_unordered_range(area.min.y, area.max.y),
-                            )
-                        }
-                        game.objects[name].position = gen_in_area(*area, rng);
-                    }
-                    JumpLocation::ClampPosition { .. } => {
-                        //clamp_position(&mut game.objects[name].position, *area);
+    }
+}
+
+impl Motion {
+    fn object_name(&self) -> Option<&str> {
+        match self {
+            Motion::JumpTo(JumpLocation::Object { name }) => Some(name),
+            Motion::Swap { name } => Some(name),
+            Motion::Target {
+                target: Target::Object { name },
+                ..
+            } => Some(name),
+            _ => None,
+        }
+    }
+}
+
+fn move_object(
+    game: &mut Game,
+    table: &mut InstructionTable,
+    index: usize,
+    mouse: Mouse,
+    rng: &mut impl WeeRng,
+) -> WeeResult<()> {
+    let InstructionTable {
+        motions,
+        queued_motions,
+        clamps,
+        ..
+    } = table;
+    clamps.clear();
+    if !game.objects[index].queued_motion.is_empty() {
+        let queued_motion = std::mem::take(&mut game.objects[index].queued_motion);
+        for motion in queued_motion {
+            let motion = CompiledMotion::new(motion, &game.objects);
+            start_motion(game, index, &motion, clamps, mouse, rng)?;
+        }
+    }
+    for &motion in queued_motions.iter() {
+        start_motion(game, index, &motions[motion], clamps, mouse, rng)?;
+    }
+    queued_motions.clear();
+
+    game.objects[index].active_motion = update_active_motion(game, index, mouse, rng)?;
+
+    for area in clamps.iter() {
+        clamp_position(&mut game.objects[index].position, *area);
+        game.objects[index].active_motion = ActiveMotion::Stop;
+    }
+
+    Ok(())
+}
+
+fn start_motion(
+    game: &mut Game,
+    index: usize,
+    motion: &CompiledMotion,
+    clamps: &mut Vec<AABB>,
+    mouse: Mouse,
+    rng: &mut impl WeeRng,
+) -> WeeResult<()> {
+    if let Motion::JumpTo(JumpLocation::ClampPosition { .. }) = &motion.motion {
+    } else {
+        for area in clamps.iter() {
+            clamp_position(&mut game.objects[index].position, *area);
+        }
+        clamps.clear();
+    }
+    game.objects[index].active_motion = match &motion.motion {
+        Motion::GoStraight { direction, speed } => {
+            let velocity =
+                direction.vector_from(&motion.directions, &game.objects[index], *speed, rng);
+            ActiveMotion::GoStraight { velocity }
+        }
+        Motion::JumpTo(jump_location) => {
+            match jump_location {
+                JumpLocation::Point(point) => {
+                    game.objects[index].position = *point;
+                }
+                JumpLocation::Relative { to, distance } => match to {
+                    RelativeTo::CurrentPosition => {
+                        game.objects[index].position += *distance;
                     }
-                    JumpLocation::Object { name: other_name } => {
-                        game.objects[name].position = game.objects.get_obj(&other_name)?.position;
+                    RelativeTo::CurrentAngle => {
+                        let angle = game.objects[index].trig_angle();
+                        game.objects[index].position.x +=
+                            -distance.y * angle.cos() - distance.x * angle.sin();
+                        game.objects[index].position.y +=
+                            -distance.y * angle.sin() + distance.x * angle.cos();
                     }
-                    JumpLocation::Mouse => {
-                        game.objects[name].position = mouse.position;
+                },
+                JumpLocation::Area(area) => {
+                    fn gen_in_area(area: AABB, rng: &mut impl WeeRng) -> Vec2 {
+                        Vec2::new(
+                            rng.gen_in_unordered_range(area.min.x, area.max.x),
+                            rng.gen_in_unordered_range(area.min.y, area.max.y),
+                        )
                     }
+                    game.objects[index].position = gen_in_area(*area, rng);
                 }
-                if let Motion::JumpTo(JumpLocation::ClampPosition { area }) = motion {
-                    clamps.push(area);
-                    game.objects[name].active_motion.clone()
-                } else {
-                    ActiveMotion::Stop
+                JumpLocation::ClampPosition { .. } => {
+                    //clamp_position(&mut game.objects[index].position, *area);
+                }
+                JumpLocation::Object { name: other_name } => {
+                    game.objects[index].position =
+                        game.objects[found_object(motion.other, other_name)?].position;
+                }
+                JumpLocation::Mouse => {
+                    game.objects[index].position = mouse.position;
                 }
             }
-            Motion::Roam {
-                movement_type,
-                area,
-                speed,
-            } => {
-                let active_roam = match movement_type {
-                    MovementType::Wiggle => ActiveRoam::Wiggle,
-                    MovementType::Reflect {
-                        initial_direction,
-                        movement_handling,
-                    } => {
-                        let width = area.width();
-                        let height = area.height();
-                        match initial_direction {
-                            MovementDirection::Angle(angle) => {
-                                let angle = match angle {
-                                    Angle::Current => game.objects[name].angle,
-                                    Angle::Degrees(degrees) => *degrees,
-                                    Angle::Random { min, max } => {
-                                        rng.gen_in_unordered_range(*min, *max)
-                                    }
-                                };
-                                let velocity = vector_from_angle(angle, *speed);
-                                ActiveRoam::Reflect {
-                                    velocity,
-                                    movement_handling: *movement_handling,
+            if let Motion::JumpTo(JumpLocation::ClampPosition { area }) = &motion.motion {
+                clamps.push(*area);
+                game.objects[index].active_motion.clone()
+            } else {
+                ActiveMotion::Stop
+            }
+        }
+        Motion::Roam {
+            movement_type,
+            area,
+            speed,
+        } => {
+            let active_roam = match movement_type {
+                MovementType::Wiggle => ActiveRoam::Wiggle,
+                MovementType::Reflect {
+                    initial_direction,
+                    movement_handling,
+                } => {
+                    let width = area.width();
+                    let height = area.height();
+                    match initial_direction {
+                        MovementDirection::Angle(angle) => {
+                            let angle = match angle {
+                                Angle::Current => game.objects[index].angle,
+                                Angle::Degrees(degrees) => *degrees,
+                                Angle::Random { min, max } => {
+                                    rng.gen_in_unordered_range(*min, *max)
                                 }
+                            };
+                            let velocity = vector_from_angle(angle, *speed);
+                            ActiveRoam::Reflect {
+                                velocity,
+                                movement_handling: *movement_handling,
                             }
-                            MovementDirection::Direction {
-                                possible_directions,
-                            } => {
-                                let enough_horizontal_space = width < game.objects[name].size.width;
-                                let enough_vertical_space = height < game.objects[name].size.height;
-
-                                let mut possible_directions = if !possible_directions.is_empty() {
-                                    possible_directions.iter().cloned().collect()
+                        }
+                        MovementDirection::Direction { .. } => {
+                            let enough_horizontal_space = width < game.objects[index].size.width;
+                            let enough_vertical_space = height < game.objects[index].size.height;
+
+                            let possible_directions: &[CompassDirection] =
+                                if !motion.directions.is_empty() {
+                                    &motion.directions
                                 } else if enough_horizontal_space && enough_vertical_space {
-                                    Vec::new()
+                                    &[]
                                 } else if enough_horizontal_space {
-                                    vec![CompassDirection::Up, CompassDirection::Down]
+                                    &[CompassDirection::Up, CompassDirection::Down]
                                 } else if enough_vertical_space {
-                                    vec![CompassDirection::Left, CompassDirection::Right]
+                                    &[CompassDirection::Right, CompassDirection::Left]
                                 } else {
-                                    CompassDirection::all_directions()
-                                };
-                                possible_directions.sort();
-                                let dir = rng.random_in_slice(&possible_directions);
-                                let velocity = match dir {
-                                    Some(dir) => dir.to_vector(*speed),
-                                    None => Vec2::zero(),
+                                    &ALL_DIRECTIONS
                                 };
-                                ActiveRoam::Reflect {
-                                    velocity,
-                                    movement_handling: *movement_handling,
-                                }
+                            let dir = rng.random_in_slice(possible_directions);
+                            let velocity = match dir {
+                                Some(dir) => dir.to_vector(*speed),
+                                None => Vec2::zero(),
+                            };
+                            ActiveRoam::Reflect {
+                                velocity,
+                                movement_handling: *movement_handling,
                             }
                         }
                     }
-                    MovementType::Insect => ActiveRoam::Insect {
-                        velocity: rng.random_velocity(*speed),
-                    },
-                    MovementType::Bounce { initial_direction } => {
-                        let frames_in_bounce = 60.0 * Speed::Normal.as_value() / speed.as_value();
-                        let acceleration = -2.0 * (area.min.y - area.max.y)
-                            / (frames_in_bounce * frames_in_bounce);
-                        let velocity = {
-                            let y_velocity = 2.0 * (area.min.y - game.objects[name].position.y)
-                                / frames_in_bounce;
-                            Vec2::new(0.0, y_velocity)
-                        };
-                        let direction = initial_direction.clone().unwrap_or_else(|| {
-                            if rng.coin_flip() {
-                                BounceDirection::Left
-                            } else {
-                                BounceDirection::Right
-                            }
-                        });
-
-                        ActiveRoam::Bounce {
-                            velocity,
-                            direction,
-                            acceleration,
-                            frames_in_bounce,
+                }
+                MovementType::Insect => ActiveRoam::Insect {
+                    velocity: rng.random_velocity(*speed),
+                },
+                MovementType::Bounce { initial_direction } => {
+                    let frames_in_bounce = 60.0 * Speed::Normal.as_value() / speed.as_value();
+                    let acceleration =
+                        -2.0 * (area.min.y - area.max.y) / (frames_in_bounce * frames_in_bounce);
+                    let velocity = {
+                        let y_velocity =
+                            2.0 * (area.min.y - game.objects[index].position.y) / frames_in_bounce;
+                        Vec2::new(0.0, y_velocity)
+                    };
+                    let direction = initial_direction.clone().unwrap_or_else(|| {
+                        if rng.coin_flip() {
+                            BounceDirection::Left
+                        } else {
+                            BounceDirection::Right
                         }
+                    });
+
+                    ActiveRoam::Bounce {
+                        velocity,
+                        direction,
+                        acceleration,
+                        frames_in_bounce,
                     }
-                };
-                ActiveMotion::Roam {
-                    movement_type: active_roam,
-                    area: *area,
-                    speed: *speed,
                 }
-            }
-            Motion::Swap { name: other_name } => {
-                game.objects.get_obj(&other_name)?;
-                let temp = game.objects[&*other_name].position;
-                game.objects[&*other_name].position = game.objects[name].position;
-                game.objects[name].position = temp;
-                ActiveMotion::Stop
-            }
-            Motion::Target {
-                target,
-                target_type,
-                offset,
-                speed,
-            } => ActiveMotion::Target {
-                target: target.clone(),
-                target_type: *target_type,
-                offset: *offset,
+            };
+            ActiveMotion::Roam {
+                movement_type: active_roam,
+                area: *area,
                 speed: *speed,
+            }
+        }
+        Motion::Swap { name: other_name } => {
+            let other = found_object(motion.other, other_name)?;
+            let temp = game.objects[other].position;
+            game.objects[other].position = game.objects[index].position;
+            game.objects[index].position = temp;
+            game.collisions.update(&game.objects, other);
+            ActiveMotion::Stop
+        }
+        Motion::Target {
+            target,
+            target_type,
+            offset,
+            speed,
+        } => ActiveMotion::Target {
+            target: match target {
+                Target::Object { name: other_name } => ActiveTarget::Object {
+                    index: found_object(motion.other, other_name)?,
+                },
+                Target::Mouse => ActiveTarget::Mouse,
             },
-            Motion::Accelerate(Acceleration::Continuous { direction, speed }) => {
-                let speed = Speed::Value(speed.as_value() / 40.0);
-                let acceleration = direction.to_vector(&game.objects[name], speed, rng);
-                let velocity = match &game.objects[name].active_motion {
-                    ActiveMotion::Accelerate { velocity, .. } => *velocity,
-                    ActiveMotion::GoStraight { velocity } => *velocity,
-                    ActiveMotion::Roam { movement_type, .. } => match movement_type {
-                        ActiveRoam::Insect { velocity } => *velocity,
-                        ActiveRoam::Bounce { velocity, .. } => *velocity,
-                        ActiveRoam::Reflect { velocity, .. } => *velocity,
-                        _ => Vec2::zero(),
-                    },
-                    ActiveMotion::Target { .. } => Vec2::zero(),
-                    ActiveMotion::SlowDown { velocity, .. } => *velocity,
-                    ActiveMotion::Stop => Vec2::zero(),
-                };
-                ActiveMotion::Accelerate {
-                    velocity,
-                    acceleration,
-                }
+            target_type: *target_type,
+            offset: *offset,
+            speed: *speed,
+        },
+        Motion::Accelerate(Acceleration::Continuous { direction, speed }) => {
+            let speed = Speed::Value(speed.as_value() / 40.0);
+            let acceleration =
+                direction.vector_from(&motion.directions, &game.objects[index], speed, rng);
+            let velocity = game.objects[index].active_motion.velocity();
+            ActiveMotion::Accelerate {
+                velocity,
+                acceleration,
             }
-            Motion::Accelerate(Acceleration::SlowDown { speed }) => {
-                let velocity = match &game.objects[name].active_motion {
-                    ActiveMotion::Accelerate { velocity, .. } => *velocity,
-                    ActiveMotion::GoStraight { velocity } => *velocity,
-                    ActiveMotion::Roam { movement_type, .. } => match movement_type {
-                        ActiveRoam::Insect { velocity } => *velocity,
-                        ActiveRoam::Bounce { velocity, .. } => *velocity,
-                        ActiveRoam::Reflect { velocity, .. } => *velocity,
-                        _ => Vec2::zero(),
-                    },
-                    ActiveMotion::Target { .. } => Vec2::zero(),
-                    ActiveMotion::SlowDown { velocity, .. } => *velocity,
-                    ActiveMotion::Stop => Vec2::zero(),
-                };
-                if velocity.x == 0.0 && velocity.y == 0.0 {
-                    ActiveMotion::Stop
-                } else {
-                    let deceleration = -(velocity.unit() * (speed.as_value() / 40.0));
-                    ActiveMotion::SlowDown {
-                        velocity,
-                        deceleration,
-                    }
+        }
+        Motion::Accelerate(Acceleration::SlowDown { speed }) => {
+            let velocity = game.objects[index].active_motion.velocity();
+            if velocity.x == 0.0 && velocity.y == 0.0 {
+                ActiveMotion::Stop
+            } else {
+                let deceleration = -(velocity.unit() * (speed.as_value() / 40.0));
+                ActiveMotion::SlowDown {
+                    velocity,
+                    deceleration,
                 }
             }
-            Motion::Stop => ActiveMotion::Stop,
-        };
-    }
-    game.objects[name].queued_motion = Vec::new();
-
-    game.objects[name].active_motion = update_active_motion(game, name, mouse, rng)?;
-
-    for area in clamps {
-        clamp_position(&mut game.objects[name].position, area);
-        game.objects[name].active_motion = ActiveMotion::Stop;
-    }
+        }
+        Motion::Stop => ActiveMotion::Stop,
+    };
 
     Ok(())
 }
@@ -1402,6 +3887,7 @@ fn vector_from_angle(angle: f32, speed: Speed) -> Vec2 {
 
 fn angle_from_direction(
     direction: &MovementDirection,
+    directions: &[CompassDirection],
     object: &Object,
     rng: &mut impl WeeRng,
 ) -> f32 {
@@ -1411,30 +3897,59 @@ fn angle_from_direction(
             Angle::Degrees(degrees) => *degrees,
             Angle::Random { min, max } => rng.gen_in_unordered_range(*min, *max),
         },
-        MovementDirection::Direction {
-            possible_directions,
-        } => {
-            let mut possible_directions = if !possible_directions.is_empty() {
-                possible_directions.iter().cloned().collect()
+        MovementDirection::Direction { .. } => {
+            let possible_directions = if !directions.is_empty() {
+                directions
             } else {
-                CompassDirection::all_directions()
+                &ALL_DIRECTIONS
             };
-            possible_directions.sort();
-            let dir = rng.random_in_slice(&possible_directions).unwrap();
+            let dir = rng.random_in_slice(possible_directions).unwrap();
             dir.angle()
         }
     }
-}
+}
+
+impl MovementDirection {
+    fn sorted_directions(&self) -> Vec<CompassDirection> {
+        match self {
+            MovementDirection::Angle(_) => Vec::new(),
+            MovementDirection::Direction {
+                possible_directions,
+            } => {
+                let mut directions: Vec<CompassDirection> =
+                    possible_directions.iter().cloned().collect();
+                directions.sort();
+                directions
+            }
+        }
+    }
+
+    fn vector_from(
+        &self,
+        directions: &[CompassDirection],
+        object: &Object,
+        speed: Speed,
+        rng: &mut impl WeeRng,
+    ) -> Vec2 {
+        vector_from_angle(angle_from_direction(self, directions, object, rng), speed)
+    }
 
-impl MovementDirection {
-    fn angle(&self, object: &Object, rng: &mut impl WeeRng) -> f32 {
-        angle_from_direction(self, object, rng)
-    }
     pub fn to_vector(&self, object: &Object, speed: Speed, rng: &mut impl WeeRng) -> Vec2 {
-        vector_from_angle(self.angle(object, rng), speed)
+        self.vector_from(&self.sorted_directions(), object, speed, rng)
     }
 }
 
+const ALL_DIRECTIONS: [CompassDirection; 8] = [
+    CompassDirection::Up,
+    CompassDirection::UpRight,
+    CompassDirection::Right,
+    CompassDirection::DownRight,
+    CompassDirection::Down,
+    CompassDirection::DownLeft,
+    CompassDirection::Left,
+    CompassDirection::UpLeft,
+];
+
 impl CompassDirection {
     fn angle(self) -> f32 {
         match self {
@@ -1449,19 +3964,6 @@ impl CompassDirection {
         }
     }
 
-    fn all_directions() -> Vec<CompassDirection> {
-        vec![
-            CompassDirection::Up,
-            CompassDirection::UpRight,
-            CompassDirection::Right,
-            CompassDirection::DownRight,
-            CompassDirection::Down,
-            CompassDirection::DownLeft,
-            CompassDirection::Left,
-            CompassDirection::UpLeft,
-        ]
-    }
-
     fn to_vector(self, speed: Speed) -> Vec2 {
         vector_from_angle(self.angle(), speed)
     }
@@ -1469,13 +3971,13 @@ impl CompassDirection {
 
 fn update_active_motion(
     game: &mut Game,
-    name: &str,
+    index: usize,
     mouse: Mouse,
     rng: &mut impl WeeRng,
 ) -> WeeResult<ActiveMotion> {
-    let active_motion = match game.objects[name].active_motion.clone() {
+    let active_motion = match game.objects[index].active_motion.clone() {
         ActiveMotion::GoStraight { velocity } => {
-            game.objects[name].position += velocity;
+            game.objects[index].position += velocity;
             ActiveMotion::GoStraight { velocity }
         }
         ActiveMotion::Roam {
@@ -1485,8 +3987,8 @@ fn update_active_motion(
         } => {
             let movement_type = match movement_type {
                 ActiveRoam::Wiggle => {
-                    game.objects[name].position += rng.random_velocity(speed);
-                    clamp_position(&mut game.objects[name].position, area);
+                    game.objects[index].position += rng.random_velocity(speed);
+                    clamp_position(&mut game.objects[index].position, area);
 
                     ActiveRoam::Wiggle
                 }
@@ -1495,9 +3997,9 @@ fn update_active_motion(
                     if rng.random_in_range(0.0, 1.0) < CHANGE_DIRECTION_PROBABILTY {
                         velocity = rng.random_velocity(speed);
                     }
-                    game.objects[name].position += velocity;
+                    game.objects[index].position += velocity;
 
-                    clamp_position(&mut game.objects[name].position, area);
+                    clamp_position(&mut game.objects[index].position, area);
 
                     ActiveRoam::Insect { velocity }
                 }
@@ -1506,22 +4008,26 @@ fn update_active_motion(
                     movement_handling,
                 } => {
                     if let MovementHandling::TryNotToOverlap = movement_handling {
-                        fn calculate_closest_manifold<T: BasicShape>(
+                        fn calculate_closest_manifold(
                             objects: &Objects,
-                            name: &str,
-                            poly: T,
+                            collisions: &mut CollisionCache,
+                            index: usize,
+                            bounds: AABB,
+                            manifold: impl Fn(&WorldShape) -> c2::Manifold,
                         ) -> (Option<c2::Manifold>, Vec2) {
                             let mut longest_depth = 0.0;
                             let mut closest_manifold = None;
                             let mut position = Vec2::zero();
-                            for other_name in objects.keys() {
-                                if other_name != name {
-                                    let manifold = poly.manifold(&objects[other_name].poly());
+                            collisions.find_nearby(bounds);
+                            for &other_index in collisions.nearby() {
+                                if other_index != index {
+                                    let manifold =
+                                        manifold(&collisions.shape(objects, other_index));
                                     if manifold.count() > 0 {
                                         let depth = manifold.depths()[0];
                                         if depth > longest_depth || closest_manifold.is_none() {
                                             closest_manifold = Some(manifold);
-                                            position = objects[other_name].position;
+                                            position = objects[other_index].position;
                                             longest_depth = depth;
                                         }
                                     }
@@ -1529,10 +4035,13 @@ fn update_active_motion(
                             }
                             (closest_manifold, position)
                         }
+                        let shape = game.objects[index].shape();
                         let (original_manifold, other_position) = calculate_closest_manifold(
                             &game.objects,
-                            name,
-                            game.objects[name].poly(),
+                            &mut game.collisions,
+                            index,
+                            shape_bounds(&shape),
+                            |other| shape.manifold(other),
                         );
                         let move_away = |manifold: Option<c2::Manifold>| {
                             if let Some(manifold) = manifold {
@@ -1543,22 +4052,20 @@ fn update_active_motion(
                                 Vec2::zero()
                             }
                         };
-                        game.objects[name].position -= move_away(original_manifold);
+                        game.objects[index].position -= move_away(original_manifold);
 
                         let closest_manifold = {
-                            let moved_poly = {
-                                let transformation = c2::Transformation::new(
-                                    [velocity.x, velocity.y],
-                                    c2::Rotation::zero(),
-                                );
-                                (game.objects[name].poly(), transformation)
-                            };
-
-                            let (new_manifold, _) =
-                                calculate_closest_manifold(&game.objects, name, moved_poly);
+                            let shape = game.objects[index].shape();
+                            let (new_manifold, _) = calculate_closest_manifold(
+                                &game.objects,
+                                &mut game.collisions,
+                                index,
+                                shape_bounds(&shape).move_position(velocity),
+                                |other| shape.moved_manifold(velocity, other),
+                            );
 
                             let is_moving_towards = {
-                                let to_point = other_position - game.objects[name].position;
+                                let to_point = other_position - game.objects[index].position;
                                 (to_point.x * velocity.x + to_point.y * velocity.y) > 0.0
                             };
                             if new_manifold.is_none() && is_moving_towards {
@@ -1567,7 +4074,7 @@ fn update_active_motion(
                                 new_manifold
                             }
                         };
-                        game.objects[name].position -= move_away(closest_manifold);
+                        game.objects[index].position -= move_away(closest_manifold);
                         if let Some(manifold) = closest_manifold {
                             let normal = manifold.normal();
                             let len = (normal.x().powf(2.0) + normal.y().powf(2.0)).sqrt();
@@ -1577,19 +4084,19 @@ fn update_active_motion(
                             }
                         }
                     }
-                    if game.objects[name].position.x + velocity.x < area.min.x {
+                    if game.objects[index].position.x + velocity.x < area.min.x {
                         velocity.x = velocity.x.abs();
                     }
-                    if game.objects[name].position.x + velocity.x > area.max.x {
+                    if game.objects[index].position.x + velocity.x > area.max.x {
                         velocity.x = -velocity.x.abs();
                     }
-                    if game.objects[name].position.y + velocity.y < area.min.y {
+                    if game.objects[index].position.y + velocity.y < area.min.y {
                         velocity.y = velocity.y.abs();
                     }
-                    if game.objects[name].position.y + velocity.y > area.max.y {
+                    if game.objects[index].position.y + velocity.y > area.max.y {
                         velocity.y = -velocity.y.abs();
                     }
-                    game.objects[name].position += velocity;
+                    game.objects[index].position += velocity;
 
                     ActiveRoam::Reflect {
                         velocity,
@@ -1602,7 +4109,10 @@ fn update_active_motion(
                     acceleration,
                     frames_in_bounce,
                 } => {
-                    let (x, y) = (game.objects[name].position.x, game.objects[name].position.y);
+                    let (x, y) = (
+                        game.objects[index].position.x,
+                        game.objects[index].position.y,
+                    );
                     if y < area.min.y && velocity.y < 0.0 {
                         velocity.y = 0.0;
                     }
@@ -1618,7 +4128,7 @@ fn update_active_motion(
                     }
                     if x >= area.min.x
                         && x <= area.max.x
-                        && game.objects[name].size.width >= area.width()
+                        && game.objects[index].size.width >= area.width()
                     {
                         velocity.x = 0.0;
                     } else {
@@ -1627,7 +4137,7 @@ fn update_active_motion(
                             BounceDirection::Right => velocity.x = speed.as_value() / 2.0,
                         }
                     }
-                    game.objects[name].position += velocity;
+                    game.objects[index].position += velocity;
 
                     ActiveRoam::Bounce {
                         velocity,
@@ -1649,14 +4159,12 @@ fn update_active_motion(
             offset,
             speed,
         } => {
-            game.objects[name].position = {
+            game.objects[index].position = {
                 let other = match &target {
-                    Target::Object { name: other_name } => {
-                        game.objects.get_obj(other_name)?.position
-                    }
-                    Target::Mouse => mouse.position,
+                    ActiveTarget::Object { index: other } => game.objects[*other].position,
+                    ActiveTarget::Mouse => mouse.position,
                 };
-                let target_vector = other + offset - game.objects[name].position;
+                let target_vector = other + offset - game.objects[index].position;
                 let target_vector =
                     target_vector / (target_vector.x.powf(2.0) + target_vector.y.powf(2.0)).sqrt();
                 let move_to = |x: f32, other: f32, velocity: f32| {
@@ -1670,12 +4178,12 @@ fn update_active_motion(
 
                 Vec2::new(
                     move_to(
-                        game.objects[name].position.x,
+                        game.objects[index].position.x,
                         other.x + offset.x,
                         velocity.x,
                     ),
                     move_to(
-                        game.objects[name].position.y,
+                        game.objects[index].position.y,
                         other.y + offset.y,
                         velocity.y,
                     ),
@@ -1684,15 +4192,13 @@ fn update_active_motion(
 
             if let TargetType::StopWhenReached = target_type {
                 let other = match &target {
-                    Target::Object { name: other_name } => {
-                        game.objects.get_obj(other_name)?.position
-                    }
-                    Target::Mouse => mouse.position,
+                    ActiveTarget::Object { index: other } => game.objects[*other].position,
+                    ActiveTarget::Mouse => mouse.position,
                 };
                 let close_enough =
                     |pos: f32, other: f32, offset: f32| (pos - (other + offset)).abs() < 0.5;
-                if close_enough(game.objects[name].position.x, other.x, offset.x)
-                    && close_enough(game.objects[name].position.y, other.y, offset.y)
+                if close_enough(game.objects[index].position.x, other.x, offset.x)
+                    && close_enough(game.objects[index].position.y, other.y, offset.y)
                 {
                     ActiveMotion::Stop
                 } else {
@@ -1716,7 +4222,7 @@ fn update_active_motion(
             mut velocity,
             acceleration,
         } => {
-            game.objects[name].position += velocity;
+            game.objects[index].position += velocity;
             velocity += acceleration;
             ActiveMotion::Accelerate {
                 velocity,
@@ -1730,7 +4236,7 @@ fn update_active_motion(
             if velocity.magnitude() <= deceleration.magnitude() {
                 ActiveMotion::Stop
             } else {
-                game.objects[name].position += velocity;
+                game.objects[index].position += velocity;
                 velocity += deceleration;
                 ActiveMotion::SlowDown {
                     velocity,
@@ -1746,15 +4252,32 @@ fn update_active_motion(
 
 use wee_common::{Colour, Flip, Size, Vec2, WeeResult, AABB, PROJECTION_HEIGHT, PROJECTION_WIDTH};
 
-use c2::prelude::*;
+pub use atlas::{AtlasLayout, AtlasPlacement, ATLAS_PADDING};
+use broadphase::{shape_bounds, CollisionCache};
+pub use draw::{DrawCommand, SpriteTransform, TextureMapping, INTRO_TEXT_TIME};
+pub use effects::ScreenEffects;
+use effects::TimedEffect;
 use indexmap::IndexMap;
-use serde::{Deserialize, Serialize};
+pub use layers::{LayerSort, LayerSorting};
+pub use localise::{
+    fallback_languages, parse_string_table, table_path, StringTable, Translations,
+    DEFAULT_LANGUAGE, TEMPLATE_NAME, TEXT_DIRECTORY,
+};
+pub use mask::{AlphaMask, CollisionMasks};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+pub use shape::{CollisionShape, WorldShape, MAX_POLYGON_POINTS};
+pub use sheet::{SheetFrame, SheetTag, SpriteSheet};
 use std::{
+    borrow::Cow,
     collections::{HashMap, HashSet},
     default::Default,
     path::Path,
     str,
 };
+pub use text::{
+    FontMetrics, LaidOutText, TextLayout, TextLine, TextOutline, TextShadow, VerticalAlign,
+};
+pub use vector::{Fill, GradientDirection, ShapeVertex, Stroke, VectorShape, VectorSprite};
 
 #[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 pub enum FrameCount {
@@ -1776,6 +4299,37 @@ pub enum CollisionWith {
     Area(AABB),
 }
 
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub enum CollisionPhase {
+    Enter,
+    #[default]
+    Stay,
+    Exit,
+}
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq)]
+pub enum Side {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq)]
+pub enum BoundsCondition {
+    FullyInside,
+    FullyOutside,
+    TouchingEdge,
+    CrossedEdge(Side),
+}
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub enum CollisionMode {
+    #[default]
+    Area,
+    Pixels,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum MouseOver {
     Object { name: String },
@@ -1830,23 +4384,124 @@ pub enum PropertyCheck {
     Sprite(Sprite),
     FinishedAnimation,
     Timer,
+    Compare {
+        property: NumericProperty,
+        comparison: Comparison,
+        to: CompareTo,
+    },
+    // The index is into the animation's sprites, whichever way it's playing
+    AnimationFrame {
+        index: usize,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum DistanceEnd {
+    Object { name: String },
+    Tag { tag: String },
+    Mouse,
+    Point(Vec2),
+}
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub enum DistanceMeasure {
+    #[default]
+    Centre,
+    Edge,
+}
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq)]
+pub enum NumericProperty {
+    X,
+    Y,
+    Width,
+    Height,
+    Angle,
+    Layer,
+    Speed,
+}
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    LessThan,
+    LessThanOrEqual,
+    GreaterThan,
+    GreaterThanOrEqual,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum CompareTo {
+    Value(f32),
+    Object {
+        name: String,
+        property: NumericProperty,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum Trigger {
     Time(When),
-    Collision(CollisionWith),
+    #[serde(deserialize_with = "deserialize_collision")]
+    Collision(CollisionWith, CollisionPhase),
+    Bounds {
+        #[serde(default = "screen_area")]
+        area: AABB,
+        condition: BoundsCondition,
+    },
+    Distance {
+        from: DistanceEnd,
+        to: DistanceEnd,
+        #[serde(default)]
+        measure: DistanceMeasure,
+        comparison: Comparison,
+        value: f32,
+    },
     Input(Input),
     WinStatus(WinStatus),
-    Random { chance: f32 },
-    CheckProperty { name: String, check: PropertyCheck },
-    DifficultyLevel { levels: HashSet<u32> },
+    Random {
+        chance: f32,
+    },
+    CheckProperty {
+        name: String,
+        check: PropertyCheck,
+    },
+    DifficultyLevel {
+        levels: HashSet<u32>,
+    },
 }
 
 #[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq)]
 pub enum Effect {
     Freeze,
     None,
+    SlowMotion {
+        every: u32,
+        frames: u32,
+    },
+    Shake {
+        amplitude: f32,
+        frames: u32,
+    },
+    Flash {
+        colour: Colour,
+        frames: u32,
+    },
+    Fade {
+        fade: Fade,
+        colour: Colour,
+        frames: u32,
+    },
+    Invert {
+        frames: u32,
+    },
+}
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq)]
+pub enum Fade {
+    In,
+    Out,
 }
 
 #[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq)]
@@ -1978,6 +4633,17 @@ pub enum Motion {
 pub enum AnimationType {
     Loop,
     PlayOnce,
+    // Plays the animation this many times before finishing
+    LoopTimes(u32),
+}
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub enum Playback {
+    #[default]
+    Forward,
+    Reverse,
+    // Plays forwards then backwards, finishing back on the first frame
+    PingPong,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -2046,6 +4712,7 @@ pub enum TextResize {
 pub enum JustifyText {
     Centre,
     Left,
+    Right,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -2063,6 +4730,14 @@ pub enum Action {
         animation_type: AnimationType,
         sprites: Vec<Sprite>,
         speed: Speed,
+        // How many frames each sprite is shown for. Sprites without one use the speed
+        #[serde(default)]
+        frame_durations: Vec<u32>,
+        #[serde(default)]
+        playback: Playback,
+        // The sprite the first play starts on. Loops start from the beginning
+        #[serde(default)]
+        start_frame: usize,
     },
     DrawText {
         text: String,
@@ -2070,6 +4745,12 @@ pub enum Action {
         colour: Colour,
         resize: TextResize,
         justify: JustifyText,
+        // Wrapping, alignment and effects, which only text fitted to the object can wrap or shrink
+        #[serde(default)]
+        layout: TextLayout,
+        // Translates the text using the string tables beside the game
+        #[serde(default)]
+        key: Option<String>,
     },
     Random {
         random_actions: Vec<Action>,
@@ -2091,8 +4772,146 @@ pub struct FontLoadInfo {
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum Sprite {
-    Image { name: String },
+    Image {
+        name: String,
+    },
     Colour(Colour),
+    // Part of an image, such as one frame of a sprite sheet
+    Region {
+        image: String,
+        rect: ImageRect,
+    },
+    Shape(VectorSprite),
+    // Part of an image stretched without stretching its corners, for panels and buttons
+    NineSlice {
+        image: String,
+        rect: ImageRect,
+        insets: SliceInsets,
+    },
+}
+
+impl Sprite {
+    pub fn image_name(&self) -> Option<&str> {
+        match self {
+            Sprite::Image { name } => Some(name),
+            Sprite::Region { image, .. } | Sprite::NineSlice { image, .. } => Some(image),
+            Sprite::Colour(_) | Sprite::Shape(_) => None,
+        }
+    }
+}
+
+// A rectangle of pixels in an image, measured from its top left
+#[derive(Debug, Copy, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ImageRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl ImageRect {
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> ImageRect {
+        ImageRect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    // Keeps the rectangle inside an image of the given size
+    pub fn clamped(self, width: u32, height: u32) -> ImageRect {
+        let x = self.x.min(width);
+        let y = self.y.min(height);
+        ImageRect {
+            x,
+            y,
+            width: self.width.min(width - x),
+            height: self.height.min(height - y),
+        }
+    }
+
+    pub fn to_aabb(self) -> AABB {
+        AABB::new(
+            self.x as f32,
+            self.y as f32,
+            (self.x + self.width) as f32,
+            (self.y + self.height) as f32,
+        )
+    }
+}
+
+// How many pixels along each edge of a nine-slice sprite's image keep their size
+#[derive(Debug, Copy, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct SliceInsets {
+    pub left: u32,
+    pub top: u32,
+    pub right: u32,
+    pub bottom: u32,
+}
+
+impl SliceInsets {
+    pub fn new(left: u32, top: u32, right: u32, bottom: u32) -> SliceInsets {
+        SliceInsets {
+            left,
+            top,
+            right,
+            bottom,
+        }
+    }
+
+    // The part of the image and the area it covers for each piece, row by row from the top left.
+    // Borders are shrunk to fit when the area is smaller than them
+    pub fn pieces(&self, rect: ImageRect, area: AABB) -> [(ImageRect, AABB); 9] {
+        let left = self.left.min(rect.width);
+        let right = self.right.min(rect.width - left);
+        let top = self.top.min(rect.height);
+        let bottom = self.bottom.min(rect.height - top);
+
+        let edges = |start: u32, length: u32, before: u32, after: u32| {
+            [
+                start,
+                start + before,
+                start + length - after,
+                start + length,
+            ]
+        };
+        let source_x = edges(rect.x, rect.width, left, right);
+        let source_y = edges(rect.y, rect.height, top, bottom);
+
+        let placed = |min: f32, max: f32, before: u32, after: u32| {
+            let border = (before + after) as f32;
+            let scale = if border > max - min {
+                (max - min).max(0.0) / border
+            } else {
+                1.0
+            };
+            [
+                min,
+                min + before as f32 * scale,
+                max - after as f32 * scale,
+                max,
+            ]
+        };
+        let x = placed(area.min.x, area.max.x, left, right);
+        let y = placed(area.min.y, area.max.y, top, bottom);
+
+        let mut pieces = [(ImageRect::default(), area); 9];
+        for row in 0..3 {
+            for column in 0..3 {
+                pieces[row * 3 + column] = (
+                    ImageRect::new(
+                        source_x[column],
+                        source_y[row],
+                        source_x[column + 1] - source_x[column],
+                        source_y[row + 1] - source_y[row],
+                    ),
+                    AABB::new(x[column], y[row], x[column + 1], y[row + 1]),
+                );
+            }
+        }
+        pieces
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -2103,13 +4922,26 @@ pub struct BackgroundPart {
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct Animation {
-    should_loop: bool,
-    index: usize,
+    // None keeps looping forever
+    plays_left: Option<u32>,
+    // The sprite indices in the order they're shown
+    order: Vec<usize>,
+    position: usize,
     sprites: Vec<Sprite>,
     speed: Speed,
+    frame_durations: Vec<u32>,
     time_to_next_change: u32,
 }
 
+// A named list of sprites that can be copied into an Animate action
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct FrameAnimation {
+    pub sprites: Vec<Sprite>,
+    pub frame_durations: Vec<u32>,
+    #[serde(default)]
+    pub playback: Playback,
+}
+
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub enum AnimationStatus {
     Animating(Animation),
@@ -2117,7 +4949,7 @@ pub enum AnimationStatus {
     None,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub enum ActiveMotion {
     GoStraight {
         velocity: Vec2,
@@ -2128,7 +4960,7 @@ pub enum ActiveMotion {
         speed: Speed,
     },
     Target {
-        target: Target,
+        target: ActiveTarget,
         target_type: TargetType,
         offset: Vec2,
         speed: Speed,
@@ -2143,7 +4975,43 @@ pub enum ActiveMotion {
     },
     Stop,
 }
-#[derive(Clone, Debug)]
+impl ActiveMotion {
+    fn velocity(&self) -> Vec2 {
+        match self {
+            ActiveMotion::Accelerate { velocity, .. } => *velocity,
+            ActiveMotion::GoStraight { velocity } => *velocity,
+            ActiveMotion::Roam { movement_type, .. } => match movement_type {
+                ActiveRoam::Insect { velocity } => *velocity,
+                ActiveRoam::Bounce { velocity, .. } => *velocity,
+                ActiveRoam::Reflect { velocity, .. } => *velocity,
+                _ => Vec2::zero(),
+            },
+            ActiveMotion::Target { .. } => Vec2::zero(),
+            ActiveMotion::SlowDown { velocity, .. } => *velocity,
+            ActiveMotion::Stop => Vec2::zero(),
+        }
+    }
+
+    pub fn speed(&self) -> f32 {
+        match self {
+            ActiveMotion::Target { speed, .. }
+            | ActiveMotion::Roam {
+                movement_type: ActiveRoam::Wiggle,
+                speed,
+                ..
+            } => speed.as_value(),
+            _ => self.velocity().magnitude(),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub enum ActiveTarget {
+    Object { index: usize },
+    Mouse,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub enum ActiveRoam {
     Wiggle,
     Insect {
@@ -2161,22 +5029,41 @@ pub enum ActiveRoam {
     },
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct Object {
     pub sprite: Sprite,
     pub position: Vec2,
     pub size: Size,
     pub angle: f32,
     pub origin: Option<Vec2>,
-    pub collision_area: Option<AABB>,
+    pub collision_shape: Option<CollisionShape>,
+    pub collision_mode: CollisionMode,
     pub flip: Flip,
     pub layer: u8,
+    pub depth: f32,
     pub instructions: Vec<Instruction>,
     pub queued_motion: Vec<Motion>,
     pub active_motion: ActiveMotion,
     pub switch: SwitchState,
+    pub tags: Vec<String>,
     pub timer: Option<u32>,
     pub animation: AnimationStatus,
+    #[serde(skip)]
+    sprite_shape: Option<SpriteCollisionShape>,
+}
+
+// The collision shape made from a shape sprite, kept until the sprite or size changes
+#[derive(Clone, Debug)]
+struct SpriteCollisionShape {
+    sprite: VectorSprite,
+    size: Size,
+    shape: CollisionShape,
+}
+
+impl SpriteCollisionShape {
+    fn is_for(&self, sprite: &VectorSprite, size: Size) -> bool {
+        self.size == size && self.sprite == *sprite
+    }
 }
 
 #[derive(Copy, Clone, Serialize, Deserialize, Debug)]
@@ -2193,7 +5080,7 @@ pub struct Mouse {
 
 pub type Objects = IndexMap<String, Object>;
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub struct FrameInfo {
     pub total: FrameCount,
     pub ran: u32,
@@ -2201,16 +5088,98 @@ pub struct FrameInfo {
     pub to_run: u32,
     pub total_time_elapsed: f64,
     pub previous_frame_time: f64,
+    // Frames held back by slow motion, which still take up real time
+    pub slowed: u32,
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Game {
     pub objects: Objects,
+    #[serde(skip)]
+    table: InstructionTable,
+    #[serde(skip)]
+    collisions: CollisionCache,
+    #[serde(skip)]
+    masks: CollisionMasks,
+    contacts: Vec<Vec<bool>>,
     pub background: Vec<BackgroundPart>,
     pub frames: FrameInfo,
     pub status: GameStatus,
     pub intro_text: String,
     effect: Effect,
+    timed_effects: Vec<TimedEffect>,
     pub difficulty: u32,
+    pub layer_sorting: LayerSorting,
+    pub topmost_mouse_over: bool,
+    #[serde(skip)]
+    hover_order: Vec<usize>,
+    #[serde(skip)]
+    objects_generation: u32,
+    // Presses and releases made while slow motion held frames back, oldest first
+    #[serde(default)]
+    held_clicks: Vec<ButtonState>,
+}
+
+#[derive(Debug, Clone)]
+struct CompiledTrigger {
+    trigger: Trigger,
+    targets: TriggerTargets,
+}
+
+#[derive(Debug, Copy, Clone, Default)]
+struct TriggerTargets {
+    other: Option<usize>,
+    compared: Option<usize>,
+    contact: Option<usize>,
+}
+
+// A collision whose touching state is remembered between frames for enter and exit triggers
+#[derive(Debug, Clone)]
+struct Contact {
+    with: CollisionWith,
+    other: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+enum CompiledAction {
+    Action {
+        action: Action,
+        other: Option<usize>,
+    },
+    Motion(usize),
+    Random(Vec<CompiledAction>),
+}
+
+#[derive(Debug, Clone)]
+struct CompiledMotion {
+    motion: Motion,
+    other: Option<usize>,
+    directions: Vec<CompassDirection>,
+}
+
+#[derive(Debug, Clone)]
+struct CompiledInstruction {
+    triggers: Vec<CompiledTrigger>,
+    actions: Vec<CompiledAction>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct InstructionTable {
+    // The game's objects generation when the instructions were compiled
+    generation: u32,
+    objects: Vec<Vec<CompiledInstruction>>,
+    motions: Vec<CompiledMotion>,
+    triggered: Vec<usize>,
+    queued_motions: Vec<usize>,
+    clamps: Vec<AABB>,
+    contacts: Vec<Vec<Contact>>,
+    mouse_targets: Vec<usize>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Snapshot<R> {
+    pub game: Game,
+    pub rng: R,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -2223,7 +5192,14 @@ pub struct GameData {
     pub asset_files: AssetFiles,
     pub length: Length,
     pub intro_text: Option<String>,
+    #[serde(default)]
+    pub intro_text_key: Option<String>,
     pub attribution: String,
+    #[serde(default)]
+    pub layer_sorting: LayerSorting,
+    // Games made before this was added let every object under the mouse react to it
+    #[serde(default)]
+    pub topmost_mouse_over: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -2232,12 +5208,14 @@ pub struct SerialiseMusic {
     pub looped: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
 pub struct AssetFiles {
     pub images: HashMap<String, String>,
     pub audio: HashMap<String, String>,
     pub music: Option<SerialiseMusic>,
     pub fonts: HashMap<String, FontLoadInfo>,
+    #[serde(default)]
+    pub animations: HashMap<String, FrameAnimation>,
 }
 
 #[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq)]
@@ -2261,10 +5239,21 @@ pub struct SerialiseObject {
     pub size: Size,
     pub angle: f32,
     pub origin: Option<Vec2>,
-    pub collision_area: Option<AABB>,
+    #[serde(
+        alias = "collision_area",
+        default,
+        deserialize_with = "shape::deserialize_collision_shape"
+    )]
+    pub collision_shape: Option<CollisionShape>,
+    #[serde(default)]
+    pub collision_mode: CollisionMode,
     pub flip: Flip,
     pub layer: u8,
+    #[serde(default)]
+    pub depth: f32,
     pub switch: Switch,
+    #[serde(default)]
+    pub tags: Vec<String>,
     pub instructions: Vec<Instruction>,
 }
 
@@ -2300,10 +5289,13 @@ impl Default for SerialiseObject {
             size: Size::new(100.0, 100.0),
             angle: 0.0,
             origin: None,
-            collision_area: None,
+            collision_shape: None,
+            collision_mode: CollisionMode::Area,
             flip: Flip::default(),
             layer: 0,
+            depth: 0.0,
             switch: Switch::Off,
+            tags: Vec::new(),
             instructions: Vec::new(),
         }
     }
@@ -2346,6 +5338,58 @@ impl SerialiseObject {
         )
     }
 
+    pub fn collision_outline(&self) -> Vec<Vec2> {
+        let shape = active_collision_shape(&self.collision_shape, &self.sprite, self.size)
+            .unwrap_or_else(|| {
+                Cow::Owned(CollisionShape::AABB(AABB::new(
+                    0.0,
+                    0.0,
+                    self.size.width,
+                    self.size.height,
+                )))
+            });
+        if let CollisionShape::Polygon { points } = shape.as_ref() {
+            if points.len() < 3 {
+                return points
+                    .iter()
+                    .map(|point| self.shape_point_to_world(*point))
+                    .collect();
+            }
+        }
+        shape::world_shape(&shape.flipped(self.flip, self.size), |point| {
+            self.rotate_into_world(point)
+        })
+        .outline()
+    }
+
+    // Collision shape points are relative to the top left of the unflipped object
+    pub fn shape_point_to_world(&self, point: Vec2) -> Vec2 {
+        self.rotate_into_world(shape::flip_point(point, self.flip, self.size))
+    }
+
+    pub fn world_to_shape_point(&self, point: Vec2) -> Vec2 {
+        let origin = self.origin_in_world();
+        let angle = self.angle.to_radians();
+        let c = angle.cos();
+        let s = angle.sin();
+        let offset = point - origin;
+        let point = Vec2::new(offset.x * c + offset.y * s, -offset.x * s + offset.y * c) + origin
+            - self.aabb().min;
+        shape::flip_point(point, self.flip, self.size)
+    }
+
+    fn rotate_into_world(&self, point: Vec2) -> Vec2 {
+        let origin = self.origin_in_world();
+        let angle = self.angle.to_radians();
+        let c = angle.cos();
+        let s = angle.sin();
+        let point = point + self.aabb().min - origin;
+        Vec2::new(
+            point.x * c - point.y * s + origin.x,
+            point.x * s + point.y * c + origin.y,
+        )
+    }
+
     pub fn full_poly(&self) -> c2::Poly {
         let aabb = self.aabb();
         let origin = self.origin_in_world();
@@ -2382,19 +5426,19 @@ impl Default for GameData {
             asset_files: AssetFiles::default(),
             length: Length::Seconds(4.0),
             intro_text: None,
+            intro_text_key: None,
             attribution: "".to_string(),
+            layer_sorting: LayerSorting::new(),
+            topmost_mouse_over: true,
         }
     }
 }
 
-impl Default for AssetFiles {
-    fn default() -> AssetFiles {
-        AssetFiles {
-            images: HashMap::new(),
-            audio: HashMap::new(),
-            music: None,
-            fonts: HashMap::new(),
-        }
+impl GameData {
+    pub fn uses_pixel_collision(&self) -> bool {
+        self.objects
+            .iter()
+            .any(|object| object.collision_mode == CollisionMode::Pixels)
     }
 }
 
@@ -2411,6 +5455,55 @@ impl Object {
 
 use std::fmt;
 
+impl fmt::Display for Side {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Side::Top => write!(f, "Top"),
+            Side::Bottom => write!(f, "Bottom"),
+            Side::Left => write!(f, "Left"),
+            Side::Right => write!(f, "Right"),
+        }
+    }
+}
+
+impl fmt::Display for DistanceEnd {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DistanceEnd::Object { name } => write!(f, "{}", name),
+            DistanceEnd::Tag { tag } => write!(f, "the nearest object tagged {}", tag),
+            DistanceEnd::Mouse => write!(f, "the mouse"),
+            DistanceEnd::Point(point) => write!(f, "{}, {}", point.x, point.y),
+        }
+    }
+}
+
+impl fmt::Display for NumericProperty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NumericProperty::X => write!(f, "x"),
+            NumericProperty::Y => write!(f, "y"),
+            NumericProperty::Width => write!(f, "width"),
+            NumericProperty::Height => write!(f, "height"),
+            NumericProperty::Angle => write!(f, "angle"),
+            NumericProperty::Layer => write!(f, "layer"),
+            NumericProperty::Speed => write!(f, "speed"),
+        }
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Comparison::Equal => write!(f, "is"),
+            Comparison::NotEqual => write!(f, "isn't"),
+            Comparison::LessThan => write!(f, "is less than"),
+            Comparison::LessThanOrEqual => write!(f, "is at most"),
+            Comparison::GreaterThan => write!(f, "is greater than"),
+            Comparison::GreaterThanOrEqual => write!(f, "is at least"),
+        }
+    }
+}
+
 impl fmt::Display for Trigger {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -2425,14 +5518,70 @@ impl fmt::Display for Trigger {
                 (*start as f32) / 60.0,
                 (*end as f32) / 60.0,
             ),
-            Trigger::Collision(CollisionWith::Object { name }) => {
-                write!(f, "While this object collides with {}", name)
-            }
-            Trigger::Collision(CollisionWith::Area(area)) => write!(
+            Trigger::Collision(CollisionWith::Object { name }, phase) => match phase {
+                CollisionPhase::Enter => {
+                    write!(f, "When this object starts colliding with {}", name)
+                }
+                CollisionPhase::Stay => write!(f, "While this object collides with {}", name),
+                CollisionPhase::Exit => {
+                    write!(f, "When this object stops colliding with {}", name)
+                }
+            },
+            Trigger::Collision(CollisionWith::Area(area), phase) => write!(
                 f,
-                "While this object is inside {}, {} and {}, {}",
-                area.min.x, area.min.y, area.max.x, area.max.y
+                "{} {}, {} and {}, {}",
+                match phase {
+                    CollisionPhase::Enter => "When this object enters",
+                    CollisionPhase::Stay => "While this object is inside",
+                    CollisionPhase::Exit => "When this object leaves",
+                },
+                area.min.x,
+                area.min.y,
+                area.max.x,
+                area.max.y
             ),
+            Trigger::Bounds { area, condition } => {
+                let area = if *area == screen_area() {
+                    "the screen".to_string()
+                } else {
+                    format!(
+                        "{}, {} and {}, {}",
+                        area.min.x, area.min.y, area.max.x, area.max.y
+                    )
+                };
+                match condition {
+                    BoundsCondition::FullyInside => {
+                        write!(f, "While this object is fully inside {}", area)
+                    }
+                    BoundsCondition::FullyOutside => {
+                        write!(f, "While this object is fully outside {}", area)
+                    }
+                    BoundsCondition::TouchingEdge => {
+                        write!(f, "While this object is touching the edge of {}", area)
+                    }
+                    BoundsCondition::CrossedEdge(side) => write!(
+                        f,
+                        "While this object is past the {} edge of {}",
+                        side.to_string().to_lowercase(),
+                        area
+                    ),
+                }
+            }
+            Trigger::Distance {
+                from,
+                to,
+                measure,
+                comparison,
+                value,
+            } => {
+                write!(f, "While the distance ")?;
+                if *measure == DistanceMeasure::Edge {
+                    write!(f, "between the edges of {} and {}", from, to)?;
+                } else {
+                    write!(f, "from {} to {}", from, to)?;
+                }
+                write!(f, " {} {}", comparison, value)
+            }
             Trigger::WinStatus(status) => match status {
                 WinStatus::Won => write!(f, "While you have won the game"),
                 WinStatus::Lost => write!(f, "While you have lost the game"),
@@ -2502,7 +5651,23 @@ impl fmt::Display for Trigger {
                 PropertyCheck::FinishedAnimation => {
                     write!(f, "When {}'s animation is finished", name)
                 }
+                PropertyCheck::AnimationFrame { index } => {
+                    write!(f, "While {}'s animation is on frame {}", name, index)
+                }
                 PropertyCheck::Timer => write!(f, "When {}'s timer hits zero", name),
+                PropertyCheck::Compare {
+                    property,
+                    comparison,
+                    to,
+                } => {
+                    write!(f, "While {}'s {} {} ", name, property, comparison)?;
+                    match to {
+                        CompareTo::Value(value) => write!(f, "{}", value),
+                        CompareTo::Object { name, property } => {
+                            write!(f, "{}'s {}", name, property)
+                        }
+                    }
+                }
             },
             Trigger::Random { chance } => write!(f, "With a {}% chance", chance * 100.0),
             Trigger::DifficultyLevel { levels } => {
@@ -2708,18 +5873,48 @@ impl fmt::Display for Action {
             Action::Effect(effect) => match effect {
                 Effect::Freeze => write!(f, "Freeze the screen"),
                 Effect::None => write!(f, "No effect"),
+                Effect::SlowMotion { every, frames } => write!(
+                    f,
+                    "Slow motion, running every {} frames for {} frames",
+                    every, frames
+                ),
+                Effect::Shake { amplitude, frames } => {
+                    write!(f, "Shake the screen by {} for {} frames", amplitude, frames)
+                }
+                Effect::Flash { frames, .. } => {
+                    write!(f, "Flash the screen for {} frames", frames)
+                }
+                Effect::Fade { fade, frames, .. } => match fade {
+                    Fade::In => write!(f, "Fade in over {} frames", frames),
+                    Fade::Out => write!(f, "Fade out over {} frames", frames),
+                },
+                Effect::Invert { frames } => {
+                    write!(f, "Invert the screen's colours for {} frames", frames)
+                }
             },
             Action::PlaySound { name } => write!(f, "Play the {} sound", name),
             Action::StopMusic => write!(f, "Stop the music"),
             Action::Animate {
                 animation_type,
                 speed,
+                playback,
                 ..
             } => {
-                if let AnimationType::Loop = animation_type {
-                    write!(f, "Loops an animation {}", speed)
-                } else {
-                    write!(f, "Plays an animation once {}", speed)
+                let playback = match playback {
+                    Playback::Forward => "",
+                    Playback::Reverse => " in reverse",
+                    Playback::PingPong => " back and forth",
+                };
+                match animation_type {
+                    AnimationType::Loop => write!(f, "Loops an animation{} {}", playback, speed),
+                    AnimationType::PlayOnce => {
+                        write!(f, "Plays an animation{} once {}", playback, speed)
+                    }
+                    AnimationType::LoopTimes(times) => write!(
+                        f,
+                        "Plays an animation{} {} times {}",
+                        playback, times, speed
+                    ),
                 }
             }
             Action::DrawText {
@@ -2728,6 +5923,7 @@ impl fmt::Display for Action {
                 colour,
                 resize: _resize,
                 justify,
+                ..
             } => {
                 let change_size = "";
                 let colour = format!(
@@ -2737,6 +5933,7 @@ impl fmt::Display for Action {
                 let justification = match justify {
                     JustifyText::Left => "left",
                     JustifyText::Centre => "centre",
+                    JustifyText::Right => "right",
                 };
                 write!(
                     f,
@@ -2842,17 +6039,6 @@ impl Not for TextResize {
     }
 }
 
-impl Not for AnimationType {
-    type Output = AnimationType;
-
-    fn not(self) -> Self::Output {
-        match self {
-            AnimationType::Loop => AnimationType::PlayOnce,
-            AnimationType::PlayOnce => AnimationType::Loop,
-        }
-    }
-}
-
 impl Not for Switch {
     type Output = Switch;
 
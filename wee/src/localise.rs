@@ -0,0 +1,149 @@
+// Games keep their text in the default language. Text with a key is looked up in the string
+// tables beside the game, one for each language, in a `text` directory
+use crate::{Action, GameData};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+};
+use wee_common::WeeResult;
+
+pub const DEFAULT_LANGUAGE: &str = "en";
+pub const TEXT_DIRECTORY: &str = "text";
+// Lists every key with its text in the default language, for translators to copy
+pub const TEMPLATE_NAME: &str = "template";
+
+pub type StringTable = BTreeMap<String, String>;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Translations {
+    // The chosen language first, then the default language
+    tables: Vec<StringTable>,
+}
+
+impl Translations {
+    pub fn new(tables: Vec<StringTable>) -> Translations {
+        Translations { tables }
+    }
+
+    // Languages without a table are skipped
+    pub fn load(directory: impl AsRef<Path>, language: &str) -> WeeResult<Translations> {
+        let mut tables = Vec::new();
+        for language in fallback_languages(language) {
+            let path = table_path(&directory, language);
+            if path.exists() {
+                tables.push(parse_string_table(&fs::read_to_string(&path)?)?);
+            }
+        }
+        Ok(Translations::new(tables))
+    }
+
+    pub fn lookup(&self, key: &str) -> Option<&str> {
+        self.tables
+            .iter()
+            .find_map(|table| table.get(key))
+            .map(String::as_str)
+    }
+
+    // For text the host shows that isn't part of any game
+    pub fn text<'a>(&'a self, key: &str, default: &'a str) -> &'a str {
+        self.lookup(key).unwrap_or(default)
+    }
+}
+
+pub fn fallback_languages(language: &str) -> Vec<&str> {
+    if language == DEFAULT_LANGUAGE {
+        vec![DEFAULT_LANGUAGE]
+    } else {
+        vec![language, DEFAULT_LANGUAGE]
+    }
+}
+
+pub fn table_path(directory: impl AsRef<Path>, language: &str) -> PathBuf {
+    directory
+        .as_ref()
+        .join(TEXT_DIRECTORY)
+        .join(format!("{}.json", language))
+}
+
+pub fn parse_string_table(json: &str) -> WeeResult<StringTable> {
+    Ok(serde_json::from_str(json)?)
+}
+
+fn translate(text: &mut String, key: &Option<String>, translations: &Translations) {
+    if let Some(translated) = key.as_deref().and_then(|key| translations.lookup(key)) {
+        *text = translated.to_string();
+    }
+}
+
+fn draw_text_actions(actions: &mut [Action], f: &mut impl FnMut(&mut String, &mut Option<String>)) {
+    for action in actions {
+        match action {
+            Action::DrawText { text, key, .. } => f(text, key),
+            Action::Random { random_actions } => draw_text_actions(random_actions, f),
+            _ => {}
+        }
+    }
+}
+
+impl GameData {
+    // Text without a translation keeps the text saved in the game
+    pub fn localise(&mut self, translations: &Translations) {
+        if let Some(intro_text) = &mut self.intro_text {
+            translate(intro_text, &self.intro_text_key, translations);
+        }
+        for object in self.objects.iter_mut() {
+            for instruction in object.instructions.iter_mut() {
+                draw_text_actions(&mut instruction.actions, &mut |text, key| {
+                    translate(text, key, translations)
+                });
+            }
+        }
+    }
+
+    // Gives every piece of text without a key one starting with the prefix, then returns each
+    // key with the text saved in the game
+    pub fn extract_text(&mut self, prefix: &str) -> StringTable {
+        // Generated keys mustn't match one already chosen for text later in the game
+        let mut used = BTreeSet::new();
+        used.extend(self.intro_text_key.clone());
+        for object in self.objects.iter_mut() {
+            for instruction in object.instructions.iter_mut() {
+                draw_text_actions(&mut instruction.actions, &mut |_, key| {
+                    used.extend(key.clone())
+                });
+            }
+        }
+
+        let mut table = StringTable::new();
+        let mut add_key = |text: &str, key: &mut Option<String>, name: String| {
+            if text.is_empty() {
+                return;
+            }
+            let key = key.get_or_insert_with(|| {
+                let mut unique = name.clone();
+                let mut count = 1;
+                while table.contains_key(&unique) || used.contains(&unique) {
+                    count += 1;
+                    unique = format!("{} {}", name, count);
+                }
+                unique
+            });
+            table.insert(key.clone(), text.to_string());
+        };
+
+        if let Some(intro_text) = &self.intro_text {
+            let name = format!("{}.intro", prefix);
+            add_key(intro_text, &mut self.intro_text_key, name);
+        }
+        for object in self.objects.iter_mut() {
+            let name = format!("{}.{}", prefix, object.name);
+            for instruction in object.instructions.iter_mut() {
+                draw_text_actions(&mut instruction.actions, &mut |text, key| {
+                    add_key(text, key, name.clone())
+                });
+            }
+        }
+        table
+    }
+}
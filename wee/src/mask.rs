@@ -0,0 +1,302 @@
+use crate::{CollisionMode, GameData, ImageRect, Object, Sprite, WorldShape};
+use std::{collections::HashMap, path::Path, sync::Arc};
+use wee_common::{Vec2, WeeResult, AABB};
+
+const ALPHA_THRESHOLD: u8 = 128;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlphaMask {
+    width: u32,
+    height: u32,
+    solid: Vec<bool>,
+}
+
+impl AlphaMask {
+    pub fn from_rgba(width: u32, height: u32, pixels: &[u8]) -> AlphaMask {
+        let solid = pixels
+            .chunks_exact(4)
+            .take((width * height) as usize)
+            .map(|pixel| pixel[3] >= ALPHA_THRESHOLD)
+            .collect();
+        AlphaMask {
+            width,
+            height,
+            solid,
+        }
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> WeeResult<AlphaMask> {
+        let image = image::load_from_memory(bytes)?.into_rgba8();
+        Ok(AlphaMask::from_rgba(
+            image.width(),
+            image.height(),
+            image.as_raw(),
+        ))
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> WeeResult<AlphaMask> {
+        let bytes = std::fs::read(&path).map_err(|error| {
+            format!(
+                "Couldn't load collision mask from {}: {}",
+                path.as_ref().display(),
+                error
+            )
+        })?;
+        AlphaMask::from_bytes(&bytes)
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn is_solid(&self, x: u32, y: u32) -> bool {
+        x < self.width
+            && y < self.height
+            && self
+                .solid
+                .get((y * self.width + x) as usize)
+                .copied()
+                .unwrap_or(false)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CollisionMasks(Arc<HashMap<String, AlphaMask>>);
+
+impl CollisionMasks {
+    pub fn new(masks: HashMap<String, AlphaMask>) -> CollisionMasks {
+        CollisionMasks(Arc::new(masks))
+    }
+
+    pub fn load(game_data: &GameData, base_path: impl AsRef<Path>) -> WeeResult<CollisionMasks> {
+        if !game_data.uses_pixel_collision() {
+            return Ok(CollisionMasks::default());
+        }
+
+        let base_path = base_path.as_ref().join("images");
+        let mut masks = HashMap::new();
+        for (name, filename) in &game_data.asset_files.images {
+            masks.insert(
+                name.clone(),
+                AlphaMask::from_file(base_path.join(filename))?,
+            );
+        }
+        Ok(CollisionMasks::new(masks))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&AlphaMask> {
+        self.0.get(name)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+struct SolidArea<'a> {
+    // Regions of a sprite sheet only use their part of the image's mask
+    mask: Option<(&'a AlphaMask, ImageRect)>,
+    shape: WorldShape,
+    area: AABB,
+    top_left: Vec2,
+    size: Vec2,
+    flip_horizontal: bool,
+    flip_vertical: bool,
+    origin: Vec2,
+    cos: f32,
+    sin: f32,
+}
+
+impl<'a> SolidArea<'a> {
+    fn new(object: &'a Object, shape: WorldShape, masks: &'a CollisionMasks) -> SolidArea<'a> {
+        let angle = object.angle.to_radians();
+        SolidArea {
+            mask: object.mask(masks),
+            shape,
+            area: object.collision_aabb(),
+            top_left: object.top_left(),
+            size: Vec2::new(object.size.width, object.size.height),
+            flip_horizontal: object.flip.horizontal,
+            flip_vertical: object.flip.vertical,
+            origin: object.origin_in_world(),
+            cos: angle.cos(),
+            sin: angle.sin(),
+        }
+    }
+
+    fn has_mask(&self) -> bool {
+        self.mask.is_some()
+    }
+
+    fn contains(&self, point: Vec2) -> bool {
+        if !self.shape.contains_point(point) {
+            return false;
+        }
+        let point = self.unrotate(point);
+        match (self.mask, self.texel_at(point)) {
+            (Some((mask, _)), Some((x, y))) => mask.is_solid(x, y),
+            (Some(_), None) => false,
+            (None, _) => true,
+        }
+    }
+
+    fn overlaps(&self, bounds: AABB, other: impl Fn(Vec2) -> bool) -> bool {
+        let mask = match self.mask {
+            Some(mask) => mask,
+            None => return false,
+        };
+        let (mask, region) = mask;
+        if self.size.x <= 0.0 || self.size.y <= 0.0 || region.width == 0 || region.height == 0 {
+            return false;
+        }
+
+        let corners = [
+            bounds.min,
+            Vec2::new(bounds.max.x, bounds.min.y),
+            bounds.max,
+            Vec2::new(bounds.min.x, bounds.max.y),
+        ];
+        let mut local = AABB {
+            min: self.unrotate(corners[0]),
+            max: self.unrotate(corners[0]),
+        };
+        for corner in corners.iter().skip(1) {
+            let corner = self.unrotate(*corner);
+            local.min.x = local.min.x.min(corner.x);
+            local.min.y = local.min.y.min(corner.y);
+            local.max.x = local.max.x.max(corner.x);
+            local.max.y = local.max.y.max(corner.y);
+        }
+        local.min.x = local.min.x.max(self.area.min.x);
+        local.min.y = local.min.y.max(self.area.min.y);
+        local.max.x = local.max.x.min(self.area.max.x);
+        local.max.y = local.max.y.min(self.area.max.y);
+        if !(local.min.x < local.max.x && local.min.y < local.max.y) {
+            return false;
+        }
+
+        let texel_size = Vec2::new(
+            self.size.x / region.width as f32,
+            self.size.y / region.height as f32,
+        );
+        let column = |x: f32| ((x - self.top_left.x) / texel_size.x).floor();
+        let row = |y: f32| ((y - self.top_left.y) / texel_size.y).floor();
+        let clamp_column = |x: f32| x.max(0.0).min(region.width as f32 - 1.0) as u32;
+        let clamp_row = |y: f32| y.max(0.0).min(region.height as f32 - 1.0) as u32;
+        let columns = clamp_column(column(local.min.x))..=clamp_column(column(local.max.x));
+        let rows = clamp_row(row(local.min.y))..=clamp_row(row(local.max.y));
+
+        for row in rows {
+            for column in columns.clone() {
+                let point = Vec2::new(
+                    self.top_left.x + (column as f32 + 0.5) * texel_size.x,
+                    self.top_left.y + (row as f32 + 0.5) * texel_size.y,
+                );
+                let is_solid = match self.texel_at(point) {
+                    Some((x, y)) => mask.is_solid(x, y),
+                    None => false,
+                };
+                if is_solid {
+                    let point = self.rotate(point);
+                    if self.shape.contains_point(point) && other(point) {
+                        return true;
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    // The pixel of the whole image that the point falls on
+    fn texel_at(&self, point: Vec2) -> Option<(u32, u32)> {
+        let (_, region) = self.mask?;
+        let u = (point.x - self.top_left.x) / self.size.x;
+        let v = (point.y - self.top_left.y) / self.size.y;
+        if !(0.0..1.0).contains(&u) || !(0.0..1.0).contains(&v) {
+            return None;
+        }
+        let u = if self.flip_horizontal { 1.0 - u } else { u };
+        let v = if self.flip_vertical { 1.0 - v } else { v };
+        let x = ((u * region.width as f32) as u32).min(region.width.saturating_sub(1));
+        let y = ((v * region.height as f32) as u32).min(region.height.saturating_sub(1));
+        Some((region.x + x, region.y + y))
+    }
+
+    fn rotate(&self, point: Vec2) -> Vec2 {
+        let offset = point - self.origin;
+        Vec2::new(
+            offset.x * self.cos - offset.y * self.sin + self.origin.x,
+            offset.x * self.sin + offset.y * self.cos + self.origin.y,
+        )
+    }
+
+    fn unrotate(&self, point: Vec2) -> Vec2 {
+        let offset = point - self.origin;
+        Vec2::new(
+            offset.x * self.cos + offset.y * self.sin + self.origin.x,
+            -offset.x * self.sin + offset.y * self.cos + self.origin.y,
+        )
+    }
+}
+
+pub(crate) fn objects_overlap(
+    a: &Object,
+    a_shape: WorldShape,
+    b: &Object,
+    b_shape: WorldShape,
+    masks: &CollisionMasks,
+) -> bool {
+    let a = SolidArea::new(a, a_shape, masks);
+    let b = SolidArea::new(b, b_shape, masks);
+    if a.has_mask() {
+        a.overlaps(b_shape.bounds(), |point| b.contains(point))
+    } else if b.has_mask() {
+        b.overlaps(a_shape.bounds(), |point| a.contains(point))
+    } else {
+        true
+    }
+}
+
+pub(crate) fn overlaps_area(
+    object: &Object,
+    shape: WorldShape,
+    area: AABB,
+    masks: &CollisionMasks,
+) -> bool {
+    let object = SolidArea::new(object, shape, masks);
+    !object.has_mask()
+        || object.overlaps(area, |point| {
+            point.x >= area.min.x
+                && point.y >= area.min.y
+                && point.x <= area.max.x
+                && point.y <= area.max.y
+        })
+}
+
+pub(crate) fn contains_point(
+    object: &Object,
+    shape: WorldShape,
+    point: Vec2,
+    masks: &CollisionMasks,
+) -> bool {
+    let object = SolidArea::new(object, shape, masks);
+    !object.has_mask() || object.contains(point)
+}
+
+impl Object {
+    fn mask<'a>(&self, masks: &'a CollisionMasks) -> Option<(&'a AlphaMask, ImageRect)> {
+        match (&self.collision_mode, &self.sprite) {
+            (CollisionMode::Pixels, Sprite::Image { name }) => masks
+                .get(name)
+                .map(|mask| (mask, ImageRect::new(0, 0, mask.width(), mask.height()))),
+            (CollisionMode::Pixels, Sprite::Region { image, rect }) => masks
+                .get(image)
+                .map(|mask| (mask, rect.clamped(mask.width(), mask.height()))),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,365 @@
+use c2::prelude::*;
+use serde::{Deserialize, Deserializer, Serialize};
+use wee_common::{Flip, Size, Vec2, AABB};
+
+pub const MAX_POLYGON_POINTS: usize = 8;
+const OUTLINE_SEGMENTS: usize = 32;
+
+// Positions are relative to the top left of the object, the same as the old collision area
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum CollisionShape {
+    AABB(AABB),
+    Circle { centre: Vec2, radius: f32 },
+    Capsule { start: Vec2, end: Vec2, radius: f32 },
+    Polygon { points: Vec<Vec2> },
+}
+
+#[derive(Debug, Copy, Clone)]
+pub enum WorldShape {
+    Poly(c2::Poly),
+    Circle { centre: Vec2, radius: f32 },
+    Capsule { start: Vec2, end: Vec2, radius: f32 },
+}
+
+macro_rules! with_shape {
+    ($shape:expr, $name:ident => $body:expr) => {
+        match *$shape {
+            WorldShape::Poly(ref $name) => $body,
+            WorldShape::Circle { centre, radius } => {
+                let $name = &c2::Circle::new(c2v(centre), radius);
+                $body
+            }
+            WorldShape::Capsule { start, end, radius } => {
+                let $name = &c2::Capsule::new(c2v(start), c2v(end), radius);
+                $body
+            }
+        }
+    };
+}
+
+impl CollisionShape {
+    pub fn bounds(&self) -> AABB {
+        match self {
+            CollisionShape::AABB(area) => *area,
+            CollisionShape::Circle { centre, radius } => AABB {
+                min: Vec2::new(centre.x - radius, centre.y - radius),
+                max: Vec2::new(centre.x + radius, centre.y + radius),
+            },
+            CollisionShape::Capsule { start, end, radius } => AABB {
+                min: Vec2::new(start.x.min(end.x) - radius, start.y.min(end.y) - radius),
+                max: Vec2::new(start.x.max(end.x) + radius, start.y.max(end.y) + radius),
+            },
+            CollisionShape::Polygon { points } => {
+                let mut points = points.iter();
+                let first = points.next().copied().unwrap_or_else(Vec2::zero);
+                points.fold(
+                    AABB {
+                        min: first,
+                        max: first,
+                    },
+                    |bounds, point| AABB {
+                        min: Vec2::new(bounds.min.x.min(point.x), bounds.min.y.min(point.y)),
+                        max: Vec2::new(bounds.max.x.max(point.x), bounds.max.y.max(point.y)),
+                    },
+                )
+            }
+        }
+    }
+
+    pub fn flipped(&self, flip: Flip, size: Size) -> CollisionShape {
+        let flip_point = |point: Vec2| flip_point(point, flip, size);
+        match self {
+            CollisionShape::AABB(mut area) => {
+                if flip.horizontal {
+                    let difference_from_left = area.min.x;
+                    let difference_from_right = size.width - area.max.x;
+                    area.min.x = difference_from_right;
+                    area.max.x = size.width - difference_from_left;
+                }
+                if flip.vertical {
+                    let difference_from_top = area.min.y;
+                    let difference_from_bottom = size.height - area.max.y;
+                    area.min.y = difference_from_bottom;
+                    area.max.y = size.height - difference_from_top;
+                }
+                CollisionShape::AABB(area)
+            }
+            CollisionShape::Circle { centre, radius } => CollisionShape::Circle {
+                centre: flip_point(*centre),
+                radius: *radius,
+            },
+            CollisionShape::Capsule { start, end, radius } => CollisionShape::Capsule {
+                start: flip_point(*start),
+                end: flip_point(*end),
+                radius: *radius,
+            },
+            CollisionShape::Polygon { points } => CollisionShape::Polygon {
+                points: points.iter().copied().map(flip_point).collect(),
+            },
+        }
+    }
+
+    pub fn scale(&mut self, old_size: Size, size: Size) {
+        let difference = Size::new(size.width / old_size.width, size.height / old_size.height);
+        let scale_point = |point: &mut Vec2| {
+            *point = Vec2::new(
+                if old_size.width == 0.0 {
+                    size.width
+                } else {
+                    point.x * difference.width
+                },
+                if old_size.height == 0.0 {
+                    size.height
+                } else {
+                    point.y * difference.height
+                },
+            )
+        };
+        let scale_radius = |radius: &mut f32| {
+            if old_size.width != 0.0 && old_size.height != 0.0 {
+                *radius *= difference.width.min(difference.height);
+            }
+        };
+        match self {
+            CollisionShape::AABB(area) => {
+                scale_point(&mut area.min);
+                scale_point(&mut area.max);
+            }
+            CollisionShape::Circle { centre, radius } => {
+                scale_point(centre);
+                scale_radius(radius);
+            }
+            CollisionShape::Capsule { start, end, radius } => {
+                scale_point(start);
+                scale_point(end);
+                scale_radius(radius);
+            }
+            CollisionShape::Polygon { points } => points.iter_mut().for_each(scale_point),
+        }
+    }
+}
+
+impl WorldShape {
+    pub fn collides_with(&self, other: &WorldShape) -> bool {
+        with_shape!(self, a => with_shape!(other, b => a.collides_with(b)))
+    }
+
+    pub fn collides_with_area(&self, area: AABB) -> bool {
+        let area = c2::AABB::new(c2v(area.min), c2v(area.max));
+        with_shape!(self, shape => shape.collides_with(&area))
+    }
+
+    pub fn manifold(&self, other: &WorldShape) -> c2::Manifold {
+        with_shape!(self, a => with_shape!(other, b => a.manifold(b)))
+    }
+
+    pub fn moved_manifold(&self, velocity: Vec2, other: &WorldShape) -> c2::Manifold {
+        match self {
+            WorldShape::Poly(poly) => {
+                let transformation =
+                    c2::Transformation::new([velocity.x, velocity.y], c2::Rotation::zero());
+                let moved = (*poly, transformation);
+                with_shape!(other, b => moved.manifold(b))
+            }
+            _ => self.moved(velocity).manifold(other),
+        }
+    }
+
+    // Zero when the shapes overlap
+    pub fn distance_to(&self, other: &WorldShape) -> f32 {
+        with_shape!(self, a => with_shape!(other, b => a.gjk(b).use_radius(true).run().distance()))
+    }
+
+    pub fn contains_point(&self, point: Vec2) -> bool {
+        match self {
+            WorldShape::Poly(poly) => {
+                poly.gjk(&c2::Circle::new(c2v(point), 1.0))
+                    .use_radius(false)
+                    .run()
+                    .distance()
+                    == 0.0
+            }
+            WorldShape::Circle { centre, radius } => (point - *centre).magnitude() <= *radius,
+            WorldShape::Capsule { start, end, radius } => {
+                distance_to_segment(point, *start, *end) <= *radius
+            }
+        }
+    }
+
+    pub fn bounds(&self) -> AABB {
+        match *self {
+            WorldShape::Poly(poly) => {
+                let first = from_c2v(poly.get_vert(0));
+                (1..poly.count()).fold(
+                    AABB {
+                        min: first,
+                        max: first,
+                    },
+                    |bounds, i| {
+                        let vert = from_c2v(poly.get_vert(i));
+                        AABB {
+                            min: Vec2::new(bounds.min.x.min(vert.x), bounds.min.y.min(vert.y)),
+                            max: Vec2::new(bounds.max.x.max(vert.x), bounds.max.y.max(vert.y)),
+                        }
+                    },
+                )
+            }
+            WorldShape::Circle { centre, radius } => {
+                CollisionShape::Circle { centre, radius }.bounds()
+            }
+            WorldShape::Capsule { start, end, radius } => {
+                CollisionShape::Capsule { start, end, radius }.bounds()
+            }
+        }
+    }
+
+    // Points around the edge of the shape, for drawing
+    pub fn outline(&self) -> Vec<Vec2> {
+        match *self {
+            WorldShape::Poly(poly) => (0..poly.count())
+                .map(|i| from_c2v(poly.get_vert(i)))
+                .collect(),
+            WorldShape::Circle { centre, radius } => arc(
+                centre,
+                radius,
+                0.0,
+                std::f32::consts::PI * 2.0,
+                OUTLINE_SEGMENTS,
+            ),
+            WorldShape::Capsule { start, end, radius } => {
+                let direction = end - start;
+                let angle = direction.y.atan2(direction.x);
+                let half_turn = std::f32::consts::PI;
+                let mut points = arc(
+                    end,
+                    radius,
+                    angle - half_turn / 2.0,
+                    half_turn,
+                    OUTLINE_SEGMENTS / 2,
+                );
+                points.extend(arc(
+                    start,
+                    radius,
+                    angle + half_turn / 2.0,
+                    half_turn,
+                    OUTLINE_SEGMENTS / 2,
+                ));
+                points
+            }
+        }
+    }
+
+    pub fn moved(&self, offset: Vec2) -> WorldShape {
+        match self {
+            WorldShape::Poly(poly) => {
+                let points: Vec<c2::Vec2> = (0..poly.count())
+                    .map(|i| c2v(from_c2v(poly.get_vert(i)) + offset))
+                    .collect();
+                WorldShape::Poly(c2::Poly::from_slice(&points))
+            }
+            WorldShape::Circle { centre, radius } => WorldShape::Circle {
+                centre: *centre + offset,
+                radius: *radius,
+            },
+            WorldShape::Capsule { start, end, radius } => WorldShape::Capsule {
+                start: *start + offset,
+                end: *end + offset,
+                radius: *radius,
+            },
+        }
+    }
+}
+
+// Games saved before collision shapes store a plain collision area
+pub(crate) fn deserialize_collision_shape<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<CollisionShape>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum SavedShape {
+        Shape(CollisionShape),
+        Area(AABB),
+    }
+
+    Ok(
+        Option::<SavedShape>::deserialize(deserializer)?.map(|shape| match shape {
+            SavedShape::Shape(shape) => shape,
+            SavedShape::Area(area) => CollisionShape::AABB(area),
+        }),
+    )
+}
+
+pub(crate) fn world_shape(shape: &CollisionShape, to_world: impl Fn(Vec2) -> Vec2) -> WorldShape {
+    match shape {
+        CollisionShape::AABB(area) => {
+            let points = [
+                area.min,
+                Vec2::new(area.max.x, area.min.y),
+                area.max,
+                Vec2::new(area.min.x, area.max.y),
+            ];
+            let points: Vec<c2::Vec2> = points.iter().map(|point| c2v(to_world(*point))).collect();
+            WorldShape::Poly(c2::Poly::from_slice(&points))
+        }
+        CollisionShape::Circle { centre, radius } => WorldShape::Circle {
+            centre: to_world(*centre),
+            radius: radius.max(0.0),
+        },
+        CollisionShape::Capsule { start, end, radius } => WorldShape::Capsule {
+            start: to_world(*start),
+            end: to_world(*end),
+            radius: radius.max(0.0),
+        },
+        CollisionShape::Polygon { points } => {
+            let points: Vec<c2::Vec2> = points
+                .iter()
+                .take(MAX_POLYGON_POINTS)
+                .map(|point| c2v(to_world(*point)))
+                .collect();
+            WorldShape::Poly(c2::Poly::from_slice(&points))
+        }
+    }
+}
+
+pub(crate) fn flip_point(point: Vec2, flip: Flip, size: Size) -> Vec2 {
+    Vec2::new(
+        if flip.horizontal {
+            size.width - point.x
+        } else {
+            point.x
+        },
+        if flip.vertical {
+            size.height - point.y
+        } else {
+            point.y
+        },
+    )
+}
+
+fn arc(centre: Vec2, radius: f32, start_angle: f32, sweep: f32, segments: usize) -> Vec<Vec2> {
+    (0..=segments)
+        .map(|i| {
+            let angle = start_angle + sweep * i as f32 / segments as f32;
+            centre + Vec2::new(angle.cos(), angle.sin()) * radius
+        })
+        .collect()
+}
+
+fn c2v(v: Vec2) -> c2::Vec2 {
+    c2::Vec2::new(v.x, v.y)
+}
+
+fn from_c2v(v: c2::Vec2) -> Vec2 {
+    Vec2::new(v.x(), v.y())
+}
+
+fn distance_to_segment(point: Vec2, start: Vec2, end: Vec2) -> f32 {
+    let segment = end - start;
+    let length_squared = segment.x * segment.x + segment.y * segment.y;
+    if length_squared == 0.0 {
+        return (point - start).magnitude();
+    }
+    let t = ((point - start).x * segment.x + (point - start).y * segment.y) / length_squared;
+    let t = t.clamp(0.0, 1.0);
+    (point - (start + segment * t)).magnitude()
+}
@@ -0,0 +1,250 @@
+// Reads the JSON that Aseprite and TexturePacker save next to a sprite sheet image
+use crate::{FrameAnimation, ImageRect, Playback, Sprite};
+use indexmap::IndexMap;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use wee_common::WeeResult;
+
+const FRAMES_PER_SECOND: f32 = 60.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SheetFrame {
+    pub name: String,
+    pub rect: ImageRect,
+    pub duration_ms: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SheetTag {
+    pub name: String,
+    pub frames: Vec<usize>,
+    pub playback: Playback,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpriteSheet {
+    // The image filename relative to the JSON file
+    pub image: String,
+    pub frames: Vec<SheetFrame>,
+    pub tags: Vec<SheetTag>,
+}
+
+#[derive(Deserialize)]
+struct SheetJson {
+    frames: FramesJson,
+    meta: MetaJson,
+    // TexturePacker's Pixi exporter lists animations by frame name
+    #[serde(default)]
+    animations: BTreeMap<String, Vec<String>>,
+}
+
+// Aseprite and TexturePacker can both save frames as either a map or a list
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum FramesJson {
+    Hash(IndexMap<String, FrameJson>),
+    Array(Vec<NamedFrameJson>),
+}
+
+#[derive(Deserialize)]
+struct NamedFrameJson {
+    filename: String,
+    #[serde(flatten)]
+    frame: FrameJson,
+}
+
+#[derive(Deserialize)]
+struct FrameJson {
+    frame: RectJson,
+    #[serde(default)]
+    rotated: bool,
+    #[serde(default)]
+    trimmed: bool,
+    // Where the trimmed frame sits in the untrimmed one
+    #[serde(rename = "spriteSourceSize")]
+    sprite_source_size: Option<RectJson>,
+    #[serde(rename = "sourceSize")]
+    source_size: Option<SizeJson>,
+    duration: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct RectJson {
+    x: u32,
+    y: u32,
+    w: u32,
+    h: u32,
+}
+
+#[derive(Deserialize)]
+struct SizeJson {
+    w: u32,
+    h: u32,
+}
+
+impl FrameJson {
+    // Trimmed frames would need drawing with their offset inside the untrimmed size
+    fn is_trimmed(&self) -> bool {
+        let RectJson { w, h, .. } = self.frame;
+        let moved = self
+            .sprite_source_size
+            .as_ref()
+            .is_some_and(|source| (source.x, source.y, source.w, source.h) != (0, 0, w, h));
+        let resized = self
+            .source_size
+            .as_ref()
+            .is_some_and(|source| (source.w, source.h) != (w, h));
+        self.trimmed || moved || resized
+    }
+}
+
+#[derive(Deserialize)]
+struct MetaJson {
+    image: String,
+    #[serde(default, rename = "frameTags")]
+    frame_tags: Vec<TagJson>,
+}
+
+#[derive(Deserialize)]
+struct TagJson {
+    name: String,
+    from: usize,
+    to: usize,
+    #[serde(default)]
+    direction: String,
+}
+
+impl SpriteSheet {
+    pub fn from_json(json: &str) -> WeeResult<SpriteSheet> {
+        let sheet: SheetJson = serde_json::from_str(json)?;
+
+        let frames: Vec<(String, FrameJson)> = match sheet.frames {
+            FramesJson::Hash(frames) => frames.into_iter().collect(),
+            FramesJson::Array(frames) => frames
+                .into_iter()
+                .map(|frame| (frame.filename, frame.frame))
+                .collect(),
+        };
+        let mut sheet_frames = Vec::with_capacity(frames.len());
+        for (name, frame) in frames {
+            if frame.rotated {
+                return Err(format!(
+                    "Frame `{}` is rotated. Turn off rotation when exporting the sheet",
+                    name
+                )
+                .into());
+            }
+            if frame.is_trimmed() {
+                return Err(format!(
+                    "Frame `{}` is trimmed. Turn off trimming when exporting the sheet",
+                    name
+                )
+                .into());
+            }
+            let RectJson { x, y, w, h } = frame.frame;
+            sheet_frames.push(SheetFrame {
+                name,
+                rect: ImageRect::new(x, y, w, h),
+                duration_ms: frame.duration,
+            });
+        }
+
+        let mut tags = Vec::new();
+        for tag in sheet.meta.frame_tags {
+            if tag.from > tag.to || tag.to >= sheet_frames.len() {
+                return Err(format!(
+                    "Tag `{}` uses frames {} to {} but the sheet only has {} frames",
+                    tag.name,
+                    tag.from,
+                    tag.to,
+                    sheet_frames.len()
+                )
+                .into());
+            }
+            let mut frames: Vec<usize> = (tag.from..=tag.to).collect();
+            let playback = match tag.direction.as_str() {
+                "reverse" => Playback::Reverse,
+                "pingpong" => Playback::PingPong,
+                "pingpong_reverse" => {
+                    frames.reverse();
+                    Playback::PingPong
+                }
+                _ => Playback::Forward,
+            };
+            tags.push(SheetTag {
+                name: tag.name,
+                frames,
+                playback,
+            });
+        }
+        for (name, frame_names) in sheet.animations {
+            let mut frames = Vec::with_capacity(frame_names.len());
+            for frame_name in frame_names {
+                let index = sheet_frames
+                    .iter()
+                    .position(|frame| frame.name == frame_name)
+                    .ok_or_else(|| {
+                        format!("Animation `{}` uses missing frame `{}`", name, frame_name)
+                    })?;
+                frames.push(index);
+            }
+            tags.push(SheetTag {
+                name,
+                frames,
+                playback: Playback::Forward,
+            });
+        }
+
+        Ok(SpriteSheet {
+            image: sheet.meta.image,
+            frames: sheet_frames,
+            tags,
+        })
+    }
+
+    // One animation per tag named after the sheet and tag, or one of every frame if untagged
+    pub fn animations(&self, image_name: &str) -> Vec<(String, FrameAnimation)> {
+        if self.tags.is_empty() {
+            let frames: Vec<usize> = (0..self.frames.len()).collect();
+            return vec![(
+                image_name.to_string(),
+                self.animation(image_name, &frames, Playback::Forward),
+            )];
+        }
+        self.tags
+            .iter()
+            .map(|tag| {
+                (
+                    format!("{} {}", image_name, tag.name),
+                    self.animation(image_name, &tag.frames, tag.playback),
+                )
+            })
+            .collect()
+    }
+
+    fn animation(&self, image_name: &str, frames: &[usize], playback: Playback) -> FrameAnimation {
+        let frames: Vec<&SheetFrame> = frames.iter().map(|index| &self.frames[*index]).collect();
+        let sprites = frames
+            .iter()
+            .map(|frame| Sprite::Region {
+                image: image_name.to_string(),
+                rect: frame.rect,
+            })
+            .collect();
+        // Frames without durations play at the speed chosen in the editor
+        let frame_durations = frames
+            .iter()
+            .map(|frame| frame.duration_ms.map(ms_to_frames))
+            .collect::<Option<Vec<u32>>>()
+            .unwrap_or_default();
+        FrameAnimation {
+            sprites,
+            frame_durations,
+            playback,
+        }
+    }
+}
+
+fn ms_to_frames(ms: u32) -> u32 {
+    (ms as f32 * FRAMES_PER_SECOND / 1000.0).round().max(1.0) as u32
+}
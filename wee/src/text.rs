@@ -0,0 +1,243 @@
+// Lays text out in an object's area so every frontend wraps and aligns it the same way
+use crate::JustifyText;
+use serde::{Deserialize, Serialize};
+use wee_common::{Colour, Size, Vec2, AABB};
+
+// Text is never shrunk further than this, so it stays readable and layout always finishes
+const MIN_TEXT_SCALE: f32 = 0.1;
+const OUTLINE_DIRECTIONS: usize = 8;
+
+// What a frontend knows about a font at the size it was loaded at
+pub trait FontMetrics {
+    fn text_width(&self, text: &str) -> f32;
+    // The distance from the top of one line to the top of the next
+    fn line_height(&self) -> f32;
+}
+
+impl<T: FontMetrics + ?Sized> FontMetrics for &T {
+    fn text_width(&self, text: &str) -> f32 {
+        (**self).text_width(text)
+    }
+
+    fn line_height(&self) -> f32 {
+        (**self).line_height()
+    }
+}
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq)]
+pub enum VerticalAlign {
+    Top,
+    Middle,
+    Bottom,
+}
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TextOutline {
+    pub colour: Colour,
+    pub width: f32,
+}
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TextShadow {
+    pub colour: Colour,
+    pub offset: Vec2,
+}
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct TextLayout {
+    // Breaks lines between words so they fit the object's width
+    pub wrap: bool,
+    pub vertical_align: VerticalAlign,
+    // How many line heights apart each line is
+    pub line_spacing: f32,
+    // Scales the text down until it fits inside the object
+    pub shrink_to_fit: bool,
+    pub outline: Option<TextOutline>,
+    pub shadow: Option<TextShadow>,
+}
+
+impl Default for TextLayout {
+    fn default() -> TextLayout {
+        TextLayout {
+            wrap: false,
+            vertical_align: VerticalAlign::Middle,
+            line_spacing: 1.0,
+            shrink_to_fit: false,
+            outline: None,
+            shadow: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextLine {
+    pub text: String,
+    // Where the line goes before the object is rotated
+    pub area: AABB,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LaidOutText {
+    pub lines: Vec<TextLine>,
+    // How much the font is scaled, less than 1 when the text was shrunk to fit
+    pub scale: f32,
+}
+
+impl LaidOutText {
+    // The size of the area covering every line
+    pub fn size(&self) -> Size {
+        let mut lines = self.lines.iter();
+        let first = match lines.next() {
+            Some(line) => line.area,
+            None => return Size::new(0.0, 0.0),
+        };
+        let bounds = lines.fold(first, |bounds, line| {
+            AABB::new(
+                bounds.min.x.min(line.area.min.x),
+                bounds.min.y.min(line.area.min.y),
+                bounds.max.x.max(line.area.max.x),
+                bounds.max.y.max(line.area.max.y),
+            )
+        });
+        Size::new(bounds.width(), bounds.height())
+    }
+}
+
+impl TextLayout {
+    pub fn lay_out(
+        &self,
+        text: &str,
+        area: AABB,
+        justify: JustifyText,
+        metrics: &impl FontMetrics,
+    ) -> LaidOutText {
+        let line_spacing = self.line_spacing.max(0.0);
+        let height_of = |count: usize, scale: f32| {
+            let line_height = metrics.line_height() * scale;
+            if count == 0 {
+                0.0
+            } else {
+                line_height + line_height * line_spacing * (count - 1) as f32
+            }
+        };
+
+        let mut scale = 1.0;
+        let mut lines;
+        loop {
+            let max_width = if self.wrap {
+                Some(area.width() / scale)
+            } else {
+                None
+            };
+            lines = break_lines(text, max_width, metrics);
+            if !self.shrink_to_fit || scale <= MIN_TEXT_SCALE {
+                break;
+            }
+            let widest = lines
+                .iter()
+                .map(|line| metrics.text_width(line) * scale)
+                .fold(0.0, f32::max);
+            let height = height_of(lines.len(), scale);
+            let fit = |length: f32, available: f32| {
+                if length > available && length > 0.0 {
+                    available.max(0.0) / length
+                } else {
+                    1.0
+                }
+            };
+            let shrink = fit(widest, area.width()).min(fit(height, area.height()));
+            if shrink >= 1.0 {
+                break;
+            }
+            // Wrapped lines can change when the text shrinks, so go a little at a time
+            scale = (scale * shrink.min(0.95)).max(MIN_TEXT_SCALE);
+        }
+
+        let line_height = metrics.line_height() * scale;
+        let total_height = height_of(lines.len(), scale);
+        let top = match self.vertical_align {
+            VerticalAlign::Top => area.min.y,
+            VerticalAlign::Middle => (area.min.y + area.max.y - total_height) / 2.0,
+            VerticalAlign::Bottom => area.max.y - total_height,
+        };
+        let lines = lines
+            .into_iter()
+            .enumerate()
+            .map(|(index, text)| {
+                let width = metrics.text_width(&text) * scale;
+                let left = match justify {
+                    JustifyText::Left => area.min.x,
+                    JustifyText::Centre => (area.min.x + area.max.x - width) / 2.0,
+                    JustifyText::Right => area.max.x - width,
+                };
+                let y = top + line_height * line_spacing * index as f32;
+                TextLine {
+                    text,
+                    area: AABB::new(left, y, left + width, y + line_height),
+                }
+            })
+            .collect();
+        LaidOutText { lines, scale }
+    }
+
+    // How far each copy of the text is drawn from where the text goes, with its colour.
+    // The shadow is drawn first, then the outline, then the text itself on top
+    pub fn passes(&self, colour: Colour, scale: f32) -> Vec<(Vec2, Colour)> {
+        let mut passes = Vec::new();
+        if let Some(shadow) = self.shadow {
+            passes.push((shadow.offset * scale, shadow.colour));
+        }
+        if let Some(outline) = self.outline {
+            let width = outline.width * scale;
+            if width > 0.0 {
+                for i in 0..OUTLINE_DIRECTIONS {
+                    let angle = std::f32::consts::PI * 2.0 * i as f32 / OUTLINE_DIRECTIONS as f32;
+                    passes.push((Vec2::new(angle.cos(), angle.sin()) * width, outline.colour));
+                }
+            }
+        }
+        passes.push((Vec2::zero(), colour));
+        passes
+    }
+}
+
+// Splits at newlines, then between words when a line is wider than the maximum width.
+// Words too long for a line of their own are split between letters
+fn break_lines(text: &str, max_width: Option<f32>, metrics: &impl FontMetrics) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let max_width = match max_width {
+            Some(max_width) => max_width,
+            None => {
+                lines.push(paragraph.to_string());
+                continue;
+            }
+        };
+        let mut line = String::new();
+        for word in paragraph.split(' ') {
+            let joined = if line.is_empty() {
+                word.to_string()
+            } else {
+                format!("{} {}", line, word)
+            };
+            if metrics.text_width(&joined) <= max_width {
+                line = joined;
+                continue;
+            }
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            for letter in word.chars() {
+                line.push(letter);
+                if line.chars().count() > 1 && metrics.text_width(&line) > max_width {
+                    line.pop();
+                    lines.push(std::mem::take(&mut line));
+                    line.push(letter);
+                }
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
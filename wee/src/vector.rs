@@ -0,0 +1,370 @@
+use crate::{CollisionShape, SpriteTransform, MAX_POLYGON_POINTS};
+use serde::{Deserialize, Serialize};
+use wee_common::{Colour, Size, Vec2, AABB};
+
+const ELLIPSE_SEGMENTS: usize = 48;
+const CORNER_SEGMENTS: usize = 8;
+// Stops sharp corners in outlines from sticking out a long way
+const MITER_LIMIT: f32 = 4.0;
+
+// A sprite drawn from a shape stretched over the object's area instead of an image
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VectorSprite {
+    pub shape: VectorShape,
+    pub fill: Fill,
+    pub stroke: Option<Stroke>,
+}
+
+// Points are fractions of the object's size, from (0, 0) at the top left to (1, 1)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum VectorShape {
+    // A circle when the object is square
+    Ellipse,
+    RoundedRectangle { radius: f32 },
+    // Only drawn by the stroke
+    Line { start: Vec2, end: Vec2 },
+    Polygon { points: Vec<Vec2> },
+}
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq)]
+pub enum Fill {
+    None,
+    Solid(Colour),
+    Gradient {
+        start: Colour,
+        end: Colour,
+        direction: GradientDirection,
+    },
+}
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq)]
+pub enum GradientDirection {
+    // From the left edge to the right
+    Horizontal,
+    // From the top edge to the bottom
+    Vertical,
+}
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Stroke {
+    pub colour: Colour,
+    pub width: f32,
+}
+
+// Every three vertices make a triangle
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ShapeVertex {
+    pub position: Vec2,
+    pub colour: Colour,
+}
+
+impl VectorSprite {
+    pub fn new(shape: VectorShape, colour: Colour) -> VectorSprite {
+        VectorSprite {
+            shape,
+            fill: Fill::Solid(colour),
+            stroke: None,
+        }
+    }
+
+    // Triangles on screen for the fill then the stroke
+    pub fn triangles(&self, transform: &SpriteTransform) -> Vec<ShapeVertex> {
+        let size = Size::new(transform.area.width(), transform.area.height());
+        let outline = self.outline(size);
+        let mut vertices = Vec::new();
+
+        let is_line = matches!(self.shape, VectorShape::Line { .. });
+
+        if !is_line && !matches!(self.fill, Fill::None) {
+            for triangle in triangulate(&outline) {
+                for point in triangle.iter() {
+                    vertices.push(ShapeVertex {
+                        position: transform.to_screen(*point),
+                        colour: self.fill.colour_at(*point, size),
+                    });
+                }
+            }
+        }
+
+        if let Some(stroke) = self.stroke {
+            for point in stroke_triangles(&outline, stroke.width, !is_line) {
+                vertices.push(ShapeVertex {
+                    position: transform.to_screen(point),
+                    colour: stroke.colour,
+                });
+            }
+        }
+        vertices
+    }
+
+    // Points around the edge in pixels from the object's top left
+    pub fn outline(&self, size: Size) -> Vec<Vec2> {
+        let scale = |point: &Vec2| Vec2::new(point.x * size.width, point.y * size.height);
+        match &self.shape {
+            VectorShape::Ellipse => {
+                let radius = Vec2::new(size.width / 2.0, size.height / 2.0);
+                ellipse_points(radius, radius, ELLIPSE_SEGMENTS)
+            }
+            VectorShape::RoundedRectangle { radius } => {
+                let radius = radius.clamp(0.0, size.width.min(size.height) / 2.0);
+                if radius == 0.0 {
+                    return rectangle_points(size);
+                }
+                let quarter_turn = std::f32::consts::FRAC_PI_2;
+                let corners = [
+                    (Vec2::new(radius, radius), 2.0),
+                    (Vec2::new(size.width - radius, radius), 3.0),
+                    (Vec2::new(size.width - radius, size.height - radius), 0.0),
+                    (Vec2::new(radius, size.height - radius), 1.0),
+                ];
+                corners
+                    .iter()
+                    .flat_map(|(centre, turns)| {
+                        (0..=CORNER_SEGMENTS).map(move |i| {
+                            let angle = quarter_turn * (turns + i as f32 / CORNER_SEGMENTS as f32);
+                            *centre + Vec2::new(angle.cos(), angle.sin()) * radius
+                        })
+                    })
+                    .collect()
+            }
+            VectorShape::Line { start, end } => vec![scale(start), scale(end)],
+            VectorShape::Polygon { points } => points.iter().map(scale).collect(),
+        }
+    }
+
+    // Relative to the object's top left, the same as a collision shape set in the editor
+    pub fn collision_shape(&self, size: Size) -> CollisionShape {
+        let whole_area = CollisionShape::AABB(AABB::new(0.0, 0.0, size.width, size.height));
+        match &self.shape {
+            VectorShape::Ellipse => {
+                let radius = Vec2::new(size.width / 2.0, size.height / 2.0);
+                if (size.width - size.height).abs() < 0.5 {
+                    CollisionShape::Circle {
+                        centre: radius,
+                        radius: radius.x,
+                    }
+                } else {
+                    CollisionShape::Polygon {
+                        points: ellipse_points(radius, radius, MAX_POLYGON_POINTS),
+                    }
+                }
+            }
+            VectorShape::RoundedRectangle { radius } => {
+                let radius = radius.clamp(0.0, size.width.min(size.height) / 2.0);
+                if radius == 0.0 {
+                    return whole_area;
+                }
+                // Cut the corners along the line touching the middle of each curve
+                let cut = radius * (2.0 - std::f32::consts::SQRT_2);
+                let (width, height) = (size.width, size.height);
+                CollisionShape::Polygon {
+                    points: vec![
+                        Vec2::new(cut, 0.0),
+                        Vec2::new(width - cut, 0.0),
+                        Vec2::new(width, cut),
+                        Vec2::new(width, height - cut),
+                        Vec2::new(width - cut, height),
+                        Vec2::new(cut, height),
+                        Vec2::new(0.0, height - cut),
+                        Vec2::new(0.0, cut),
+                    ],
+                }
+            }
+            VectorShape::Line { .. } => {
+                let outline = self.outline(size);
+                let width = self.stroke.map_or(1.0, |stroke| stroke.width);
+                CollisionShape::Capsule {
+                    start: outline[0],
+                    end: outline[1],
+                    radius: width / 2.0,
+                }
+            }
+            VectorShape::Polygon { points } if points.is_empty() => whole_area,
+            VectorShape::Polygon { points } => {
+                let outline = CollisionShape::Polygon {
+                    points: self.outline(size),
+                };
+                // Collision polygons can't have as many points as drawn ones and have to be
+                // convex, so other polygons collide with their bounds instead
+                let fits = (3..=MAX_POLYGON_POINTS).contains(&points.len());
+                if fits && is_convex(points) {
+                    outline
+                } else {
+                    CollisionShape::AABB(outline.bounds())
+                }
+            }
+        }
+    }
+}
+
+impl Fill {
+    pub fn colour_at(&self, point: Vec2, size: Size) -> Colour {
+        match *self {
+            Fill::None => Colour::rgba(0.0, 0.0, 0.0, 0.0),
+            Fill::Solid(colour) => colour,
+            Fill::Gradient {
+                start,
+                end,
+                direction,
+            } => {
+                let amount = match direction {
+                    GradientDirection::Horizontal if size.width > 0.0 => point.x / size.width,
+                    GradientDirection::Vertical if size.height > 0.0 => point.y / size.height,
+                    _ => 0.0,
+                };
+                let amount = amount.clamp(0.0, 1.0);
+                let mix = |a: f32, b: f32| a + (b - a) * amount;
+                Colour::rgba(
+                    mix(start.r, end.r),
+                    mix(start.g, end.g),
+                    mix(start.b, end.b),
+                    mix(start.a, end.a),
+                )
+            }
+        }
+    }
+}
+
+fn ellipse_points(centre: Vec2, radius: Vec2, segments: usize) -> Vec<Vec2> {
+    (0..segments)
+        .map(|i| {
+            let angle = std::f32::consts::PI * 2.0 * i as f32 / segments as f32;
+            centre + Vec2::new(angle.cos() * radius.x, angle.sin() * radius.y)
+        })
+        .collect()
+}
+
+fn rectangle_points(size: Size) -> Vec<Vec2> {
+    vec![
+        Vec2::zero(),
+        Vec2::new(size.width, 0.0),
+        Vec2::new(size.width, size.height),
+        Vec2::new(0.0, size.height),
+    ]
+}
+
+fn cross(a: Vec2, b: Vec2) -> f32 {
+    a.x * b.y - a.y * b.x
+}
+
+// Every corner turns the same way. Points in a straight line don't count as turning
+fn is_convex(points: &[Vec2]) -> bool {
+    let mut turns = (0..points.len()).map(|i| {
+        let (a, b, c) = (
+            points[i],
+            points[(i + 1) % points.len()],
+            points[(i + 2) % points.len()],
+        );
+        cross(b - a, c - b)
+    });
+    let mut direction = 0.0;
+    turns.all(|turn| {
+        if turn * direction < 0.0 {
+            return false;
+        }
+        if turn != 0.0 {
+            direction = turn;
+        }
+        true
+    })
+}
+
+// Ear clipping, so polygons that aren't convex are filled properly too
+fn triangulate(points: &[Vec2]) -> Vec<[Vec2; 3]> {
+    let mut remaining: Vec<Vec2> = points.to_vec();
+    let area: f32 = (0..remaining.len())
+        .map(|i| cross(remaining[i], remaining[(i + 1) % remaining.len()]))
+        .sum();
+    if area < 0.0 {
+        remaining.reverse();
+    }
+
+    let mut triangles = Vec::new();
+    while remaining.len() > 3 {
+        let count = remaining.len();
+        let ear = (0..count).find(|&i| {
+            let (a, b, c) = (
+                remaining[(i + count - 1) % count],
+                remaining[i],
+                remaining[(i + 1) % count],
+            );
+            cross(b - a, c - b) > 0.0
+                && !remaining.iter().any(|&point| {
+                    point != a && point != b && point != c && inside_triangle(point, a, b, c)
+                })
+        });
+        // Points in a line or a polygon crossing itself leave no ears, so fan out the rest
+        let ear = match ear {
+            Some(ear) => ear,
+            None => break,
+        };
+        triangles.push([
+            remaining[(ear + count - 1) % count],
+            remaining[ear],
+            remaining[(ear + 1) % count],
+        ]);
+        remaining.remove(ear);
+    }
+    if remaining.len() >= 3 {
+        for i in 1..remaining.len() - 1 {
+            triangles.push([remaining[0], remaining[i], remaining[i + 1]]);
+        }
+    }
+    triangles
+}
+
+fn inside_triangle(point: Vec2, a: Vec2, b: Vec2, c: Vec2) -> bool {
+    cross(b - a, point - a) >= 0.0
+        && cross(c - b, point - b) >= 0.0
+        && cross(a - c, point - c) >= 0.0
+}
+
+// The stroke is centred on the outline with its corners joined
+fn stroke_triangles(points: &[Vec2], width: f32, closed: bool) -> Vec<Vec2> {
+    if points.len() < 2 || width <= 0.0 {
+        return Vec::new();
+    }
+    let half_width = width / 2.0;
+    let count = points.len();
+    let normal = |from: Vec2, to: Vec2| {
+        let direction = (to - from).unit();
+        Vec2::new(-direction.y, direction.x)
+    };
+    let offsets: Vec<Vec2> = (0..count)
+        .map(|i| {
+            let previous = if i > 0 || closed {
+                Some(normal(points[(i + count - 1) % count], points[i]))
+            } else {
+                None
+            };
+            let next = if i + 1 < count || closed {
+                Some(normal(points[i], points[(i + 1) % count]))
+            } else {
+                None
+            };
+            match (previous, next) {
+                (Some(previous), Some(next)) => {
+                    let miter = (previous + next).unit();
+                    let length = miter.x * next.x + miter.y * next.y;
+                    if length.abs() < 1.0 / MITER_LIMIT {
+                        next * half_width
+                    } else {
+                        miter * (half_width / length)
+                    }
+                }
+                (Some(normal), None) | (None, Some(normal)) => normal * half_width,
+                (None, None) => Vec2::zero(),
+            }
+        })
+        .collect();
+
+    let segments = if closed { count } else { count - 1 };
+    let mut triangles = Vec::with_capacity(segments * 6);
+    for i in 0..segments {
+        let j = (i + 1) % count;
+        let (outer_a, inner_a) = (points[i] + offsets[i], points[i] - offsets[i]);
+        let (outer_b, inner_b) = (points[j] + offsets[j], points[j] - offsets[j]);
+        triangles.extend_from_slice(&[outer_a, outer_b, inner_b, outer_a, inner_b, inner_a]);
+    }
+    triangles
+}